@@ -0,0 +1,95 @@
+//! Benchmarks of reading columns from the data sources and rendering them
+//!
+//! Run with `cargo bench --bench data`.
+
+use std::{collections::HashMap, env, hint::black_box};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use gongbi::{aes, data::Data, geom_point, plot};
+use polars::prelude::*;
+
+/// The sizes of the data, in rows
+const ROWS: [usize; 2] = [100_000, 1_000_000];
+
+fn values(rows: usize) -> Vec<f64> {
+    (0..rows).map(|i| (i as f64 * 0.001).sin()).collect()
+}
+
+fn data_frame(rows: usize) -> DataFrame {
+    df!(
+        "x" => (0..rows).map(|i| i as f64).collect::<Vec<_>>(),
+        "y" => values(rows),
+        "n" => (0..rows).map(|i| i as i32).collect::<Vec<_>>(),
+    )
+    .unwrap()
+}
+
+fn column_f64(c: &mut Criterion) {
+    let mut group = c.benchmark_group("column_f64");
+
+    for rows in ROWS {
+        let df = data_frame(rows);
+        let map = HashMap::from([("y", values(rows))]);
+
+        group.bench_with_input(BenchmarkId::new("data_frame", rows), &df, |b, df| {
+            b.iter(|| black_box(df.column_f64("y").len()))
+        });
+        group.bench_with_input(BenchmarkId::new("data_frame_cast", rows), &df, |b, df| {
+            b.iter(|| black_box(df.column_f64("n").len()))
+        });
+        group.bench_with_input(BenchmarkId::new("hash_map", rows), &map, |b, map| {
+            b.iter(|| black_box(map.column_f64("y").len()))
+        });
+    }
+
+    group.finish();
+}
+
+fn column_range_f64(c: &mut Criterion) {
+    let mut group = c.benchmark_group("column_range_f64");
+
+    for rows in ROWS {
+        let df = data_frame(rows);
+        let lf = df.clone().lazy();
+
+        group.bench_with_input(BenchmarkId::new("data_frame", rows), &df, |b, df| {
+            b.iter(|| black_box(df.column_range_f64("y")))
+        });
+        group.bench_with_input(BenchmarkId::new("lazy_frame", rows), &lf, |b, lf| {
+            b.iter(|| black_box(lf.column_range_f64("y")))
+        });
+    }
+
+    group.finish();
+}
+
+fn render_point(c: &mut Criterion) {
+    let file_path = env::temp_dir().join("gongbi-bench-point.svg");
+    let mut group = c.benchmark_group("render_point");
+    group.sample_size(10);
+
+    let rows = ROWS[0];
+    let plot = plot!(data_frame(rows), aes!(x, y)) + geom_point!();
+
+    group.bench_function(BenchmarkId::new("svg", rows), |b| {
+        b.iter(|| plot.to_svg(&file_path, (1024, 768)).unwrap())
+    });
+
+    // The expressions are evaluated in the query of the lazy frame for each new plot
+    let lf = data_frame(rows).lazy();
+    group.bench_function(BenchmarkId::new("svg_lazy_expr", rows), |b| {
+        b.iter(|| {
+            let plot = plot!(
+                lf.clone(),
+                aes!(x = col("x") / lit(1000.0), y = col("y") * col("y"))
+            ) + geom_point!();
+
+            plot.to_svg(&file_path, (1024, 768)).unwrap()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, column_f64, column_range_f64, render_point);
+criterion_main!(benches);
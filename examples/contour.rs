@@ -0,0 +1,42 @@
+use std::{env, path::PathBuf};
+
+use gongbi::{aes, geom_contour, geom_contour_filled, labs, plot, scale_fill_viridis_c};
+use polars::prelude::*;
+
+fn main() -> anyhow::Result<()> {
+    let examples_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("examples");
+
+    // Validation loss of a model on a grid of log10 learning rate and log10 weight decay
+    let (lr, decay): (Vec<f64>, Vec<f64>) = (0..41 * 41)
+        .map(|i| {
+            (
+                -5.0 + 4.0 * (i % 41) as f64 / 40.0,
+                -6.0 + 5.0 * (i / 41) as f64 / 40.0,
+            )
+        })
+        .unzip();
+    let loss: Vec<f64> = lr
+        .iter()
+        .zip(&decay)
+        .map(|(&lr, &decay)| {
+            let valley = (lr + 3.0 - 0.3 * (decay + 3.5)).powi(2) + 0.3 * (decay + 3.5).powi(2);
+            let ripple = 0.05 * (3.0 * lr).sin() * (2.0 * decay).cos();
+            0.2 + 0.1 * valley + ripple
+        })
+        .collect();
+    let df = df!("lr" => lr, "decay" => decay, "loss" => loss)?;
+
+    let plot = plot!(df.clone(), aes!(lr, decay, z = loss))
+        + geom_contour!(bins = 12)
+        + labs!(title = "Demo of geom_contour");
+    plot.to_svg(examples_dir.join("contour.svg"), (1024, 768))?;
+
+    let plot = plot!(df, aes!(lr, decay, z = loss))
+        + geom_contour_filled!(breaks = vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.75, 1.0, 1.5])
+        + geom_contour!(aes!(color = "white"), breaks = vec![0.2, 0.3, 0.4, 0.5])
+        + scale_fill_viridis_c!()
+        + labs!(title = "Demo of geom_contour_filled");
+    plot.to_svg(examples_dir.join("contour_filled.svg"), (1024, 768))?;
+
+    Ok(())
+}
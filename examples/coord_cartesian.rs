@@ -0,0 +1,29 @@
+use std::{env, path::PathBuf};
+
+use gongbi::{aes, coord_fixed, coord_flip, geom_abline, geom_bar, geom_point, labs, plot};
+use polars::prelude::*;
+
+fn main() -> anyhow::Result<()> {
+    let examples_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("examples");
+    let mpg = examples_dir.join("mpg.csv");
+
+    let mpg = CsvReadOptions::default()
+        .with_has_header(true)
+        .try_into_reader_with_file_path(Some(mpg))?
+        .finish()?;
+
+    let plot = plot!(mpg.clone(), aes!(cyl, fill = drv))
+        + geom_bar!()
+        + coord_flip!()
+        + labs!(title = "Demo of coord_flip");
+    plot.to_svg(examples_dir.join("mpg_bar_flip.svg"), (1024, 768))?;
+
+    let plot = plot!(mpg, aes!(cty, hwy))
+        + geom_abline!(aes!(color = "gray"))
+        + geom_point!()
+        + coord_fixed!()
+        + labs!(title = "Demo of coord_fixed");
+    plot.to_svg(examples_dir.join("mpg_fixed.svg"), (1024, 768))?;
+
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use std::{env, path::PathBuf};
+
+use gongbi::{aes, coord_polar, geom_bar, geom_hline, geom_line, geom_point, labs, plot};
+use polars::prelude::*;
+
+fn main() -> anyhow::Result<()> {
+    let examples_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("examples");
+    let mpg = examples_dir.join("mpg.csv");
+
+    let mpg = CsvReadOptions::default()
+        .with_has_header(true)
+        .try_into_reader_with_file_path(Some(mpg))?
+        .finish()?;
+
+    let plot =
+        plot!(mpg.clone(), aes!(cyl, fill = drv)) + geom_bar!() + labs!(title = "Demo of geom_bar");
+    plot.to_svg(examples_dir.join("mpg_bar.svg"), (1024, 768))?;
+
+    let plot = plot!(mpg.clone(), aes!(fill = class))
+        + geom_bar!(aes!(color = "white"), width = 1.0)
+        + coord_polar!(theta = "y")
+        + labs!(title = "Demo of coord_polar: pie chart");
+    plot.to_svg(examples_dir.join("mpg_pie.svg"), (1024, 768))?;
+
+    let plot = plot!(mpg, aes!(fill = drv))
+        + geom_bar!(aes!(color = "white"), width = 1.0)
+        + coord_polar!(theta = "y", inner_radius = 0.5)
+        + labs!(title = "Demo of coord_polar: donut chart");
+    plot.to_svg(examples_dir.join("mpg_donut.svg"), (1024, 768))?;
+
+    // Scores of two models on six benchmarks
+    let df = df!(
+        "benchmark" => [1, 2, 3, 4, 5, 6, 1, 2, 3, 4, 5, 6],
+        "score" => [72.0, 85.0, 64.0, 90.0, 58.0, 77.0, 80.0, 70.0, 75.0, 82.0, 69.0, 88.0],
+        "model" => ["A", "A", "A", "A", "A", "A", "B", "B", "B", "B", "B", "B"],
+    )?;
+
+    let plot = plot!(df, aes!(benchmark, score, color = model))
+        + geom_hline!(
+            aes!(color = "gray"),
+            yintercept = vec![0.0, 100.0],
+            expand = true
+        )
+        + geom_line!()
+        + geom_point!()
+        + coord_polar!()
+        + labs!(title = "Demo of coord_polar: radar chart");
+    plot.to_svg(examples_dir.join("radar.svg"), (1024, 768))?;
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use std::{env, path::PathBuf};
+
+use gongbi::{
+    aes, geom_bin2d, geom_density_2d, geom_density_2d_filled, geom_hex, labs, plot,
+    scale_fill_viridis_c,
+};
+use polars::prelude::*;
+
+fn main() -> anyhow::Result<()> {
+    let examples_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("examples");
+
+    // Two overlapping gaussian clusters of 20000 points
+    let mut rng = Lcg(42);
+    let (x, y): (Vec<f64>, Vec<f64>) = (0..20000)
+        .map(|i| {
+            let (a, b) = rng.gaussian();
+            if i % 3 == 0 {
+                (3.0 + 0.6 * a, 1.0 + 0.4 * b)
+            } else {
+                (a, 0.8 * a + 0.6 * b)
+            }
+        })
+        .unzip();
+    let df = df!("x" => x, "y" => y)?;
+
+    let plot = plot!(df.clone(), aes!(x, y))
+        + geom_density_2d!()
+        + labs!(title = "Demo of geom_density_2d");
+    plot.to_svg(examples_dir.join("density_2d.svg"), (1024, 768))?;
+
+    let plot = plot!(df.clone(), aes!(x, y))
+        + geom_density_2d_filled!()
+        + scale_fill_viridis_c!()
+        + labs!(title = "Demo of geom_density_2d_filled");
+    plot.to_svg(examples_dir.join("density_2d_filled.svg"), (1024, 768))?;
+
+    let plot = plot!(df.clone(), aes!(x, y))
+        + geom_bin2d!(bins = 40)
+        + labs!(title = "Demo of geom_bin2d");
+    plot.to_svg(examples_dir.join("bin2d.svg"), (1024, 768))?;
+
+    let plot = plot!(df, aes!(x, y))
+        + geom_hex!(bins = 30)
+        + scale_fill_viridis_c!()
+        + labs!(title = "Demo of geom_hex");
+    plot.to_svg(examples_dir.join("hex.svg"), (1024, 768))?;
+
+    Ok(())
+}
+
+/// A tiny linear congruential generator, to keep the example reproducible
+struct Lcg(u64);
+
+impl Lcg {
+    fn uniform(&mut self) -> f64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.0 >> 11) as f64 + 0.5) / (1u64 << 53) as f64
+    }
+
+    /// Two independent standard normal values by the Box-Muller transform
+    fn gaussian(&mut self) -> (f64, f64) {
+        let (u, v) = (self.uniform(), self.uniform());
+        let r = (-2.0 * u.ln()).sqrt();
+        let theta = 2.0 * std::f64::consts::PI * v;
+
+        (r * theta.cos(), r * theta.sin())
+    }
+}
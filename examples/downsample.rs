@@ -0,0 +1,48 @@
+use std::{collections::HashMap, env, path::PathBuf, sync::Arc};
+
+use gongbi::{aes, geom_line, geom_point, labs, plot};
+
+fn main() -> anyhow::Result<()> {
+    let examples_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("examples");
+
+    // A million samples of a noisy signal over 100 seconds
+    let mut state: u64 = 42;
+    let mut noise = move || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (state >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+    };
+
+    let n = 1_000_000;
+    let t: Vec<f64> = (0..n).map(|i| i as f64 * 1e-4).collect();
+    let signal: Vec<f64> = t
+        .iter()
+        .map(|t| (t / 8.0).sin() * 2.0 + (t * 1.7).sin() * 0.5 + noise())
+        .collect();
+
+    // The same samples read by a coarse sensor, at each second by steps of 0.25
+    let readings = HashMap::from([
+        ("t", t.iter().map(|t| t.round()).collect::<Vec<_>>()),
+        (
+            "signal",
+            signal.iter().map(|y| (y * 4.0).round() / 4.0).collect(),
+        ),
+    ]);
+
+    let plot = plot!(
+        HashMap::from([("t", t), ("signal", signal)]),
+        aes!(t, signal)
+    ) + geom_line!(downsample = "minmax")
+        + geom_point!(
+            aes!(color = "red", size = 3),
+            data = Arc::new(readings),
+            dedup = true
+        )
+        + labs!(
+            title = "Demo of downsampling",
+            subtitle = "A million samples drawn at the pixels of the output"
+        );
+
+    plot.to_svg(examples_dir.join("downsample.svg"), (1024, 768))?;
+
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use std::{env, path::PathBuf};
+
+use gongbi::{aes, geom_raster, geom_tile, labs, plot, scale_fill_gradientn};
+use polars::prelude::*;
+
+fn main() -> anyhow::Result<()> {
+    let examples_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("examples");
+    let mpg = examples_dir.join("mpg.csv");
+
+    let mpg = CsvReadOptions::default()
+        .with_has_header(true)
+        .try_into_reader_with_file_path(Some(mpg))?
+        .finish()?;
+
+    // Correlation matrix of the numeric columns
+    let columns = ["displ", "year", "cyl", "cty", "hwy"];
+    let values: Vec<Vec<f64>> = columns
+        .iter()
+        .map(|c| {
+            mpg.column(c)
+                .unwrap()
+                .cast(&DataType::Float64)
+                .unwrap()
+                .f64()
+                .unwrap()
+                .into_no_null_iter()
+                .collect()
+        })
+        .collect();
+
+    let (mut x, mut y, mut corr) = (vec![], vec![], vec![]);
+    for (i, a) in values.iter().enumerate() {
+        for (j, b) in values.iter().enumerate() {
+            x.push(i as u32);
+            y.push(j as u32);
+            corr.push(correlation(a, b));
+        }
+    }
+    let df = df!("x" => x, "y" => y, "corr" => corr)?;
+
+    let plot = plot!(df, aes!(x, y, fill = corr))
+        + geom_tile!(aes!(color = "white"))
+        + scale_fill_gradientn!(colors = ["blue", "white", "red"], limits = (-1.0, 1.0))
+        + labs!(title = "Demo of geom_tile");
+
+    plot.to_svg(examples_dir.join("heatmap_tile.svg"), (1024, 768))?;
+
+    // Utilization per hour of a week
+    let (hour, day): (Vec<u32>, Vec<u32>) = (0..7 * 24).map(|i| (i % 24, i / 24)).unzip();
+    let usage: Vec<f64> = hour
+        .iter()
+        .zip(&day)
+        .map(|(&h, &d)| {
+            let daily = (std::f64::consts::PI * (h as f64 - 3.0) / 24.0)
+                .sin()
+                .max(0.0);
+            let weekly = if d < 5 { 1.0 } else { 0.4 };
+            daily * weekly
+        })
+        .collect();
+    let df = df!("hour" => hour, "day" => day, "usage" => usage)?;
+
+    let plot = plot!(df, aes!(hour, day, fill = usage))
+        + geom_raster!()
+        + labs!(title = "Demo of geom_raster");
+
+    plot.to_svg(examples_dir.join("heatmap_raster.svg"), (1024, 768))?;
+
+    Ok(())
+}
+
+fn correlation(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len() as f64;
+    let (mean_a, mean_b) = (a.iter().sum::<f64>() / n, b.iter().sum::<f64>() / n);
+    let cov: f64 = a
+        .iter()
+        .zip(b)
+        .map(|(a, b)| (a - mean_a) * (b - mean_b))
+        .sum();
+    let var_a: f64 = a.iter().map(|a| (a - mean_a).powi(2)).sum();
+    let var_b: f64 = b.iter().map(|b| (b - mean_b).powi(2)).sum();
+
+    cov / (var_a * var_b).sqrt()
+}
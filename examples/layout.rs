@@ -0,0 +1,36 @@
+use std::{env, path::PathBuf};
+
+use gongbi::{aes, geom_bar, geom_density, geom_point, labs, plot, plot_layout, wrap_plots};
+use polars::prelude::*;
+
+fn main() -> anyhow::Result<()> {
+    let examples_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("examples");
+    let mpg = examples_dir.join("mpg.csv");
+
+    let mpg = CsvReadOptions::default()
+        .with_has_header(true)
+        .try_into_reader_with_file_path(Some(mpg))?
+        .finish()?;
+
+    let point = plot!(mpg.clone(), aes!(displ, hwy, color = drv))
+        + geom_point!()
+        + labs!(title = "Highway mileage");
+    let density = plot!(mpg.clone(), aes!(hwy, color = drv, fill = drv))
+        + geom_density!(alpha = 0.3)
+        + labs!(y = "density", title = "Distribution");
+    let bar = plot!(mpg.clone(), aes!(cyl, fill = drv))
+        + geom_bar!()
+        + labs!(y = "count", title = "Cylinders");
+
+    let layout = (point.clone() | density.clone()) / bar.clone()
+        + plot_layout!(heights = [3.0, 2.0], guides = "collect", tag_levels = "A");
+
+    layout.to_svg(examples_dir.join("mpg_layout.svg"), (1280, 960))?;
+
+    let layout =
+        wrap_plots!(point, density, bar) + plot_layout!(widths = [3.0, 2.0], tag_levels = "i");
+
+    layout.to_svg(examples_dir.join("mpg_layout_wrap.svg"), (1280, 960))?;
+
+    Ok(())
+}
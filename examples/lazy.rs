@@ -0,0 +1,23 @@
+use std::{env, path::PathBuf};
+
+use gongbi::{aes, geom_point, labs, plot};
+use polars::prelude::*;
+
+fn main() -> anyhow::Result<()> {
+    let examples_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("examples");
+    let mpg = examples_dir.join("mpg.csv");
+
+    // Only the columns used by the layers are read, and only the rows of 2008
+    let mpg = LazyCsvReader::new(mpg)
+        .with_has_header(true)
+        .finish()?
+        .filter(col("year").eq(lit(2008)));
+
+    let plot = plot!(mpg, aes!(displ, hwy, color = drv))
+        + geom_point!()
+        + labs!(title = "Demo of a LazyFrame", subtitle = "Cars of 2008");
+
+    plot.to_svg(examples_dir.join("mpg_lazy.svg"), (1024, 768))?;
+
+    Ok(())
+}
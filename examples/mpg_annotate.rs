@@ -0,0 +1,48 @@
+use std::{env, path::PathBuf};
+
+use gongbi::{aes, annotate, geom_point, labs, plot};
+use polars::prelude::*;
+
+fn main() -> anyhow::Result<()> {
+    let examples_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("examples");
+    let mpg = examples_dir.join("mpg.csv");
+
+    let mpg = CsvReadOptions::default()
+        .with_has_header(true)
+        .try_into_reader_with_file_path(Some(mpg))?
+        .finish()?;
+
+    let plot = plot!(mpg.clone(), aes!("displ", "hwy"))
+        + geom_point!()
+        + annotate!(
+            "rect",
+            xmin = 5.5,
+            xmax = 7.2,
+            ymin = 22,
+            ymax = 27,
+            alpha = 0.2,
+            fill = "blue"
+        )
+        + annotate!(
+            "rect",
+            xmin = f64::NEG_INFINITY,
+            xmax = f64::INFINITY,
+            ymin = 40,
+            ymax = 45,
+            alpha = 0.1
+        )
+        + annotate!(
+            "segment",
+            x = 6.0,
+            y = 32,
+            xend = 6.3,
+            yend = 27.5,
+            color = "red"
+        )
+        + annotate!("text", x = 6.0, y = 33, label = "2seater", color = "red")
+        + labs!(title = "Demo of annotate");
+
+    plot.to_svg(examples_dir.join("mpg_annotate.svg"), (1024, 768))?;
+
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use std::{env, path::PathBuf};
+
+use gongbi::{aes, geom_density, labs, plot};
+use polars::prelude::*;
+
+fn main() -> anyhow::Result<()> {
+    let examples_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("examples");
+    let mpg = examples_dir.join("mpg.csv");
+
+    let mpg = CsvReadOptions::default()
+        .with_has_header(true)
+        .try_into_reader_with_file_path(Some(mpg))?
+        .finish()?;
+
+    let plot = plot!(mpg.clone(), aes!(hwy))
+        + geom_density!(aes!(fill = "gray"), alpha = 0.5)
+        + labs!(y = "density", title = "Demo of geom_density");
+
+    plot.to_svg(examples_dir.join("mpg_density.svg"), (1024, 768))?;
+
+    let plot = plot!(mpg.clone(), aes!(hwy, color = drv, fill = drv))
+        + geom_density!(alpha = 0.3, adjust = 1.5)
+        + labs!(
+            y = "density",
+            title = "Demo of geom_density grouped by color"
+        );
+
+    plot.to_svg(examples_dir.join("mpg_density_color.svg"), (1024, 768))?;
+
+    Ok(())
+}
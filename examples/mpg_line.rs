@@ -12,7 +12,7 @@ fn main() -> anyhow::Result<()> {
         .try_into_reader_with_file_path(Some(mpg))?
         .finish()?;
 
-    let plot = plot!(mpg.clone(), aes!(hwy)) + geom_line!(aes!(label = "hwy"));
+    let plot = plot!(mpg.clone(), aes!(hwy)) + geom_line!(aes!(legend = "hwy"));
 
     plot.to_svg(examples_dir.join("mpg_line.svg"), (1024, 768))?;
 
@@ -0,0 +1,22 @@
+use std::{env, path::PathBuf};
+
+use gongbi::{aes, geom_point, labs, pdf::PageSize, plot};
+
+fn main() -> anyhow::Result<()> {
+    let examples_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("examples");
+
+    let plot = plot!(
+        csv = examples_dir.join("mpg.csv"),
+        mapping = aes!(displ, hwy, color = drv)
+    ) + geom_point!()
+        + labs!(
+            title = "Demo of PDF export",
+            x = "Engine displacement (L)",
+            y = "Highway miles per gallon",
+        );
+
+    // A figure on an A5 page in landscape orientation
+    plot.to_pdf(examples_dir.join("mpg_pdf.pdf"), PageSize::A5.landscape())?;
+
+    Ok(())
+}
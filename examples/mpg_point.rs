@@ -1,35 +1,54 @@
 use std::{env, path::PathBuf};
 
-use gongbi::{aes, geom_point, labs, plot};
+use gongbi::{aes, data::polars::read_csv, geom_point, labs, plot};
 use polars::prelude::*;
 
 fn main() -> anyhow::Result<()> {
     let examples_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("examples");
     let mpg = examples_dir.join("mpg.csv");
 
-    let mpg = CsvReadOptions::default()
-        .with_has_header(true)
-        .try_into_reader_with_file_path(Some(mpg))?
-        .finish()?;
-
-    let plot = plot!(mpg.clone(), aes!("displ", "hwy"))
+    let plot = plot!(csv = &mpg, mapping = aes!("displ", "hwy"))
         + geom_point!()
-        + labs!(caption = "Demo of geom_point");
+        + labs!(title = "Demo of geom_point");
 
     plot.to_svg(examples_dir.join("mpg_point.svg"), (1024, 768))?;
     // plot.to_png(examples_dir.join("mpg_point.png"), (1024, 768))?;
 
+    let mpg = read_csv(mpg)?;
+
     let plot = plot!(mpg.clone(), aes!("displ", "hwy", color = "blue"))
         + geom_point!()
-        + labs!(caption = "Demo of geom_point with color");
+        + labs!(title = "Demo of geom_point with color");
 
     plot.to_svg(examples_dir.join("mpg_point_color.svg"), (1024, 768))?;
 
     let plot = plot!(mpg.clone(), aes!("displ", "hwy"))
         + geom_point!(aes!(shape = 0))
-        + labs!(caption = "Demo of geom_point with shape");
+        + labs!(title = "Demo of geom_point with shape");
 
     plot.to_svg(examples_dir.join("mpg_point_shape.svg"), (1024, 768))?;
 
+    let plot = plot!(mpg.clone(), aes!(displ, hwy, color = drv))
+        + geom_point!()
+        + labs!(
+            title = "Demo of labs",
+            subtitle = "Fuel economy decreases with the engine size",
+            caption = "Data from fueleconomy.gov",
+            tag = "A",
+            x = "Engine displacement (L)",
+            y = "Highway miles per gallon",
+            color = "Drive train",
+        );
+
+    plot.to_svg(examples_dir.join("mpg_point_labs.svg"), (1024, 768))?;
+
+    let plot = plot!(
+        mpg.clone(),
+        aes!(x = col("displ"), y = col("hwy") - col("cty"))
+    ) + geom_point!()
+        + labs!(title = "Demo of geom_point with expressions");
+
+    plot.to_svg(examples_dir.join("mpg_point_expr.svg"), (1024, 768))?;
+
     Ok(())
 }
@@ -0,0 +1,25 @@
+use std::{env, path::PathBuf};
+
+use gongbi::{aes, geom_abline, geom_hline, geom_point, geom_vline, labs, plot};
+use polars::prelude::*;
+
+fn main() -> anyhow::Result<()> {
+    let examples_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("examples");
+    let mpg = examples_dir.join("mpg.csv");
+
+    let mpg = CsvReadOptions::default()
+        .with_has_header(true)
+        .try_into_reader_with_file_path(Some(mpg))?
+        .finish()?;
+
+    let plot = plot!(mpg.clone(), aes!("cty", "hwy"))
+        + geom_point!()
+        + geom_hline!(aes!(color = "red"), yintercept = 30)
+        + geom_vline!(aes!(shape = 2), xintercept = vec![15.0, 25.0])
+        + geom_abline!(aes!(color = "blue"), slope = 1.4, intercept = 0.0)
+        + labs!(title = "Demo of geom_hline, geom_vline and geom_abline");
+
+    plot.to_svg(examples_dir.join("mpg_reference.svg"), (1024, 768))?;
+
+    Ok(())
+}
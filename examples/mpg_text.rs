@@ -0,0 +1,41 @@
+use std::{env, path::PathBuf};
+
+use gongbi::{aes, geom_label, geom_point, geom_text, labs, plot};
+use polars::prelude::*;
+
+fn main() -> anyhow::Result<()> {
+    let examples_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("examples");
+    let mpg = examples_dir.join("mpg.csv");
+
+    let mpg = CsvReadOptions::default()
+        .with_has_header(true)
+        .try_into_reader_with_file_path(Some(mpg))?
+        .finish()?;
+
+    let plot = plot!(mpg.clone(), aes!("displ", "hwy"))
+        + geom_point!()
+        + geom_text!(
+            aes!(label = model),
+            vjust = 0.0,
+            nudge_y = 0.5,
+            check_overlap = true
+        )
+        + labs!(title = "Demo of geom_text");
+
+    plot.to_svg(examples_dir.join("mpg_text.svg"), (1024, 768))?;
+
+    let midsize = mpg
+        .clone()
+        .lazy()
+        .filter(col("class").eq(lit("midsize")))
+        .collect()?;
+
+    let plot = plot!(midsize, aes!("displ", "hwy"))
+        + geom_point!()
+        + geom_label!(aes!(label = model), vjust = 0.0, repel = true)
+        + labs!(title = "Demo of geom_label");
+
+    plot.to_svg(examples_dir.join("mpg_label.svg"), (1024, 768))?;
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use std::{collections::HashMap, env, path::PathBuf, sync::Arc};
+
+use gongbi::{aes, geom_line, geom_point, labs, plot, Data};
+
+/// A reading of a sensor, each field is a column
+#[derive(Data)]
+struct Reading {
+    hour: f64,
+    celsius: f64,
+    sensor: &'static str,
+}
+
+fn main() -> anyhow::Result<()> {
+    let examples_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("examples");
+
+    let readings: Vec<Reading> = ["indoor", "outdoor"]
+        .into_iter()
+        .zip([21.0, 12.0])
+        .flat_map(|(sensor, base)| {
+            (0..24).map(move |hour| Reading {
+                hour: hour as f64,
+                celsius: base
+                    + (base - 10.0) / 2.0
+                        * (std::f64::consts::PI * (hour as f64 - 9.0) / 12.0).sin(),
+                sensor,
+            })
+        })
+        .collect();
+
+    // The columns of a plain map, here the comfortable temperature
+    let comfort = HashMap::from([("hour", vec![0.0, 23.0]), ("celsius", vec![20.0, 20.0])]);
+
+    let plot = plot!(readings, aes!(hour, celsius, color = sensor))
+        + geom_line!()
+        + geom_point!()
+        + geom_line!(aes!(color = "black", shape = 2), data = Arc::new(comfort))
+        + labs!(
+            title = "Demo of plain Rust data",
+            subtitle = "A Vec of #[derive(Data)] structs and a HashMap of columns"
+        );
+
+    plot.to_svg(examples_dir.join("record.svg"), (1024, 768))?;
+
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use std::{env, path::PathBuf};
+
+use gongbi::{
+    aes, geom_line, geom_point, labs, plot, scale::format, scale_x_continuous, scale_y_continuous,
+};
+use polars::prelude::*;
+
+fn main() -> anyhow::Result<()> {
+    let examples_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("examples");
+
+    // The heap of a service over two hours, in bytes
+    let (second, heap): (Vec<f64>, Vec<f64>) = (0..=120)
+        .map(|minute| {
+            let t = minute as f64 * 60.0;
+            (t, 2.0e8 + 1.5e5 * t + 6.0e7 * (t / 900.0).sin())
+        })
+        .unzip();
+
+    let df = df!("second" => second, "heap" => heap)?;
+
+    let plot = plot!(df, aes!(second, heap))
+        + geom_line!()
+        + scale_x_continuous!(
+            breaks = vec![0.0, 1800.0, 3600.0, 5400.0, 7200.0],
+            minor_breaks = 16,
+            labels = format::duration()
+        )
+        + scale_y_continuous!(
+            breaks = (1..=5)
+                .map(|i| i as f64 * 256.0 * 1024.0 * 1024.0)
+                .collect::<Vec<_>>(),
+            labels = format::bytes()
+        )
+        + labs!(x = "uptime", title = "Demo of scale breaks and labels");
+    plot.to_svg(examples_dir.join("heap_breaks.svg"), (1024, 768))?;
+
+    // The share of requests served from the cache against the number of requests
+    let df = df!(
+        "requests" => [1.2e3, 8.5e3, 2.4e4, 7.7e4, 1.9e5, 4.6e5, 9.8e5],
+        "hit_rate" => [0.12, 0.31, 0.47, 0.62, 0.74, 0.81, 0.86],
+    )?;
+
+    let plot = plot!(df, aes!(requests, hit_rate))
+        + geom_line!()
+        + geom_point!()
+        + scale_x_continuous!(labels = format::si())
+        + scale_y_continuous!(
+            breaks = vec![0.0, 0.25, 0.5, 0.75, 1.0],
+            labels = format::percent()
+        )
+        + labs!(title = "Demo of scale labels");
+    plot.to_svg(examples_dir.join("cache_labels.svg"), (1024, 768))?;
+
+    Ok(())
+}
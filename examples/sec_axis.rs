@@ -0,0 +1,30 @@
+use std::{env, path::PathBuf};
+
+use gongbi::{aes, geom_hline, geom_line, labs, plot, scale_y_continuous, sec_axis};
+use polars::prelude::*;
+
+fn main() -> anyhow::Result<()> {
+    let examples_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("examples");
+
+    // The memory of a process growing towards its limit of 512 MiB
+    let limit = 512.0;
+    let (second, memory): (Vec<f64>, Vec<f64>) = (0..=120)
+        .map(|t| {
+            let t = t as f64;
+            (t, 96.0 + 3.2 * t + 24.0 * (t / 8.0).sin())
+        })
+        .unzip();
+
+    let df = df!("second" => second, "memory" => memory)?;
+
+    let plot = plot!(df, aes!(second, memory))
+        + geom_hline!(aes!(color = "red"), yintercept = limit, expand = true)
+        + geom_line!()
+        + scale_y_continuous!(
+            sec_axis = sec_axis!(move |mib| mib / limit * 100.0, name = "percent of limit")
+        )
+        + labs!(y = "memory (MiB)", title = "Demo of sec_axis");
+    plot.to_svg(examples_dir.join("memory_sec_axis.svg"), (1024, 768))?;
+
+    Ok(())
+}
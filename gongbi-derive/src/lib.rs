@@ -0,0 +1,61 @@
+//! Derive macros for [gongbi](https://crates.io/crates/gongbi)
+//!
+//! Use them through the re-exports of `gongbi`, e.g. `#[derive(gongbi::Data)]`.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{ext::IdentExt, parse_macro_input, Data, DeriveInput, Fields};
+
+/// Derive `gongbi::data::Record` for a struct with named fields
+///
+/// Each field becomes a column named after it, so a `Vec` or slice of the
+/// struct can be used as the data source of a plot.
+#[proc_macro_derive(Data)]
+pub fn derive_data(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return syn::Error::new_spanned(
+                    &input,
+                    "Data can only be derived for structs with named fields",
+                )
+                .to_compile_error()
+                .into()
+            }
+        },
+        _ => {
+            return syn::Error::new_spanned(&input, "Data can only be derived for structs")
+                .to_compile_error()
+                .into()
+        }
+    };
+
+    let idents: Vec<_> = fields
+        .iter()
+        .map(|field| field.ident.as_ref().expect("named fields have identifiers"))
+        .collect();
+    let names: Vec<_> = idents
+        .iter()
+        .map(|ident| ident.unraw().to_string())
+        .collect();
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics ::gongbi::data::Record for #ident #ty_generics #where_clause {
+            const COLUMN_NAMES: &'static [&'static str] = &[#(#names),*];
+
+            fn field(&self, column_name: &str) -> Option<&dyn ::gongbi::data::Field> {
+                match column_name {
+                    #(#names => Some(&self.#idents),)*
+                    _ => None,
+                }
+            }
+        }
+    }
+    .into()
+}
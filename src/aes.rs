@@ -5,6 +5,7 @@ use std::ops::{Add, AddAssign};
 use derive_builder::Builder;
 
 pub mod color;
+pub mod position;
 
 /// Aesthetic mappings
 ///
@@ -16,17 +17,24 @@ pub mod color;
 #[derive(Clone, Debug, Default, PartialEq, Builder)]
 #[builder(default, setter(into, strip_option))]
 pub struct Aes {
-    /// The column name to map to the x-axis
-    pub x: Option<&'static str>,
+    /// The column name or the expression to map to the x-axis
+    pub x: Option<position::PositionMapping>,
 
-    /// The column name to map to the y-axis
-    pub y: Option<&'static str>,
+    /// The column name or the expression to map to the y-axis
+    pub y: Option<position::PositionMapping>,
+
+    /// The column name of the values at the (x, y) positions, e.g. for contours
+    pub z: Option<String>,
 
     /// The color aesthetic
-    pub color: Option<color::Color>,
+    ///
+    /// A constant color, or the column name to map to the color
+    pub color: Option<color::ColorMapping>,
 
     /// The fill aesthetic
-    pub fill: Option<bool>,
+    ///
+    /// A constant fill color, or the column name to map to the fill color
+    pub fill: Option<color::ColorMapping>,
 
     /// The size aesthetic
     pub size: Option<i32>,
@@ -34,8 +42,11 @@ pub struct Aes {
     /// The shape aesthetic
     pub shape: Option<u8>,
 
-    /// The label aesthetic
+    /// The column name of the strings to draw, e.g. for geom_text
     pub label: Option<String>,
+
+    /// The label of the layer in the legend
+    pub legend: Option<String>,
 }
 
 impl Aes {
@@ -43,17 +54,40 @@ impl Aes {
     pub fn builder() -> AesBuilder {
         AesBuilder::default()
     }
+
+    /// Get the names of the columns read by the mapping, without duplicates
+    pub fn columns(&self) -> Vec<String> {
+        let mut columns: Vec<String> = [&self.x, &self.y]
+            .into_iter()
+            .flatten()
+            .flat_map(|position| position.columns())
+            .chain(
+                [&self.color, &self.fill]
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|color| color.as_column())
+                    .map(String::from),
+            )
+            .chain([&self.z, &self.label].into_iter().flatten().cloned())
+            .collect();
+        columns.sort();
+        columns.dedup();
+
+        columns
+    }
 }
 
 impl AddAssign for Aes {
     fn add_assign(&mut self, rhs: Self) {
-        self.x = self.x.or(rhs.x);
-        self.y = self.y.or(rhs.y);
+        self.x = self.x.clone().or(rhs.x);
+        self.y = self.y.clone().or(rhs.y);
+        self.z = self.z.clone().or(rhs.z);
         self.color = self.color.clone().or(rhs.color);
-        self.fill = self.fill.or(rhs.fill);
+        self.fill = self.fill.clone().or(rhs.fill);
         self.size = self.size.or(rhs.size);
         self.shape = self.shape.or(rhs.shape);
         self.label = self.label.clone().or(rhs.label);
+        self.legend = self.legend.clone().or(rhs.legend);
     }
 }
 
@@ -69,12 +103,12 @@ impl Add for Aes {
 
 impl AesBuilder {
     /// Set the color aesthetic, alias for [`AesBuilder::color`]
-    pub fn colour<T: Into<color::Color>>(&mut self, colour: T) -> &mut Self {
+    pub fn colour<T: Into<color::ColorMapping>>(&mut self, colour: T) -> &mut Self {
         self.color(colour)
     }
 
     /// Set the color aesthetic, alias for [`AesBuilder::color`]
-    pub fn col<T: Into<color::Color>>(&mut self, col: T) -> &mut Self {
+    pub fn col<T: Into<color::ColorMapping>>(&mut self, col: T) -> &mut Self {
         self.color(col)
     }
 }
@@ -111,16 +145,42 @@ macro_rules! __aes {
         /// let a1 = aes!(x);
         /// let a2 = aes!(x, y);
         ///
-        /// assert_eq!(a1.x, Some("x"));
-        /// assert_eq!(a2.x, Some("x"));
-        /// assert_eq!(a2.y, Some("y"));
+        /// assert_eq!(a1.x, Some("x".into()));
+        /// assert_eq!(a2.x, Some("x".into()));
+        /// assert_eq!(a2.y, Some("y".into()));
+        /// ```
+        ///
+        /// A bare identifier is taken as the column name itself. Column names
+        /// chosen at runtime are passed as expressions, e.g. `&name` or `name.clone()`.
+        ///
+        /// ```
+        /// # use gongbi::*;
+        /// let name = String::from("hwy");
+        /// let a = aes!(x = &name, color = name.clone());
+        ///
+        /// assert_eq!(a.x, Some("hwy".into()));
+        /// ```
+        ///
+        /// They can also be `polars` expressions of the columns, evaluated against
+        /// the data of each layer. The default axis title is then the display
+        /// string of the expression.
+        ///
+        /// ```
+        /// # use gongbi::*;
+        /// # use polars::prelude::*;
+        /// let a = aes!(x = col("displ"), y = col("hwy") - col("cty"));
+        ///
+        /// assert_eq!(a.y, Some((col("hwy") - col("cty")).into()));
         /// ```
         ///
         /// #### Other Aesthetics
         ///
         /// Other aesthetics can be set with the named argument.
         ///
-        /// - `color`
+        /// - `z`: the column name of the values at the (x, y) positions, e.g. for contours
+        /// - `color`: a constant color like `color = "red"`, or a column name like
+        ///   `color = class` to group the rows by the values of the column
+        /// - `fill`: a constant color like `fill = "red"`, or a column name like `fill = value`
         /// - `size`
         /// - `shape`
         $aes
@@ -199,7 +259,7 @@ mod tests {
         assert_eq!(
             aes,
             Aes {
-                x: Some("mpg"),
+                x: Some("mpg".into()),
                 ..Default::default()
             }
         );
@@ -211,7 +271,7 @@ mod tests {
         assert_eq!(
             aes,
             Aes {
-                x: Some("mpg"),
+                x: Some("mpg".into()),
                 ..Default::default()
             }
         );
@@ -220,9 +280,82 @@ mod tests {
         assert_eq!(
             aes,
             Aes {
-                x: Some("mpg"),
+                x: Some("mpg".into()),
                 ..Default::default()
             }
         );
     }
+
+    #[test]
+    fn aes_runtime_columns() {
+        use color::ColorMapping;
+
+        let columns = [String::from("displ"), String::from("hwy")];
+        let aes = aes!(x = &columns[0], y = columns[1].clone(), fill = &columns[1]);
+
+        assert_eq!(aes.x, Some("displ".into()));
+        assert_eq!(aes.y, Some("hwy".into()));
+        assert_eq!(aes.fill, Some(ColorMapping::Column("hwy".into())));
+
+        // Runtime column names are never colors
+        let columns = [String::from("red"), String::from("NA")];
+        let aes = aes!(color = &columns[0], fill = columns[1].clone());
+
+        assert_eq!(aes.color, Some(ColorMapping::Column("red".into())));
+        assert_eq!(aes.fill, Some(ColorMapping::Column("NA".into())));
+        assert_eq!(
+            aes!(color = "red").color,
+            Some(ColorMapping::Constant("red".into()))
+        );
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn aes_expr() {
+        use polars::prelude::*;
+
+        use position::PositionMapping;
+
+        let df = df!("hwy" => [29.0, 31.0], "cty" => [18.0, 21.0]).unwrap();
+        let aes = aes!(hwy, y = col("hwy") - col("cty"));
+
+        assert_eq!(aes.x, Some(PositionMapping::Column("hwy".into())));
+        assert_eq!(
+            *crate::data::Data::position_f64(&df, aes.y.as_ref().unwrap()),
+            [11.0, 10.0]
+        );
+    }
+
+    #[test]
+    fn aes_fill() {
+        use color::{Color, ColorMapping};
+
+        assert_eq!(
+            aes!(fill = value).fill,
+            Some(ColorMapping::Column("value".into()))
+        );
+        assert_eq!(aes!(fill = "none").fill, Some(ColorMapping::None));
+        assert_eq!(
+            aes!(fill = "red").fill,
+            Some(ColorMapping::Constant(Color::from((255, 0, 0))))
+        );
+        assert_eq!(
+            aes!(fill = "#00FF00").fill,
+            Some(ColorMapping::Constant(Color::from((0, 255, 0))))
+        );
+    }
+
+    #[test]
+    fn aes_color() {
+        use color::{Color, ColorMapping};
+
+        assert_eq!(
+            aes!(color = class).color,
+            Some(ColorMapping::Column("class".into()))
+        );
+        assert_eq!(
+            aes!(colour = "blue").color,
+            Some(ColorMapping::Constant(Color::from((0, 0, 255))))
+        );
+    }
 }
@@ -65,12 +65,12 @@ impl FromStr for Color {
                 if s.len() != 7 {
                     return Err("Invalid color value".to_string());
                 }
-                let r = u8::from_str_radix(&s[1..3], 16).expect("Invalid red value");
-                let g = u8::from_str_radix(&s[3..5], 16).expect("Invalid green value");
-                let b = u8::from_str_radix(&s[5..7], 16).expect("Invalid blue value");
+                let r = u8::from_str_radix(&s[1..3], 16).map_err(|_| "Invalid red value")?;
+                let g = u8::from_str_radix(&s[3..5], 16).map_err(|_| "Invalid green value")?;
+                let b = u8::from_str_radix(&s[5..7], 16).map_err(|_| "Invalid blue value")?;
                 Ok(Color::RGB(RGBColor(r, g, b)))
             }
-            _ => panic!("Unsupported color"),
+            _ => Err(format!("Unsupported color: {s}")),
         }
     }
 }
@@ -86,3 +86,86 @@ impl From<String> for Color {
         Color::from_str(&value).unwrap()
     }
 }
+
+/// A color aesthetic
+///
+/// A color aesthetic is either set to a constant color, or mapped from a
+/// column of the data through a scale.
+///
+/// When converted from a string literal (`&str`), `"none"` and `"NA"` are
+/// [`ColorMapping::None`], valid colors like `"red"` or `"#FF0000"` are
+/// [`ColorMapping::Constant`], and other strings are column names.
+///
+/// An owned `String` is always a column name, since it is usually chosen at runtime,
+/// e.g. a column called `"red"`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ColorMapping {
+    /// No color, e.g. a hollow point
+    None,
+
+    /// A constant color
+    Constant(Color),
+
+    /// The column name to map to the color
+    Column(String),
+}
+
+impl ColorMapping {
+    /// Get the constant color, or `None` if there is no color or the color is mapped from a column.
+    pub fn as_constant(&self) -> Option<RGBColor> {
+        match self {
+            ColorMapping::Constant(color) => Some(color.as_rgb()),
+            _ => None,
+        }
+    }
+
+    /// Get the mapped column name, if any.
+    pub fn as_column(&self) -> Option<&str> {
+        match self {
+            ColorMapping::Column(column) => Some(column),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for ColorMapping {
+    fn from(value: &str) -> Self {
+        match value {
+            "none" | "NA" => ColorMapping::None,
+            _ => match Color::from_str(value) {
+                Ok(color) => ColorMapping::Constant(color),
+                Err(_) => ColorMapping::Column(value.to_string()),
+            },
+        }
+    }
+}
+
+impl From<String> for ColorMapping {
+    fn from(value: String) -> Self {
+        ColorMapping::Column(value)
+    }
+}
+
+impl From<&String> for ColorMapping {
+    fn from(value: &String) -> Self {
+        ColorMapping::Column(value.clone())
+    }
+}
+
+impl From<Color> for ColorMapping {
+    fn from(value: Color) -> Self {
+        ColorMapping::Constant(value)
+    }
+}
+
+impl From<RGBColor> for ColorMapping {
+    fn from(value: RGBColor) -> Self {
+        ColorMapping::Constant(value.into())
+    }
+}
+
+impl From<(u8, u8, u8)> for ColorMapping {
+    fn from(value: (u8, u8, u8)) -> Self {
+        ColorMapping::Constant(value.into())
+    }
+}
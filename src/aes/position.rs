@@ -0,0 +1,110 @@
+//! Position module
+//!
+//! This module contains the mappings of the position aesthetics, e.g. `x` and `y`.
+
+use std::fmt::Display;
+
+#[cfg(feature = "polars")]
+use polars::prelude::Expr;
+
+/// The mapping of a position aesthetic
+///
+/// A position is read from a column of the data, or, with the `polars` feature,
+/// evaluated from a `polars` expression of the columns, e.g. `col("hwy") - col("cty")`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PositionMapping {
+    /// The column name to read the positions from
+    Column(String),
+
+    /// The expression to evaluate against the data of the layer
+    #[cfg(feature = "polars")]
+    Expr(Expr),
+}
+
+impl PositionMapping {
+    /// Get the column name if the positions are read from a column.
+    pub fn as_column(&self) -> Option<&str> {
+        match self {
+            PositionMapping::Column(column) => Some(column),
+            #[cfg(feature = "polars")]
+            PositionMapping::Expr(_) => None,
+        }
+    }
+
+    /// Get the names of the columns read by the mapping, i.e. the column or the columns of the expression.
+    pub fn columns(&self) -> Vec<String> {
+        match self {
+            PositionMapping::Column(column) => vec![column.clone()],
+            #[cfg(feature = "polars")]
+            PositionMapping::Expr(expr) => expr
+                .clone()
+                .meta()
+                .root_names()
+                .into_iter()
+                .map(|name| name.to_string())
+                .collect(),
+        }
+    }
+
+    /// Get the expression of the positions, e.g. to evaluate them in a lazy query.
+    #[cfg(feature = "polars")]
+    pub(crate) fn expr(&self) -> Expr {
+        match self {
+            PositionMapping::Column(column) => polars::prelude::col(column.as_str()),
+            PositionMapping::Expr(expr) => expr.clone(),
+        }
+    }
+}
+
+impl From<&str> for PositionMapping {
+    fn from(value: &str) -> Self {
+        PositionMapping::Column(value.to_string())
+    }
+}
+
+impl From<String> for PositionMapping {
+    fn from(value: String) -> Self {
+        PositionMapping::Column(value)
+    }
+}
+
+impl From<&String> for PositionMapping {
+    fn from(value: &String) -> Self {
+        PositionMapping::Column(value.clone())
+    }
+}
+
+#[cfg(feature = "polars")]
+impl From<Expr> for PositionMapping {
+    fn from(value: Expr) -> Self {
+        PositionMapping::Expr(value)
+    }
+}
+
+/// The column name, or the display string of the expression, e.g. as the default axis title
+impl Display for PositionMapping {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PositionMapping::Column(column) => write!(f, "{column}"),
+            #[cfg(feature = "polars")]
+            PositionMapping::Expr(expr) => write!(f, "{expr}"),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "polars"))]
+mod tests {
+    use polars::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn position_display() {
+        assert_eq!(PositionMapping::from("hwy").to_string(), "hwy");
+        assert_eq!(
+            PositionMapping::from(col("hwy") - col("cty")).to_string(),
+            (col("hwy") - col("cty")).to_string()
+        );
+        assert_eq!(PositionMapping::from(col("hwy")).as_column(), None);
+    }
+}
@@ -0,0 +1,545 @@
+//! # Coordinate module
+//!
+//! A coordinate system maps the positions of the data to the positions on the
+//! chart. Plots use Cartesian coordinates by default, and polar coordinates can
+//! be added with [`coord_polar!`](crate::coord_polar!), e.g. to turn a stacked
+//! bar into a pie chart.
+//!
+//! The axes can also be swapped with [`coord_flip!`](crate::coord_flip!), e.g.
+//! for horizontal bars, or drawn at a fixed scale with [`coord_fixed!`](crate::coord_fixed!).
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use gongbi::*;
+//! # fn main() -> anyhow::Result<()> {
+//! let p = plot!(csv = "examples/mpg.csv", mapping = aes!(fill = class))
+//!     + geom_bar!(width = 1.0)
+//!     + coord_polar!(theta = "y");
+//!
+//! p.to_svg("coord_polar.svg", (800, 600))?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{f64::consts::PI, ops::Range};
+
+use derive_builder::Builder;
+use plotters::{
+    chart::ChartContext,
+    coord::types::RangedCoordf64,
+    element::Text,
+    prelude::{Cartesian2d, DrawingBackend, PathElement},
+    style::{
+        text_anchor::{HPos, Pos, VPos},
+        Color, IntoFont, BLACK,
+    },
+};
+
+use crate::guide::{breaks, format_break};
+
+/// The half size of the chart in polar coordinates, leaving space for the labels around the unit circle.
+const POLAR_EXTENT: f64 = 1.3;
+
+/// The largest angle in radians between two points of a path drawn in polar coordinates.
+const POLAR_STEP: f64 = PI / 90.0;
+
+/// A coordinate system to add to a plot
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Coord {
+    /// Cartesian coordinates
+    #[default]
+    Cartesian,
+
+    /// Cartesian coordinates with the x and y positions swapped
+    Flip,
+
+    /// Cartesian coordinates with a fixed ratio between the units of the y and x axes
+    ///
+    /// The ratio is the length of a unit of y divided by the length of a unit of x on the chart.
+    Fixed(f64),
+
+    /// Polar coordinates
+    Polar(Polar),
+}
+
+/// The position mapped to the angle in polar coordinates
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Theta {
+    /// The x position is mapped to the angle, and the y position to the radius
+    #[default]
+    X,
+
+    /// The y position is mapped to the angle, and the x position to the radius
+    Y,
+}
+
+impl std::str::FromStr for Theta {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "x" => Ok(Theta::X),
+            "y" => Ok(Theta::Y),
+            _ => Err(format!("Unsupported theta: {s}")),
+        }
+    }
+}
+
+impl From<&str> for Theta {
+    fn from(value: &str) -> Self {
+        value.parse().unwrap()
+    }
+}
+
+/// # Polar coordinates
+///
+/// One position is mapped to the angle, clockwise from 12 o'clock by default,
+/// and the other to the distance from the center.
+///
+/// To create polar coordinates, [`coord_polar!`](crate::coord_polar!) and [`Polar::builder`] can be used.
+#[derive(Clone, Debug, PartialEq, Builder)]
+pub struct Polar {
+    /// The position mapped to the angle, `"x"` or `"y"`.
+    #[builder(default, setter(into))]
+    theta: Theta,
+
+    /// The offset of the starting angle from 12 o'clock in radians.
+    #[builder(default)]
+    start: f64,
+
+    /// The direction of the angle, `1` for clockwise and `-1` for anticlockwise.
+    #[builder(default = "1")]
+    direction: i32,
+
+    /// The radius of the hole in the center, as a fraction of the radius.
+    #[builder(default)]
+    inner_radius: f64,
+}
+
+impl Default for Polar {
+    fn default() -> Self {
+        Polar::builder().build().unwrap()
+    }
+}
+
+impl Polar {
+    /// Create a new [`Polar`] via the builder pattern.
+    pub fn builder() -> PolarBuilder {
+        PolarBuilder::default()
+    }
+
+    /// Get the position mapped to the angle.
+    pub fn theta(&self) -> Theta {
+        self.theta
+    }
+}
+
+/// # coord_polar!: Construct polar coordinates
+///
+/// This macro is used to create [`Polar`] coordinates in a more concise way like `ggplot2`.
+/// It is a wrapper around [`Polar::builder`].
+///
+/// Stacked bars become pie charts with `theta = "y"`, or donut charts with an
+/// `inner_radius`, and lines become radar charts with `theta = "x"`.
+///
+/// ## Usage
+///
+/// ```ignore
+/// coord_polar!(
+///     theta = <"x" | "y">,
+///     start = <f64>,
+///     direction = <1 | -1>,
+///     inner_radius = <f64>,
+/// )
+/// ```
+///
+/// ### Arguments
+///
+/// #### theta
+///
+/// The position mapped to the angle, `"x"` (default) or `"y"`.
+///
+/// #### start
+///
+/// The offset of the starting angle from 12 o'clock in radians, defaults to 0.
+///
+/// #### direction
+///
+/// `1` (default) for clockwise, or `-1` for anticlockwise.
+///
+/// #### inner_radius
+///
+/// The radius of the hole in the center as a fraction of the radius, from
+/// `0.0` (default) to `1.0`.
+#[macro_export]
+macro_rules! coord_polar {
+    ($($param: ident = $value: expr),* $(,)?) => {
+        $crate::coord::Coord::Polar(
+            $crate::coord::Polar::builder()
+                $(.$param($value))*
+                .build()
+                .unwrap()
+        )
+    };
+}
+
+/// # coord_flip!: Construct flipped Cartesian coordinates
+///
+/// This macro is used to swap the x and y positions of all layers like `ggplot2`,
+/// e.g. to draw horizontal bars. The labels of the axes follow the positions.
+///
+/// ## Usage
+///
+/// ```ignore
+/// coord_flip!()
+/// ```
+#[macro_export]
+macro_rules! coord_flip {
+    () => {
+        $crate::coord::Coord::Flip
+    };
+}
+
+/// # coord_fixed!: Construct Cartesian coordinates with a fixed ratio
+///
+/// This macro is used to draw the x and y axes at a fixed scale like `ggplot2`,
+/// e.g. for maps or predicted against actual values. The panel is shrunk to
+/// keep the ratio, instead of filling the size of the plot.
+///
+/// ## Usage
+///
+/// ```ignore
+/// coord_fixed!(ratio = <f64>)
+/// ```
+///
+/// ### Arguments
+///
+/// #### ratio
+///
+/// The length of a unit of y divided by the length of a unit of x, defaults to
+/// `1.0`, i.e. the same scale on both axes. The `ratio =` part can be omitted.
+#[macro_export]
+macro_rules! coord_fixed {
+    () => {
+        $crate::coord::Coord::Fixed(1.0)
+    };
+
+    (ratio = $ratio: expr $(,)?) => {
+        $crate::coord::Coord::Fixed($ratio)
+    };
+
+    ($ratio: expr $(,)?) => {
+        $crate::coord::Coord::Fixed($ratio)
+    };
+}
+
+/// # Panel
+///
+/// The panel is the area of a plot where the layers are drawn. It maps the
+/// positions of the data to the positions on the chart through the coordinate
+/// system, so the layers draw the same way in any coordinates.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Panel {
+    coord: Coord,
+    x_range: Range<f64>,
+    y_range: Range<f64>,
+}
+
+impl Panel {
+    /// Create a panel showing the ranges of the data in the coordinate system.
+    pub(crate) fn new(coord: Coord, x_range: Range<f64>, y_range: Range<f64>) -> Self {
+        Panel {
+            coord,
+            x_range,
+            y_range,
+        }
+    }
+
+    /// Get the coordinate system of the panel.
+    pub fn coord(&self) -> &Coord {
+        &self.coord
+    }
+
+    /// Get the range of the x positions shown in the panel.
+    pub fn x_range(&self) -> Range<f64> {
+        self.x_range.clone()
+    }
+
+    /// Get the range of the y positions shown in the panel.
+    pub fn y_range(&self) -> Range<f64> {
+        self.y_range.clone()
+    }
+
+    /// Whether straight lines of the data stay straight on the chart.
+    ///
+    /// If so, the shapes can be drawn from their corners, e.g. rectangles.
+    pub fn is_linear(&self) -> bool {
+        !matches!(self.coord, Coord::Polar(_))
+    }
+
+    /// Whether the x positions are on the vertical axis and the y positions on the horizontal one.
+    pub fn is_flipped(&self) -> bool {
+        matches!(self.coord, Coord::Flip)
+    }
+
+    /// Get the height of the panel divided by its width, if it is fixed by the coordinate system.
+    pub(crate) fn aspect_ratio(&self) -> Option<f64> {
+        match self.coord {
+            Coord::Fixed(ratio) => {
+                let (x_range, y_range) = (self.x_range(), self.y_range());
+
+                Some(ratio * (y_range.end - y_range.start) / (x_range.end - x_range.start))
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the x positions go around a circle, i.e. x is mapped to the angle of polar coordinates.
+    pub fn wraps_x(&self) -> bool {
+        matches!(&self.coord, Coord::Polar(polar) if polar.theta == Theta::X)
+    }
+
+    /// Get the ranges of the chart coordinates.
+    pub(crate) fn chart_range(&self) -> (Range<f64>, Range<f64>) {
+        match self.coord {
+            Coord::Cartesian | Coord::Fixed(_) => (self.x_range(), self.y_range()),
+            Coord::Flip => (self.y_range(), self.x_range()),
+            Coord::Polar(_) => (-POLAR_EXTENT..POLAR_EXTENT, -POLAR_EXTENT..POLAR_EXTENT),
+        }
+    }
+
+    /// Get the angle and the radius of a position in polar coordinates.
+    fn polar(&self, polar: &Polar, (x, y): (f64, f64)) -> (f64, f64) {
+        let rescale = |v: f64, range: &Range<f64>| {
+            if range.end > range.start {
+                (v - range.start) / (range.end - range.start)
+            } else {
+                0.5
+            }
+        };
+
+        let (t, r) = match polar.theta {
+            Theta::X => (rescale(x, &self.x_range), rescale(y, &self.y_range)),
+            Theta::Y => (rescale(y, &self.y_range), rescale(x, &self.x_range)),
+        };
+
+        (
+            polar.start + polar.direction as f64 * 2.0 * PI * t,
+            polar.inner_radius + (1.0 - polar.inner_radius) * r,
+        )
+    }
+
+    /// Map a position of the data to the chart.
+    pub fn map(&self, point: (f64, f64)) -> (f64, f64) {
+        match &self.coord {
+            Coord::Cartesian | Coord::Fixed(_) => point,
+            Coord::Flip => (point.1, point.0),
+            Coord::Polar(polar) => {
+                let (angle, radius) = self.polar(polar, point);
+
+                (radius * angle.sin(), radius * angle.cos())
+            }
+        }
+    }
+
+    /// Map a path of the data to the chart.
+    ///
+    /// The segments between the points follow the coordinate system, e.g. a
+    /// horizontal segment becomes an arc in polar coordinates.
+    pub fn path(&self, points: &[(f64, f64)]) -> Vec<(f64, f64)> {
+        let Coord::Polar(polar) = &self.coord else {
+            return points.iter().map(|&point| self.map(point)).collect();
+        };
+
+        let mut path = Vec::with_capacity(points.len());
+        for (i, &point) in points.iter().enumerate() {
+            if let Some(&prev) = i.checked_sub(1).and_then(|i| points.get(i)) {
+                let span = (self.polar(polar, point).0 - self.polar(polar, prev).0).abs();
+                let steps = (span / POLAR_STEP).ceil().max(1.0) as usize;
+
+                path.extend((1..steps).map(|k| {
+                    let t = k as f64 / steps as f64;
+
+                    self.map((
+                        prev.0 + t * (point.0 - prev.0),
+                        prev.1 + t * (point.1 - prev.1),
+                    ))
+                }));
+            }
+
+            path.push(self.map(point));
+        }
+
+        path
+    }
+
+    /// Map a polygon of the data to the chart.
+    ///
+    /// Like [`Panel::path`], including the edge between the last and the first points.
+    pub fn polygon(&self, points: &[(f64, f64)]) -> Vec<(f64, f64)> {
+        if self.is_linear() || points.is_empty() {
+            return self.path(points);
+        }
+
+        let mut ring = points.to_vec();
+        ring.push(points[0]);
+
+        let mut polygon = self.path(&ring);
+        polygon.pop();
+
+        polygon
+    }
+
+    /// Map a rectangle of the data to a polygon on the chart.
+    pub fn rect(&self, [(x0, y0), (x1, y1)]: [(f64, f64); 2]) -> Vec<(f64, f64)> {
+        self.polygon(&[(x0, y0), (x1, y0), (x1, y1), (x0, y1)])
+    }
+
+    /// Draw the grid and the labels of polar coordinates.
+    ///
+    /// The circles are at the breaks of the radius, and the spokes at the
+    /// breaks of the angle.
+    pub(crate) fn draw_polar_grid<DB>(
+        &self,
+        chart: &mut ChartContext<'_, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+    ) -> anyhow::Result<()>
+    where
+        DB: DrawingBackend,
+        <DB as plotters::prelude::DrawingBackend>::ErrorType: 'static,
+    {
+        let Coord::Polar(polar) = &self.coord else {
+            return Ok(());
+        };
+
+        let (theta_range, r_range) = match polar.theta {
+            Theta::X => (self.x_range(), self.y_range()),
+            Theta::Y => (self.y_range(), self.x_range()),
+        };
+        // Get the position of the data at an angle and a radius
+        let at = |t: f64, r: f64| match polar.theta {
+            Theta::X => (t, r),
+            Theta::Y => (r, t),
+        };
+
+        let grid_style = BLACK.mix(0.2).stroke_width(1);
+        let label_style = ("sans-serif", 16).into_font().color(&BLACK);
+
+        let (r_breaks, r_step) = breaks(r_range.start, r_range.end, 5);
+        for r in r_breaks {
+            let circle = self.path(&[at(theta_range.start, r), at(theta_range.end, r)]);
+            chart.draw_series(std::iter::once(PathElement::new(circle, grid_style)))?;
+
+            // The labels of the radius are on the left of the panel
+            let (_, radius) = self.polar(polar, at(theta_range.start, r));
+            chart.draw_series(std::iter::once(Text::new(
+                format_break(r, r_step),
+                (-1.2, radius),
+                label_style.pos(Pos::new(HPos::Right, VPos::Center)),
+            )))?;
+        }
+
+        let (t_breaks, t_step) = breaks(theta_range.start, theta_range.end, 8);
+        let full_turn = theta_range.end - theta_range.start - t_step * 1e-9;
+        for &t in &t_breaks {
+            // The last break overlaps the first one after a full turn
+            if t_breaks
+                .first()
+                .is_some_and(|&first| t - first >= full_turn)
+            {
+                continue;
+            }
+
+            let spoke = vec![self.map(at(t, r_range.start)), self.map(at(t, r_range.end))];
+            chart.draw_series(std::iter::once(PathElement::new(spoke, grid_style)))?;
+
+            let (angle, _) = self.polar(polar, at(t, r_range.end));
+            chart.draw_series(std::iter::once(Text::new(
+                format_break(t, t_step),
+                (1.1 * angle.sin(), 1.1 * angle.cos()),
+                label_style.pos(Pos::new(HPos::Center, VPos::Center)),
+            )))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: (f64, f64), b: (f64, f64)) {
+        assert!(
+            (a.0 - b.0).abs() < 1e-9 && (a.1 - b.1).abs() < 1e-9,
+            "{a:?} != {b:?}"
+        );
+    }
+
+    #[test]
+    fn polar_map() {
+        let panel = Panel::new(Coord::Polar(Polar::default()), 0.0..4.0, 0.0..1.0);
+
+        // A quarter turn clockwise from 12 o'clock
+        assert_close(panel.map((0.0, 1.0)), (0.0, 1.0));
+        assert_close(panel.map((1.0, 1.0)), (1.0, 0.0));
+        assert_close(panel.map((2.0, 0.5)), (0.0, -0.5));
+        assert_close(panel.map((3.0, 0.5)), (-0.5, 0.0));
+
+        let panel = Panel::new(
+            Coord::Polar(
+                Polar::builder()
+                    .theta("y")
+                    .direction(-1)
+                    .inner_radius(0.5)
+                    .build()
+                    .unwrap(),
+            ),
+            0.0..1.0,
+            0.0..4.0,
+        );
+
+        // Anticlockwise around the hole
+        assert_close(panel.map((0.0, 1.0)), (-0.5, 0.0));
+        assert_close(panel.map((1.0, 2.0)), (0.0, -1.0));
+    }
+
+    #[test]
+    fn polar_rect_follows_arcs() {
+        let panel = Panel::new(Coord::Polar(Polar::default()), 0.0..4.0, 0.0..1.0);
+
+        // A half turn at the outer radius needs many points
+        let polygon = panel.rect([(0.0, 0.5), (2.0, 1.0)]);
+        assert!(polygon.len() > 90);
+        assert!(polygon
+            .iter()
+            .all(|&(u, v)| (0.5 - 1e-9..=1.0 + 1e-9).contains(&u.hypot(v))));
+
+        let panel = Panel::new(Coord::Cartesian, 0.0..4.0, 0.0..1.0);
+        assert_eq!(panel.rect([(0.0, 0.0), (1.0, 1.0)]).len(), 4);
+    }
+
+    #[test]
+    fn flip_swaps_positions() {
+        let panel = Panel::new(Coord::Flip, 0.0..4.0, 0.0..1.0);
+
+        assert_eq!(panel.map((3.0, 0.5)), (0.5, 3.0));
+        assert_eq!(panel.chart_range(), (0.0..1.0, 0.0..4.0));
+        assert_eq!(
+            panel.rect([(0.0, 0.0), (1.0, 0.5)]),
+            vec![(0.0, 0.0), (0.0, 1.0), (0.5, 1.0), (0.5, 0.0)]
+        );
+    }
+
+    #[test]
+    fn fixed_aspect_ratio() {
+        let panel = Panel::new(Coord::Fixed(1.0), 0.0..4.0, 0.0..1.0);
+        assert_eq!(panel.aspect_ratio(), Some(0.25));
+
+        let panel = Panel::new(Coord::Fixed(2.0), 0.0..4.0, 0.0..1.0);
+        assert_eq!(panel.aspect_ratio(), Some(0.5));
+
+        let panel = Panel::new(Coord::Cartesian, 0.0..4.0, 0.0..1.0);
+        assert_eq!(panel.aspect_ratio(), None);
+    }
+}
@@ -2,20 +2,252 @@
 //!
 //! This module defines the `Data` trait and its implementations.
 
-use std::fmt::Debug;
+use std::{borrow::Cow, fmt::Debug};
 
+#[cfg(feature = "polars")]
+use ::polars::prelude::{Expr, LazyFrame};
+
+use crate::aes::position::PositionMapping;
+
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod collection;
+#[cfg(feature = "polars")]
 pub mod polars;
 
+pub use collection::{Field, Record};
+
+/// The type of the values of a column
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColumnType {
+    /// Floating point numbers
+    Float,
+
+    /// Integers
+    Integer,
+
+    /// Booleans
+    Boolean,
+
+    /// Strings
+    String,
+
+    /// Categories, e.g. `polars` `Categorical` and `Enum`
+    Categorical,
+
+    /// Dates, times and durations
+    Temporal,
+
+    /// Any other type, e.g. lists
+    Other,
+}
+
+impl ColumnType {
+    /// Whether the values are numbers, i.e. floats or integers.
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, ColumnType::Float | ColumnType::Integer)
+    }
+
+    /// Whether the values are discrete, e.g. to be mapped by a discrete scale.
+    pub fn is_discrete(&self) -> bool {
+        matches!(
+            self,
+            ColumnType::Boolean | ColumnType::String | ColumnType::Categorical
+        )
+    }
+}
+
 /// # Data trait
 ///
 /// This trait abstracts the data source for the [`Plot`](crate::Plot) struct.
-pub trait Data: Debug {
-    /// Get a column as a vector of f64 values.
-    fn column_f64(&self, column_name: &str) -> Vec<f64>;
+///
+/// It is implemented for:
+///
+/// - `polars` `DataFrame` and `LazyFrame`, with the `polars` feature. The columns a layer
+///   needs are collected from a `LazyFrame` in one query, so only these columns are read
+///   from the source, and the counts of bars and bins are computed in the query.
+/// - Maps from column names to columns, e.g. `HashMap<String, Vec<f64>>`.
+/// - Arrow `RecordBatch`, with the `arrow` feature.
+/// - Vectors and slices of [`Record`]s, e.g. structs with `#[derive(gongbi::Data)]`.
+/// - References to any of them.
+///
+/// The data is shared by the layers of a plot, which are computed in parallel,
+/// so it must be `Send` and `Sync`.
+pub trait Data: Send + Sync {
+    /// Get the names of the columns.
+    fn column_names(&self) -> Vec<String>;
+
+    /// Get the type of the values of a column.
+    fn column_type(&self, column_name: &str) -> ColumnType;
 
-    /// Get the minimum and maximum values of a column.
-    fn column_range_f64(&self, column_name: &str) -> (f64, f64);
+    /// Get a column as f64 values, `NaN` for missing values.
+    ///
+    /// The values are borrowed without copying if the source stores them as
+    /// a contiguous slice of f64 without missing values, e.g. a `DataFrame`
+    /// column of one chunk, or a `HashMap` of `Vec<f64>`.
+    fn column_f64(&self, column_name: &str) -> Cow<'_, [f64]>;
+
+    /// Get a column as a vector of i64 values, `None` for missing values.
+    ///
+    /// By default, the f64 values are truncated, and `NaN` is missing.
+    fn column_i64(&self, column_name: &str) -> Vec<Option<i64>> {
+        self.column_f64(column_name)
+            .iter()
+            .map(|&value| (!value.is_nan()).then_some(value as i64))
+            .collect()
+    }
+
+    /// Get a column as a vector of booleans, `None` for missing values.
+    ///
+    /// By default, non-zero f64 values are `true`, and `NaN` is missing.
+    fn column_bool(&self, column_name: &str) -> Vec<Option<bool>> {
+        self.column_f64(column_name)
+            .iter()
+            .map(|&value| (!value.is_nan()).then_some(value != 0.0))
+            .collect()
+    }
+
+    /// Get a temporal column as milliseconds since the Unix epoch, `None` for missing values.
+    ///
+    /// By default, the values of the column are taken as milliseconds already.
+    fn column_timestamp(&self, column_name: &str) -> Vec<Option<i64>> {
+        self.column_i64(column_name)
+    }
+
+    /// Get a column as a vector of strings.
+    ///
+    /// Non-string columns are converted to their string representation.
+    fn column_string(&self, column_name: &str) -> Vec<String>;
+
+    /// Get the minimum and maximum values of a column, ignoring `NaN`.
+    ///
+    /// By default, both are found in one pass over the values of [`Data::column_f64`].
+    fn column_range_f64(&self, column_name: &str) -> (f64, f64) {
+        range_f64(self.column_f64(column_name).iter().copied())
+    }
 
     /// Get the length of a column.
     fn column_len(&self, column_name: &str) -> usize;
+
+    /// Get the distinct values of a column as sorted strings.
+    fn column_levels(&self, column_name: &str) -> Vec<String> {
+        let mut levels = self.column_string(column_name);
+        levels.sort();
+        levels.dedup();
+
+        levels
+    }
+
+    /// Evaluate an expression of the columns as a vector of f64 values.
+    ///
+    /// By default, the columns used by the expression are read as f64 values
+    /// into a `DataFrame` to evaluate it.
+    #[cfg(feature = "polars")]
+    fn expr_f64(&self, expr: &Expr) -> Vec<f64> {
+        polars::expr_f64(self, expr)
+    }
+
+    /// Get the lazy frame of the data, if the data is queried lazily.
+    ///
+    /// Layers aggregating the data, e.g. bars and bins, compute their statistics
+    /// in the query of the lazy frame instead of reading the columns.
+    #[cfg(feature = "polars")]
+    fn as_lazy(&self) -> Option<&LazyFrame> {
+        None
+    }
+
+    /// Get the values of a position mapping, read from a column or evaluated from an expression.
+    fn position_f64(&self, mapping: &PositionMapping) -> Cow<'_, [f64]> {
+        match mapping {
+            PositionMapping::Column(column) => self.column_f64(column),
+            #[cfg(feature = "polars")]
+            PositionMapping::Expr(expr) => Cow::Owned(self.expr_f64(expr)),
+        }
+    }
+
+    /// Get the minimum and maximum values of a position mapping.
+    fn position_range_f64(&self, mapping: &PositionMapping) -> (f64, f64) {
+        match mapping {
+            PositionMapping::Column(column) => self.column_range_f64(column),
+            #[cfg(feature = "polars")]
+            PositionMapping::Expr(expr) => range_f64(self.expr_f64(expr)),
+        }
+    }
+
+    /// Get the length of a position mapping.
+    fn position_len(&self, mapping: &PositionMapping) -> usize {
+        match mapping {
+            PositionMapping::Column(column) => self.column_len(column),
+            #[cfg(feature = "polars")]
+            PositionMapping::Expr(expr) => self.expr_f64(expr).len(),
+        }
+    }
+}
+
+/// Get the minimum and maximum values, ignoring `NaN`.
+fn range_f64(values: impl IntoIterator<Item = f64>) -> (f64, f64) {
+    values
+        .into_iter()
+        .filter(|value| !value.is_nan())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+            (min.min(value), max.max(value))
+        })
+}
+
+impl Debug for dyn Data {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Data").finish_non_exhaustive()
+    }
+}
+
+impl<D: Data + ?Sized> Data for &D {
+    fn column_names(&self) -> Vec<String> {
+        (**self).column_names()
+    }
+
+    fn column_type(&self, column_name: &str) -> ColumnType {
+        (**self).column_type(column_name)
+    }
+
+    fn column_f64(&self, column_name: &str) -> Cow<'_, [f64]> {
+        (**self).column_f64(column_name)
+    }
+
+    fn column_i64(&self, column_name: &str) -> Vec<Option<i64>> {
+        (**self).column_i64(column_name)
+    }
+
+    fn column_bool(&self, column_name: &str) -> Vec<Option<bool>> {
+        (**self).column_bool(column_name)
+    }
+
+    fn column_timestamp(&self, column_name: &str) -> Vec<Option<i64>> {
+        (**self).column_timestamp(column_name)
+    }
+
+    fn column_string(&self, column_name: &str) -> Vec<String> {
+        (**self).column_string(column_name)
+    }
+
+    fn column_range_f64(&self, column_name: &str) -> (f64, f64) {
+        (**self).column_range_f64(column_name)
+    }
+
+    fn column_len(&self, column_name: &str) -> usize {
+        (**self).column_len(column_name)
+    }
+
+    fn column_levels(&self, column_name: &str) -> Vec<String> {
+        (**self).column_levels(column_name)
+    }
+
+    #[cfg(feature = "polars")]
+    fn expr_f64(&self, expr: &Expr) -> Vec<f64> {
+        (**self).expr_f64(expr)
+    }
+
+    #[cfg(feature = "polars")]
+    fn as_lazy(&self) -> Option<&LazyFrame> {
+        (**self).as_lazy()
+    }
 }
@@ -0,0 +1,157 @@
+//! `Data` implementation for Arrow `RecordBatch`
+
+use std::borrow::Cow;
+
+use arrow_array::{
+    cast::AsArray,
+    types::{Float64Type, Int64Type, TimestampMillisecondType},
+    Array, ArrayRef, RecordBatch,
+};
+use arrow_cast::display::{ArrayFormatter, FormatOptions};
+use arrow_schema::{DataType, TimeUnit};
+
+use super::{ColumnType, Data};
+
+/// Get a column of the record batch.
+fn column<'a>(data: &'a RecordBatch, column_name: &str) -> &'a dyn Array {
+    data.column_by_name(column_name)
+        .unwrap_or_else(|| panic!("Column {column_name} not found in data"))
+}
+
+/// Get a column cast to a data type, values which cannot be cast are null.
+fn cast(data: &RecordBatch, column_name: &str, dtype: &DataType) -> ArrayRef {
+    arrow_cast::cast(column(data, column_name), dtype)
+        .unwrap_or_else(|err| panic!("Failed to cast {column_name} to {dtype}: {err}"))
+}
+
+impl Data for RecordBatch {
+    fn column_names(&self) -> Vec<String> {
+        self.schema()
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect()
+    }
+
+    fn column_type(&self, column_name: &str) -> ColumnType {
+        match column(self, column_name).data_type() {
+            dtype if dtype.is_floating() => ColumnType::Float,
+            dtype if dtype.is_integer() => ColumnType::Integer,
+            DataType::Boolean => ColumnType::Boolean,
+            DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View => ColumnType::String,
+            DataType::Dictionary(_, _) => ColumnType::Categorical,
+            dtype if dtype.is_temporal() => ColumnType::Temporal,
+            _ => ColumnType::Other,
+        }
+    }
+
+    fn column_f64(&self, column_name: &str) -> Cow<'_, [f64]> {
+        let values = column(self, column_name);
+
+        // Borrow the values of a f64 column without missing values
+        if let Some(values) = values.as_primitive_opt::<Float64Type>() {
+            if values.null_count() == 0 {
+                return Cow::Borrowed(values.values());
+            }
+        }
+
+        let values = cast(self, column_name, &DataType::Float64);
+
+        values
+            .as_primitive::<Float64Type>()
+            .iter()
+            .map(|value| value.unwrap_or(f64::NAN))
+            .collect()
+    }
+
+    fn column_string(&self, column_name: &str) -> Vec<String> {
+        let values = column(self, column_name);
+        let formatter = ArrayFormatter::try_new(values, &FormatOptions::default())
+            .unwrap_or_else(|err| panic!("Failed to format {column_name}: {err}"));
+
+        (0..values.len())
+            .map(|i| formatter.value(i).to_string())
+            .collect()
+    }
+
+    fn column_len(&self, column_name: &str) -> usize {
+        column(self, column_name).len()
+    }
+
+    fn column_i64(&self, column_name: &str) -> Vec<Option<i64>> {
+        let values = cast(self, column_name, &DataType::Int64);
+
+        values.as_primitive::<Int64Type>().iter().collect()
+    }
+
+    fn column_bool(&self, column_name: &str) -> Vec<Option<bool>> {
+        let values = cast(self, column_name, &DataType::Boolean);
+
+        values.as_boolean().iter().collect()
+    }
+
+    fn column_timestamp(&self, column_name: &str) -> Vec<Option<i64>> {
+        let dtype = DataType::Timestamp(TimeUnit::Millisecond, None);
+        let values = cast(self, column_name, &dtype);
+
+        values
+            .as_primitive::<TimestampMillisecondType>()
+            .iter()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow_array::{BooleanArray, Date32Array, Float64Array, Int32Array, StringArray};
+
+    use super::*;
+
+    #[test]
+    fn record_batch() {
+        let batch = RecordBatch::try_from_iter([
+            (
+                "x",
+                Arc::new(Float64Array::from(vec![3.0, 1.0, 2.0])) as ArrayRef,
+            ),
+            (
+                "hwy",
+                Arc::new(Int32Array::from(vec![Some(29), None, Some(31)])),
+            ),
+            ("class", Arc::new(StringArray::from(vec!["b", "a", "b"]))),
+        ])
+        .unwrap();
+
+        assert_eq!(*batch.column_f64("x"), [3.0, 1.0, 2.0]);
+        assert_eq!(batch.column_range_f64("x"), (1.0, 3.0));
+        assert_eq!(batch.column_range_f64("hwy"), (29.0, 31.0));
+        assert_eq!(batch.column_string("hwy"), ["29", "", "31"]);
+        assert_eq!(batch.column_len("class"), 3);
+        assert_eq!(batch.column_levels("class"), ["a", "b"]);
+    }
+
+    #[test]
+    fn record_batch_typed() {
+        let batch = RecordBatch::try_from_iter([
+            (
+                "x",
+                Arc::new(Float64Array::from(vec![1.5, 2.0])) as ArrayRef,
+            ),
+            ("flag", Arc::new(BooleanArray::from(vec![Some(true), None]))),
+            ("class", Arc::new(StringArray::from(vec!["b", "a"]))),
+            ("date", Arc::new(Date32Array::from(vec![Some(1), None]))),
+        ])
+        .unwrap();
+
+        assert_eq!(batch.column_names(), ["x", "flag", "class", "date"]);
+        assert_eq!(batch.column_type("x"), ColumnType::Float);
+        assert_eq!(batch.column_type("flag"), ColumnType::Boolean);
+        assert_eq!(batch.column_type("class"), ColumnType::String);
+        assert_eq!(batch.column_type("date"), ColumnType::Temporal);
+        assert_eq!(batch.column_i64("x"), [Some(1), Some(2)]);
+        assert_eq!(batch.column_bool("flag"), [Some(true), None]);
+        assert_eq!(batch.column_timestamp("date"), [Some(86_400_000), None]);
+    }
+}
@@ -0,0 +1,378 @@
+//! `Data` implementation for plain Rust collections
+//!
+//! - Maps from column names to columns, e.g. `HashMap<String, Vec<f64>>` or `HashMap<&str, &[f64]>`
+//! - Vectors and slices of records, e.g. structs with `#[derive(gongbi::Data)]`
+
+use std::{
+    borrow::{Borrow, Cow},
+    collections::HashMap,
+    hash::{BuildHasher, Hash},
+};
+
+use super::{ColumnType, Data};
+
+/// # Field trait
+///
+/// A value in a [`Record`], which can be read as a number or as a string.
+///
+/// It is implemented for the primitive numbers, `bool`, strings and `Option`s of them.
+pub trait Field {
+    /// Get the value as f64.
+    ///
+    /// Strings are parsed, and values which are not numbers are `NaN`.
+    fn to_f64(&self) -> f64;
+
+    /// Get the value as a string, e.g. as the level of a discrete scale.
+    fn to_label(&self) -> String;
+
+    /// Get the type of the column of the field.
+    fn column_type(&self) -> ColumnType;
+}
+
+macro_rules! impl_field_for_number {
+    ($column_type: ident: $($ty: ty),*) => {
+        $(
+            impl Field for $ty {
+                fn to_f64(&self) -> f64 {
+                    *self as f64
+                }
+
+                fn to_label(&self) -> String {
+                    self.to_string()
+                }
+
+                fn column_type(&self) -> ColumnType {
+                    ColumnType::$column_type
+                }
+            }
+        )*
+    };
+}
+
+impl_field_for_number!(Integer: i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+impl_field_for_number!(Float: f32, f64);
+
+impl Field for bool {
+    fn to_f64(&self) -> f64 {
+        if *self {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    fn to_label(&self) -> String {
+        self.to_string()
+    }
+
+    fn column_type(&self) -> ColumnType {
+        ColumnType::Boolean
+    }
+}
+
+impl Field for str {
+    fn to_f64(&self) -> f64 {
+        self.parse().unwrap_or(f64::NAN)
+    }
+
+    fn to_label(&self) -> String {
+        self.to_string()
+    }
+
+    fn column_type(&self) -> ColumnType {
+        ColumnType::String
+    }
+}
+
+impl Field for String {
+    fn to_f64(&self) -> f64 {
+        self.as_str().to_f64()
+    }
+
+    fn to_label(&self) -> String {
+        self.clone()
+    }
+
+    fn column_type(&self) -> ColumnType {
+        ColumnType::String
+    }
+}
+
+impl Field for &str {
+    fn to_f64(&self) -> f64 {
+        (*self).to_f64()
+    }
+
+    fn to_label(&self) -> String {
+        self.to_string()
+    }
+
+    fn column_type(&self) -> ColumnType {
+        ColumnType::String
+    }
+}
+
+/// A missing value is `NaN` as a number, and an empty string as a label.
+///
+/// The column type of a missing value is the one of `T::default()`.
+impl<T: Field + Default> Field for Option<T> {
+    fn to_f64(&self) -> f64 {
+        self.as_ref().map_or(f64::NAN, Field::to_f64)
+    }
+
+    fn to_label(&self) -> String {
+        self.as_ref().map(Field::to_label).unwrap_or_default()
+    }
+
+    fn column_type(&self) -> ColumnType {
+        match self {
+            Some(value) => value.column_type(),
+            None => T::default().column_type(),
+        }
+    }
+}
+
+/// # Record trait
+///
+/// A row of the data, whose fields are the columns. A vector or a slice of
+/// records is a [`Data`] source.
+///
+/// It is usually derived with `#[derive(gongbi::Data)]`, which names the
+/// columns after the fields of a struct:
+///
+/// ```
+/// # use gongbi::*;
+/// #[derive(Data)]
+/// struct Car {
+///     displ: f64,
+///     hwy: u32,
+///     class: String,
+/// }
+///
+/// let cars = vec![
+///     Car { displ: 1.8, hwy: 29, class: "compact".into() },
+///     Car { displ: 5.7, hwy: 17, class: "suv".into() },
+/// ];
+///
+/// let p = plot!(cars, aes!(displ, hwy, color = class)) + geom_point!();
+/// ```
+pub trait Record {
+    /// The names of the columns, i.e. the fields.
+    const COLUMN_NAMES: &'static [&'static str];
+
+    /// Get the field of a column, or `None` if there is no such column.
+    fn field(&self, column_name: &str) -> Option<&dyn Field>;
+}
+
+/// Get the fields of a column of the records.
+fn fields<'a, T: Record>(
+    records: &'a [T],
+    column_name: &'a str,
+) -> impl Iterator<Item = &'a dyn Field> {
+    records.iter().map(move |record| {
+        record
+            .field(column_name)
+            .unwrap_or_else(|| panic!("Column {column_name} not found in data"))
+    })
+}
+
+impl<T: Record + Send + Sync> Data for [T] {
+    fn column_names(&self) -> Vec<String> {
+        T::COLUMN_NAMES.iter().map(ToString::to_string).collect()
+    }
+
+    /// The type of the first record, or [`ColumnType::Other`] if there are no records.
+    fn column_type(&self, column_name: &str) -> ColumnType {
+        fields(self, column_name)
+            .next()
+            .map_or(ColumnType::Other, Field::column_type)
+    }
+
+    fn column_f64(&self, column_name: &str) -> Cow<'_, [f64]> {
+        fields(self, column_name).map(Field::to_f64).collect()
+    }
+
+    fn column_string(&self, column_name: &str) -> Vec<String> {
+        fields(self, column_name).map(Field::to_label).collect()
+    }
+
+    fn column_len(&self, column_name: &str) -> usize {
+        fields(self, column_name).count()
+    }
+}
+
+impl<T: Record + Send + Sync> Data for Vec<T> {
+    fn column_names(&self) -> Vec<String> {
+        self.as_slice().column_names()
+    }
+
+    fn column_type(&self, column_name: &str) -> ColumnType {
+        self.as_slice().column_type(column_name)
+    }
+
+    fn column_f64(&self, column_name: &str) -> Cow<'_, [f64]> {
+        self.as_slice().column_f64(column_name)
+    }
+
+    fn column_string(&self, column_name: &str) -> Vec<String> {
+        self.as_slice().column_string(column_name)
+    }
+
+    fn column_len(&self, column_name: &str) -> usize {
+        self.as_slice().column_len(column_name)
+    }
+}
+
+/// Get a column of a map from column names to columns.
+fn column<'a, K, V, S>(data: &'a HashMap<K, V, S>, column_name: &str) -> &'a [f64]
+where
+    K: Borrow<str> + Eq + Hash,
+    V: AsRef<[f64]>,
+    S: BuildHasher,
+{
+    data.get(column_name)
+        .unwrap_or_else(|| panic!("Column {column_name} not found in data"))
+        .as_ref()
+}
+
+impl<K, V, S> Data for HashMap<K, V, S>
+where
+    K: Borrow<str> + Eq + Hash + Send + Sync,
+    V: AsRef<[f64]> + Send + Sync,
+    S: BuildHasher + Send + Sync,
+{
+    /// The names of the columns in order.
+    fn column_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.keys().map(|name| name.borrow().to_string()).collect();
+        names.sort();
+
+        names
+    }
+
+    /// The columns are all floats.
+    fn column_type(&self, column_name: &str) -> ColumnType {
+        // Panic for a missing column like the other accessors
+        column(self, column_name);
+
+        ColumnType::Float
+    }
+
+    fn column_f64(&self, column_name: &str) -> Cow<'_, [f64]> {
+        Cow::Borrowed(column(self, column_name))
+    }
+
+    fn column_string(&self, column_name: &str) -> Vec<String> {
+        column(self, column_name)
+            .iter()
+            .map(f64::to_string)
+            .collect()
+    }
+
+    fn column_len(&self, column_name: &str) -> usize {
+        column(self, column_name).len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "derive")]
+    #[derive(crate::Data)]
+    struct Car {
+        displ: f64,
+        hwy: u32,
+        class: String,
+        r#type: Option<&'static str>,
+    }
+
+    #[cfg(feature = "derive")]
+    fn cars() -> Vec<Car> {
+        vec![
+            Car {
+                displ: 1.8,
+                hwy: 29,
+                class: "compact".into(),
+                r#type: Some("manual"),
+            },
+            Car {
+                displ: 5.7,
+                hwy: 17,
+                class: "suv".into(),
+                r#type: None,
+            },
+            Car {
+                displ: 2.0,
+                hwy: 31,
+                class: "compact".into(),
+                r#type: Some("auto"),
+            },
+        ]
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn records() {
+        let cars = cars();
+
+        assert_eq!(*cars.column_f64("displ"), [1.8, 5.7, 2.0]);
+        assert_eq!(*cars.column_f64("hwy"), [29.0, 17.0, 31.0]);
+        assert_eq!(cars.column_range_f64("hwy"), (17.0, 31.0));
+        assert_eq!(cars.column_len("class"), 3);
+        assert_eq!(cars.column_levels("class"), ["compact", "suv"]);
+        assert_eq!(cars.column_string("type"), ["manual", "", "auto"]);
+        assert_eq!(*cars[1..].column_f64("displ"), [5.7, 2.0]);
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn records_typed() {
+        let cars = cars();
+
+        assert_eq!(cars.column_names(), ["displ", "hwy", "class", "type"]);
+        assert_eq!(cars.column_type("displ"), ColumnType::Float);
+        assert_eq!(cars.column_type("hwy"), ColumnType::Integer);
+        assert_eq!(cars.column_type("class"), ColumnType::String);
+        assert_eq!(cars[1..].column_type("type"), ColumnType::String);
+        assert_eq!(cars.column_i64("hwy"), [Some(29), Some(17), Some(31)]);
+        assert_eq!(cars.column_i64("type"), [None, None, None]);
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    #[should_panic(expected = "Column cty not found in data")]
+    fn records_missing_column() {
+        cars().column_f64("cty");
+    }
+
+    #[test]
+    fn map_of_columns() {
+        let data = HashMap::from([
+            (String::from("x"), vec![3.0, 1.0, f64::NAN]),
+            (String::from("y"), vec![1.0, 2.0, 3.0]),
+        ]);
+
+        assert_eq!(*data.column_f64("y"), [1.0, 2.0, 3.0]);
+        assert!(matches!(data.column_f64("y"), Cow::Borrowed(_)));
+        assert_eq!(data.column_range_f64("x"), (1.0, 3.0));
+        assert_eq!(data.column_len("x"), 3);
+        assert_eq!(data.column_levels("y"), ["1", "2", "3"]);
+        assert_eq!(data.column_names(), ["x", "y"]);
+        assert_eq!(data.column_type("x"), ColumnType::Float);
+        assert_eq!(data.column_bool("x"), [Some(true), Some(true), None]);
+
+        let x: &[f64] = &[1.0, 2.0];
+        assert_eq!(*HashMap::from([("x", x)]).column_f64("x"), [1.0, 2.0]);
+    }
+
+    #[cfg(all(feature = "derive", feature = "polars"))]
+    #[test]
+    fn expr_of_columns() {
+        use polars::prelude::*;
+
+        let data = HashMap::from([("hwy", vec![29.0, 31.0]), ("cty", vec![18.0, 21.0])]);
+
+        assert_eq!(data.expr_f64(&(col("hwy") - col("cty"))), [11.0, 10.0]);
+        assert_eq!(cars().expr_f64(&(col("hwy") * lit(2))), [58.0, 34.0, 62.0]);
+    }
+}
@@ -1,37 +1,587 @@
-//! `Data` implementation for `polars` DataFrame
+//! `Data` implementation for `polars` DataFrame and LazyFrame
+
+#[cfg(any(feature = "csv", feature = "parquet"))]
+use std::path::Path;
+use std::{
+    borrow::Cow,
+    sync::{Arc, OnceLock},
+};
 
 use polars::prelude::*;
 
-impl super::Data for DataFrame {
-    fn column_f64(&self, column_name: &str) -> Vec<f64> {
-        let series = self
+use super::{ColumnType, Data};
+
+/// Get the column type of a `polars` data type.
+fn column_type(dtype: &DataType) -> ColumnType {
+    match dtype {
+        dtype if dtype.is_float() => ColumnType::Float,
+        dtype if dtype.is_integer() => ColumnType::Integer,
+        dtype if dtype.is_bool() => ColumnType::Boolean,
+        dtype if dtype.is_string() => ColumnType::String,
+        dtype if dtype.is_categorical() || dtype.is_enum() => ColumnType::Categorical,
+        dtype if dtype.is_temporal() => ColumnType::Temporal,
+        _ => ColumnType::Other,
+    }
+}
+
+/// Get a column cast to a data type, values which cannot be cast are null.
+fn cast(data: &DataFrame, column_name: &str, dtype: &DataType) -> Series {
+    data.column(column_name)
+        .unwrap_or_else(|_| panic!("Column {column_name} not found in data"))
+        .as_materialized_series()
+        .cast(dtype)
+        .unwrap_or_else(|err| panic!("Failed to cast {column_name} to {dtype}: {err}"))
+}
+
+impl Data for DataFrame {
+    fn column_names(&self) -> Vec<String> {
+        self.get_column_names()
+            .into_iter()
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    fn column_type(&self, column_name: &str) -> ColumnType {
+        let column = self
             .column(column_name)
-            .unwrap_or_else(|_| panic!("Column {column_name} not found in data"))
-            .as_series()
-            .expect("Only support Series for now")
-            .rechunk();
+            .unwrap_or_else(|_| panic!("Column {column_name} not found in data"));
+
+        column_type(column.dtype())
+    }
+
+    /// The values are borrowed if the column is f64 of one chunk without
+    /// missing values, e.g. after `DataFrame::rechunk_mut`.
+    fn column_f64(&self, column_name: &str) -> Cow<'_, [f64]> {
+        let column = self
+            .column(column_name)
+            .unwrap_or_else(|_| panic!("Column {column_name} not found in data"));
+
+        if let Ok(values) = column.f64().and_then(|values| values.cont_slice()) {
+            return Cow::Borrowed(values);
+        }
+
+        let values = cast(self, column_name, &DataType::Float64);
+        let values = values.f64().expect("Failed to extract f64");
+
+        values
+            .into_iter()
+            .map(|value| value.unwrap_or(f64::NAN))
+            .collect()
+    }
+
+    fn column_string(&self, column_name: &str) -> Vec<String> {
+        let series = cast(self, column_name, &DataType::String);
 
         let values = series
-            .iter()
-            .map(|any_val| any_val.try_extract::<f64>().expect("Failed to extract f64"));
+            .str()
+            .expect("Failed to extract string")
+            .into_iter()
+            .map(|s| s.unwrap_or_default().to_string());
 
         values.collect()
     }
 
-    fn column_range_f64(&self, column_name: &str) -> (f64, f64) {
-        let series = self
+    fn column_len(&self, column_name: &str) -> usize {
+        self.column(column_name).unwrap().len()
+    }
+
+    fn column_i64(&self, column_name: &str) -> Vec<Option<i64>> {
+        let values = cast(self, column_name, &DataType::Int64);
+
+        values
+            .i64()
+            .expect("Failed to extract i64")
+            .into_iter()
+            .collect()
+    }
+
+    fn column_bool(&self, column_name: &str) -> Vec<Option<bool>> {
+        let values = cast(self, column_name, &DataType::Boolean);
+
+        values
+            .bool()
+            .expect("Failed to extract bool")
+            .into_iter()
+            .collect()
+    }
+
+    /// Dates and datetimes are cast to milliseconds since the epoch, durations and
+    /// times of day are converted to milliseconds, other columns are taken as
+    /// milliseconds already.
+    fn column_timestamp(&self, column_name: &str) -> Vec<Option<i64>> {
+        let column = self
             .column(column_name)
-            .unwrap_or_else(|_| panic!("Column {column_name} not found in data"))
-            .as_series()
-            .expect("Only support Series for now");
+            .unwrap_or_else(|_| panic!("Column {column_name} not found in data"));
 
-        let min = series.min().unwrap().unwrap();
-        let max = series.max().unwrap().unwrap();
+        // Units of the physical values per millisecond
+        let (values, per_ms) = match column.dtype() {
+            DataType::Date | DataType::Datetime(..) => {
+                let dtype = DataType::Datetime(TimeUnit::Milliseconds, None);
+                (cast(self, column_name, &dtype), 1)
+            }
+            DataType::Duration(unit) => {
+                let per_ms = match unit {
+                    TimeUnit::Nanoseconds => 1_000_000,
+                    TimeUnit::Microseconds => 1_000,
+                    TimeUnit::Milliseconds => 1,
+                };
+                (column.as_materialized_series().clone(), per_ms)
+            }
+            DataType::Time => (column.as_materialized_series().clone(), 1_000_000),
+            _ => return self.column_i64(column_name),
+        };
+        let values = values.to_physical_repr();
 
-        (min, max)
+        values
+            .i64()
+            .expect("Failed to extract i64")
+            .into_iter()
+            .map(|value| value.map(|value| value.div_euclid(per_ms)))
+            .collect()
+    }
+
+    fn expr_f64(&self, expr: &Expr) -> Vec<f64> {
+        self.clone().lazy().expr_f64(expr)
+    }
+}
+
+/// Run the query of the lazy frame for the expressions only
+///
+/// With projection pushdown, only the columns used by the expressions are read from the source.
+fn select<E>(data: &LazyFrame, exprs: E) -> DataFrame
+where
+    E: AsRef<[Expr]>,
+{
+    collect(data.clone().select(exprs))
+}
+
+/// Run a query of the data, e.g. the aggregation of a layer.
+pub(crate) fn collect(query: LazyFrame) -> DataFrame {
+    query
+        .collect()
+        .unwrap_or_else(|err| panic!("Failed to query the data: {err}"))
+}
+
+/// Get the first value of a column of a query result, `None` if it is null or missing.
+///
+/// E.g. the minimum of a column is null if the column is empty or has only null values.
+fn first<T: num_traits::NumCast>(data: &DataFrame, column_name: &str) -> Option<T> {
+    data.column(column_name)
+        .and_then(|column| column.get(0))
+        .ok()
+        .and_then(|value| value.try_extract::<T>().ok())
+}
+
+/// Get the schema of the lazy frame, i.e. the names and types of the columns.
+fn schema(data: &LazyFrame) -> SchemaRef {
+    data.clone()
+        .collect_schema()
+        .unwrap_or_else(|err| panic!("Failed to query the data: {err}"))
+}
+
+impl Data for LazyFrame {
+    fn column_names(&self) -> Vec<String> {
+        schema(self)
+            .iter_names()
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    fn column_type(&self, column_name: &str) -> ColumnType {
+        let schema = schema(self);
+        let dtype = schema
+            .get(column_name)
+            .unwrap_or_else(|| panic!("Column {column_name} not found in data"));
+
+        column_type(dtype)
+    }
+
+    fn column_f64(&self, column_name: &str) -> Cow<'_, [f64]> {
+        let values = select(self, [col(column_name)])
+            .column_f64(column_name)
+            .into_owned();
+
+        Cow::Owned(values)
+    }
+
+    fn column_string(&self, column_name: &str) -> Vec<String> {
+        select(self, [col(column_name)]).column_string(column_name)
+    }
+
+    fn column_range_f64(&self, column_name: &str) -> (f64, f64) {
+        let column = col(column_name).cast(DataType::Float64);
+        let range = select(
+            self,
+            [column.clone().min().alias("min"), column.max().alias("max")],
+        );
+
+        // Without values, the range is empty like the one of a `DataFrame`
+        match (first(&range, "min"), first(&range, "max")) {
+            (Some(min), Some(max)) => (min, max),
+            _ => (f64::INFINITY, f64::NEG_INFINITY),
+        }
     }
 
     fn column_len(&self, column_name: &str) -> usize {
-        self.column(column_name).unwrap().len()
+        first(&select(self, [col(column_name).len().alias("len")]), "len").unwrap_or(0)
+    }
+
+    fn column_i64(&self, column_name: &str) -> Vec<Option<i64>> {
+        select(self, [col(column_name)]).column_i64(column_name)
+    }
+
+    fn column_bool(&self, column_name: &str) -> Vec<Option<bool>> {
+        select(self, [col(column_name)]).column_bool(column_name)
+    }
+
+    fn column_timestamp(&self, column_name: &str) -> Vec<Option<i64>> {
+        select(self, [col(column_name)]).column_timestamp(column_name)
+    }
+
+    fn column_levels(&self, column_name: &str) -> Vec<String> {
+        let levels = col(column_name)
+            .cast(DataType::String)
+            .fill_null(lit(""))
+            .unique()
+            .sort(SortOptions::default());
+
+        select(self, [levels]).column_string(column_name)
+    }
+
+    fn expr_f64(&self, expr: &Expr) -> Vec<f64> {
+        let values = self
+            .clone()
+            .select([expr.clone().cast(DataType::Float64)])
+            .collect()
+            .unwrap_or_else(|err| panic!("Failed to evaluate {expr}: {err}"));
+
+        values[0]
+            .f64()
+            .expect("Failed to extract f64")
+            .into_iter()
+            .map(|value| value.unwrap_or(f64::NAN))
+            .collect()
+    }
+
+    fn as_lazy(&self) -> Option<&LazyFrame> {
+        Some(self)
+    }
+}
+
+/// The columns of a lazy frame read by a layer
+///
+/// The columns are collected in one query on the first read, and the values, ranges,
+/// lengths and levels of the columns are then taken from the collected `DataFrame`.
+/// Other columns are still queried from the lazy frame.
+pub(crate) struct Projection {
+    /// The lazy frame to query
+    lazy: LazyFrame,
+
+    /// The sorted names of the columns to collect
+    columns: Vec<String>,
+
+    /// The collected columns
+    frame: OnceLock<DataFrame>,
+}
+
+impl Projection {
+    /// Get the collected columns, running the query on the first call.
+    fn frame(&self) -> &DataFrame {
+        self.frame.get_or_init(|| {
+            let columns: Vec<Expr> = self.columns.iter().map(|name| col(name.as_str())).collect();
+            let mut frame = select(&self.lazy, columns);
+            frame.rechunk_mut();
+
+            frame
+        })
+    }
+
+    /// Whether the column is collected by the projection.
+    fn contains(&self, column_name: &str) -> bool {
+        self.columns
+            .binary_search_by(|name| name.as_str().cmp(column_name))
+            .is_ok()
+    }
+
+    /// Get the source to read a column from, the collected columns if it is projected.
+    fn source(&self, column_name: &str) -> &dyn Data {
+        match self.contains(column_name) {
+            true => self.frame(),
+            false => &self.lazy,
+        }
+    }
+}
+
+impl Data for Projection {
+    fn column_names(&self) -> Vec<String> {
+        self.lazy.column_names()
+    }
+
+    fn column_type(&self, column_name: &str) -> ColumnType {
+        self.lazy.column_type(column_name)
+    }
+
+    fn column_f64(&self, column_name: &str) -> Cow<'_, [f64]> {
+        self.source(column_name).column_f64(column_name)
+    }
+
+    fn column_i64(&self, column_name: &str) -> Vec<Option<i64>> {
+        self.source(column_name).column_i64(column_name)
+    }
+
+    fn column_bool(&self, column_name: &str) -> Vec<Option<bool>> {
+        self.source(column_name).column_bool(column_name)
+    }
+
+    fn column_timestamp(&self, column_name: &str) -> Vec<Option<i64>> {
+        self.source(column_name).column_timestamp(column_name)
+    }
+
+    fn column_string(&self, column_name: &str) -> Vec<String> {
+        self.source(column_name).column_string(column_name)
+    }
+
+    fn column_range_f64(&self, column_name: &str) -> (f64, f64) {
+        self.source(column_name).column_range_f64(column_name)
+    }
+
+    fn column_len(&self, column_name: &str) -> usize {
+        self.source(column_name).column_len(column_name)
+    }
+
+    fn column_levels(&self, column_name: &str) -> Vec<String> {
+        self.source(column_name).column_levels(column_name)
+    }
+
+    fn expr_f64(&self, expr: &Expr) -> Vec<f64> {
+        let projected = expr
+            .clone()
+            .meta()
+            .root_names()
+            .iter()
+            .all(|name| self.contains(name));
+
+        match projected {
+            true => self.frame().expr_f64(expr),
+            false => self.lazy.expr_f64(expr),
+        }
+    }
+
+    fn as_lazy(&self) -> Option<&LazyFrame> {
+        Some(&self.lazy)
+    }
+}
+
+/// Project the columns read by a layer, if the data is a lazy frame.
+///
+/// Columns which are not in the data are left out, so that the query does not fail
+/// for a mapping inherited from the plot whose columns are only in the plot's data.
+/// Returns `None` if the data is not lazy or no column is projected.
+pub(crate) fn project(data: &dyn Data, columns: Vec<String>) -> Option<Arc<dyn Data>> {
+    let lazy = data.as_lazy()?;
+    let names = data.column_names();
+    let mut columns: Vec<String> = columns
+        .into_iter()
+        .filter(|column| names.contains(column))
+        .collect();
+    columns.sort();
+    columns.dedup();
+
+    if columns.is_empty() {
+        return None;
+    }
+
+    Some(Arc::new(Projection {
+        lazy: lazy.clone(),
+        columns,
+        frame: OnceLock::new(),
+    }))
+}
+
+/// Evaluate an expression against a data source, whose columns used by the expression are read as f64
+pub(super) fn expr_f64<D: Data + ?Sized>(data: &D, expr: &Expr) -> Vec<f64> {
+    let columns = expr
+        .clone()
+        .meta()
+        .root_names()
+        .into_iter()
+        .map(|name| {
+            let values = data.column_f64(&name);
+            Column::new(name, &*values)
+        })
+        .collect();
+
+    DataFrame::new(columns)
+        .unwrap_or_else(|err| panic!("Failed to evaluate {expr}: {err}"))
+        .expr_f64(expr)
+}
+
+/// Read a CSV file with a header into a `DataFrame`
+#[cfg(feature = "csv")]
+pub fn read_csv(path: impl AsRef<Path>) -> PolarsResult<DataFrame> {
+    CsvReadOptions::default()
+        .with_has_header(true)
+        .try_into_reader_with_file_path(Some(path.as_ref().to_path_buf()))?
+        .finish()
+}
+
+/// Scan a Parquet file as a `LazyFrame`
+///
+/// Parquet files are stored by columns, so only the columns used by the layers are read.
+#[cfg(feature = "parquet")]
+pub fn scan_parquet(path: impl AsRef<Path>) -> PolarsResult<LazyFrame> {
+    LazyFrame::scan_parquet(path, ScanArgsParquet::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use polars::prelude::*;
+
+    use crate::data::{ColumnType, Data};
+
+    #[test]
+    fn lazy_frame_matches_data_frame() {
+        let df = df!(
+            "x" => [3.0, 1.0, 2.0],
+            "class" => ["b", "a", "b"],
+        )
+        .unwrap();
+        let lf = df.clone().lazy();
+
+        assert_eq!(lf.column_f64("x"), df.column_f64("x"));
+        assert!(matches!(df.column_f64("x"), Cow::Borrowed(_)));
+        assert_eq!(lf.column_string("class"), df.column_string("class"));
+        assert_eq!(lf.column_range_f64("x"), (1.0, 3.0));
+        assert_eq!(lf.column_len("x"), 3);
+        assert_eq!(lf.column_levels("class"), ["a", "b"]);
+        assert_eq!(lf.column_levels("class"), df.column_levels("class"));
+        assert_eq!(lf.expr_f64(&(col("x") * lit(2.0))), [6.0, 2.0, 4.0]);
+    }
+
+    #[test]
+    fn projection_of_unordered_plan() {
+        let df = df!(
+            "id" => [1, 2, 3, 4, 5, 6],
+            "x" => [1.0, 2.0, 3.0, 4.0, 5.0, 6.0],
+            "class" => ["a", "b", "c", "d", "e", "f"],
+        )
+        .unwrap();
+        // The groups are in any order without `maintain_order`
+        let lf = df
+            .lazy()
+            .group_by([col("id")])
+            .agg([col("x").first(), col("class").first()])
+            .with_column((col("x") * lit(10.0)).alias("y"));
+
+        let projection = super::project(&lf, vec!["x".into(), "y".into(), "class".into()]).unwrap();
+        let x = projection.column_f64("x");
+        let y = projection.column_f64("y");
+        let class = projection.column_string("class");
+
+        for ((x, y), class) in x.iter().zip(y.iter()).zip(&class) {
+            assert_eq!(*y, x * 10.0);
+            assert_eq!(class, ["a", "b", "c", "d", "e", "f"][*x as usize - 1]);
+        }
+        assert_eq!(projection.column_range_f64("y"), (10.0, 60.0));
+        assert_eq!(projection.column_len("class"), 6);
+        assert_eq!(projection.column_levels("class").len(), 6);
+        assert_eq!(projection.expr_f64(&(col("y") / col("x"))), [10.0; 6]);
+        assert_eq!(
+            *projection
+                .column_f64("id")
+                .iter()
+                .max_by(|a, b| a.total_cmp(b))
+                .unwrap(),
+            6.0
+        );
+        assert!(projection.as_lazy().is_some());
+
+        // Only the columns of the data are projected, and not for eager data
+        assert!(super::project(&lf, vec!["missing".into()]).is_none());
+        assert!(super::project(&df!("x" => [1.0]).unwrap(), vec!["x".into()]).is_none());
+    }
+
+    #[test]
+    fn typed_columns() {
+        let df = df!(
+            "x" => [3.5, 1.0, 2.0],
+            "n" => [Some(1i64), None, Some(3)],
+            "flag" => [true, false, true],
+            "class" => ["b", "a", "b"],
+        )
+        .unwrap();
+        let df = df
+            .lazy()
+            .with_columns([
+                col("n")
+                    .cast(DataType::Datetime(TimeUnit::Milliseconds, None))
+                    .alias("time"),
+                (col("n") * lit(1_000i64))
+                    .cast(DataType::Duration(TimeUnit::Microseconds))
+                    .alias("duration"),
+                (col("n") * lit(1_000_000i64))
+                    .cast(DataType::Time)
+                    .alias("clock"),
+            ])
+            .collect()
+            .unwrap();
+
+        assert_eq!(
+            df.column_names(),
+            ["x", "n", "flag", "class", "time", "duration", "clock"]
+        );
+        assert_eq!(df.column_type("x"), ColumnType::Float);
+        assert_eq!(df.column_type("n"), ColumnType::Integer);
+        assert_eq!(df.column_type("flag"), ColumnType::Boolean);
+        assert_eq!(df.column_type("class"), ColumnType::String);
+        assert_eq!(df.column_type("time"), ColumnType::Temporal);
+        assert_eq!(df.column_type("duration"), ColumnType::Temporal);
+        assert_eq!(df.column_type("clock"), ColumnType::Temporal);
+        assert_eq!(df.column_i64("x"), [Some(3), Some(1), Some(2)]);
+        assert_eq!(df.column_i64("class"), [None, None, None]);
+        assert_eq!(
+            df.column_bool("flag"),
+            [Some(true), Some(false), Some(true)]
+        );
+        assert_eq!(df.column_timestamp("time"), [Some(1), None, Some(3)]);
+        assert_eq!(df.column_timestamp("duration"), [Some(1), None, Some(3)]);
+        assert_eq!(df.column_timestamp("clock"), [Some(1), None, Some(3)]);
+
+        let lf = df.clone().lazy();
+        assert_eq!(lf.column_names(), df.column_names());
+        assert_eq!(lf.column_type("time"), ColumnType::Temporal);
+        assert_eq!(lf.column_i64("n"), df.column_i64("n"));
+        assert_eq!(lf.column_timestamp("time"), df.column_timestamp("time"));
+        assert_eq!(
+            lf.column_timestamp("duration"),
+            df.column_timestamp("duration")
+        );
+        assert_eq!(lf.column_timestamp("clock"), df.column_timestamp("clock"));
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn read_csv() {
+        let mpg = super::read_csv("examples/mpg.csv").unwrap();
+
+        assert_eq!(mpg.column_len("hwy"), 234);
+        assert_eq!(mpg.column_levels("drv"), ["4", "f", "r"]);
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn scan_parquet() {
+        let mut df = df!("x" => [3.0, 1.0, 2.0], "class" => ["b", "a", "b"]).unwrap();
+        let path = std::env::temp_dir().join("gongbi_scan_parquet.parquet");
+        ParquetWriter::new(std::fs::File::create(&path).unwrap())
+            .finish(&mut df)
+            .unwrap();
+
+        let lf = super::scan_parquet(&path).unwrap();
+
+        assert_eq!(*lf.column_f64("x"), [3.0, 1.0, 2.0]);
+        assert_eq!(lf.column_levels("class"), ["a", "b"]);
     }
 }
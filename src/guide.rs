@@ -0,0 +1,424 @@
+//! Guide module
+//!
+//! Guides explain the scales of a plot, e.g. the colorbar of a continuous fill scale,
+//! the legend of a discrete color scale, or the secondary axis of a position scale.
+
+use std::ops::Range;
+
+use plotters::{
+    coord::{
+        ranged1d::{KeyPointHint, NoDefaultFormatting, Ranged, ValueFormatter},
+        types::RangedCoordf64,
+        Shift,
+    },
+    element::Text,
+    prelude::{DrawingArea, DrawingBackend, Rectangle},
+    style::{
+        text_anchor::{HPos, Pos, VPos},
+        Color, IntoFont, BLACK,
+    },
+};
+
+use crate::scale::{format::Format, Continuous, ContinuousColor, DiscreteColor, SecAxis};
+
+/// The width in pixels of the area reserved for a guide.
+pub(crate) const GUIDE_WIDTH: u32 = 120;
+
+/// Get the breaks of a range, with a step of 1, 2 or 5 times a power of 10.
+///
+/// The step giving the number of breaks closest to `n` is chosen.
+/// Returns the breaks and the step.
+pub(crate) fn breaks(low: f64, high: f64, n: usize) -> (Vec<f64>, f64) {
+    let raw_step = (high - low) / n.max(1) as f64;
+    if raw_step.is_nan() || raw_step <= 0.0 {
+        return (vec![low], 1.0);
+    }
+
+    let magnitude = 10f64.powf(raw_step.log10().floor());
+    let count = |step: f64| (high / step).floor() - (low / step).ceil() + 1.0;
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|m| m * magnitude)
+        .min_by(|a, b| {
+            (count(*a) - n as f64)
+                .abs()
+                .total_cmp(&(count(*b) - n as f64).abs())
+        })
+        .unwrap();
+
+    let start = (low / step).ceil() * step;
+    let breaks = (0..)
+        .map(|i| start + i as f64 * step)
+        .take_while(|v| *v <= high + step * 1e-9)
+        .collect();
+
+    (breaks, step)
+}
+
+/// Format a break with enough decimals for its step.
+pub(crate) fn format_break(value: f64, step: f64) -> String {
+    let decimals = (-step.log10().floor()).max(0.0) as usize;
+
+    format!("{value:.decimals$}")
+}
+
+/// A guide explaining a color scale, with its title.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Guide {
+    /// The legend of a discrete color scale
+    Legend(DiscreteColor, Option<String>),
+
+    /// The colorbar of a continuous color scale
+    Colorbar(ContinuousColor, Option<String>),
+}
+
+/// Draw the guides from the top to the bottom of the area, each with the same height.
+pub(crate) fn draw_guides<DB>(area: &DrawingArea<DB, Shift>, guides: &[Guide]) -> anyhow::Result<()>
+where
+    DB: DrawingBackend,
+    <DB as plotters::prelude::DrawingBackend>::ErrorType: 'static,
+{
+    let (_, h) = area.dim_in_pixel();
+    let n = guides.len() as u32;
+    let breakpoints: Vec<u32> = (1..n).map(|i| i * h / n).collect();
+    let areas = area.split_by_breakpoints([] as [u32; 0], breakpoints);
+
+    for (guide, area) in guides.iter().zip(areas) {
+        match guide {
+            Guide::Legend(scale, title) => draw_legend(&area, scale, title.as_deref())?,
+            Guide::Colorbar(scale, title) => draw_colorbar(&area, scale, title.as_deref())?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Draw a vertical colorbar of a continuous color scale.
+pub(crate) fn draw_colorbar<DB>(
+    area: &DrawingArea<DB, Shift>,
+    scale: &ContinuousColor,
+    title: Option<&str>,
+) -> anyhow::Result<()>
+where
+    DB: DrawingBackend,
+    <DB as plotters::prelude::DrawingBackend>::ErrorType: 'static,
+{
+    let Some((low, high)) = scale.limits() else {
+        return Ok(());
+    };
+
+    let (_, h) = area.dim_in_pixel();
+    let (bar_w, bar_h) = (20, (h / 3) as i32);
+    let (x0, y0) = (10, (h as i32 - bar_h) / 2);
+
+    if let Some(title) = title {
+        area.draw(&Text::new(
+            title.to_string(),
+            (x0, y0 - 10),
+            ("sans-serif", 16)
+                .into_font()
+                .color(&BLACK)
+                .pos(Pos::new(HPos::Left, VPos::Bottom)),
+        ))?;
+    }
+
+    for i in 0..bar_h {
+        let t = 1.0 - (i as f64 + 0.5) / bar_h as f64;
+        area.draw(&Rectangle::new(
+            [(x0, y0 + i), (x0 + bar_w, y0 + i + 1)],
+            scale.gradient(t).filled(),
+        ))?;
+    }
+
+    let label_style = ("sans-serif", 14)
+        .into_font()
+        .color(&BLACK)
+        .pos(Pos::new(HPos::Left, VPos::Center));
+    let (values, step) = breaks(low, high, 4);
+    for value in values {
+        let t = if high > low {
+            (value - low) / (high - low)
+        } else {
+            0.5
+        };
+        let y = y0 + ((1.0 - t) * bar_h as f64).round() as i32;
+
+        area.draw(&Rectangle::new(
+            [(x0 + bar_w - 4, y), (x0 + bar_w, y + 1)],
+            BLACK.filled(),
+        ))?;
+        area.draw(&Text::new(
+            format_break(value, step),
+            (x0 + bar_w + 5, y),
+            label_style.clone(),
+        ))?;
+    }
+
+    Ok(())
+}
+
+/// Draw a vertical legend of a discrete color scale, with a key for each level.
+pub(crate) fn draw_legend<DB>(
+    area: &DrawingArea<DB, Shift>,
+    scale: &DiscreteColor,
+    title: Option<&str>,
+) -> anyhow::Result<()>
+where
+    DB: DrawingBackend,
+    <DB as plotters::prelude::DrawingBackend>::ErrorType: 'static,
+{
+    let levels = scale.levels();
+    if levels.is_empty() {
+        return Ok(());
+    }
+
+    let (_, h) = area.dim_in_pixel();
+    let key = 16;
+    let x0 = 10;
+    let y0 = (h as i32 - key * levels.len() as i32) / 2;
+
+    if let Some(title) = title {
+        area.draw(&Text::new(
+            title.to_string(),
+            (x0, y0 - 10),
+            ("sans-serif", 16)
+                .into_font()
+                .color(&BLACK)
+                .pos(Pos::new(HPos::Left, VPos::Bottom)),
+        ))?;
+    }
+
+    let label_style = ("sans-serif", 14)
+        .into_font()
+        .color(&BLACK)
+        .pos(Pos::new(HPos::Left, VPos::Center));
+    for (i, level) in levels.iter().enumerate() {
+        let y = y0 + i as i32 * key;
+
+        area.draw(&Rectangle::new(
+            [(x0, y + 2), (x0 + key - 4, y + key - 2)],
+            scale.map(level).filled(),
+        ))?;
+        area.draw(&Text::new(
+            level.clone(),
+            (x0 + key + 2, y + key / 2),
+            label_style.clone(),
+        ))?;
+    }
+
+    Ok(())
+}
+
+/// Get the smallest step between consecutive breaks, used to format them with enough decimals.
+fn breaks_step(breaks: &[f64]) -> f64 {
+    breaks
+        .windows(2)
+        .map(|w| (w[1] - w[0]).abs())
+        .filter(|step| *step > 0.0)
+        .reduce(f64::min)
+        .unwrap_or(1.0)
+}
+
+/// The coordinate of an axis with the breaks and the labels of its position scale.
+///
+/// Without breaks or labels in the scale, the breaks and the labels of plotters are kept.
+pub(crate) struct AxisCoord {
+    coord: RangedCoordf64,
+    breaks: Option<Vec<f64>>,
+    minor_breaks: Option<Vec<f64>>,
+    labels: Option<Format>,
+}
+
+impl AxisCoord {
+    /// Create the coordinate of the axis of a scale on a range.
+    pub(crate) fn new(scale: &Continuous, range: Range<f64>) -> Self {
+        let breaks = scale.breaks().map(|breaks| breaks.resolve(range.clone()));
+
+        // The minor breaks are between the breaks by default, like ggplot2
+        let minor_breaks = match (scale.minor_breaks(), &breaks) {
+            (Some(minor_breaks), _) => Some(minor_breaks.resolve(range.clone())),
+            (None, Some(breaks)) => Some(breaks.windows(2).map(|w| (w[0] + w[1]) / 2.0).collect()),
+            (None, None) => None,
+        };
+
+        AxisCoord {
+            coord: range.into(),
+            breaks,
+            minor_breaks,
+            labels: scale.labels().cloned(),
+        }
+    }
+}
+
+impl Ranged for AxisCoord {
+    type FormatOption = NoDefaultFormatting;
+    type ValueType = f64;
+
+    fn map(&self, value: &f64, limit: (i32, i32)) -> i32 {
+        self.coord.map(value, limit)
+    }
+
+    fn key_points<Hint: KeyPointHint>(&self, hint: Hint) -> Vec<f64> {
+        // The light points are the positions of the minor grid lines
+        let points = match hint.weight().allow_light_points() {
+            true => &self.minor_breaks,
+            false => &self.breaks,
+        };
+
+        match points {
+            Some(points) => points.clone(),
+            None => self.coord.key_points(hint),
+        }
+    }
+
+    fn range(&self) -> Range<f64> {
+        self.coord.range()
+    }
+}
+
+impl ValueFormatter<f64> for AxisCoord {
+    fn format_ext(&self, value: &f64) -> String {
+        match &self.labels {
+            Some(labels) => labels.format(*value),
+            None => self.coord.format_ext(value),
+        }
+    }
+}
+
+/// The coordinate of a secondary axis, with the same pixels as the primary range.
+///
+/// The values of the secondary axis are mapped back to the primary range by
+/// bisection, so any monotonic transformation can be used.
+pub(crate) struct SecondaryCoord {
+    sec_axis: SecAxis,
+    primary: Range<f64>,
+    breaks: Vec<f64>,
+    step: f64,
+    labels: Option<Format>,
+}
+
+impl SecondaryCoord {
+    /// Create the coordinate of the secondary axis of a primary range.
+    pub(crate) fn new(sec_axis: &SecAxis, primary: Range<f64>) -> Self {
+        let (start, end) = (
+            sec_axis.transform(primary.start),
+            sec_axis.transform(primary.end),
+        );
+        let breaks = match sec_axis.breaks() {
+            Some(breaks) => breaks.resolve(start..end),
+            None => breaks(start.min(end), start.max(end), 10).0,
+        };
+
+        SecondaryCoord {
+            sec_axis: sec_axis.clone(),
+            primary,
+            step: breaks_step(&breaks),
+            breaks,
+            labels: sec_axis.labels().cloned(),
+        }
+    }
+
+    /// Get the position of the primary range transformed to a value.
+    fn inverse(&self, value: f64) -> f64 {
+        let (mut low, mut high) = (self.primary.start, self.primary.end);
+        let increasing = self.sec_axis.transform(high) >= self.sec_axis.transform(low);
+
+        for _ in 0..64 {
+            let mid = (low + high) / 2.0;
+            if (self.sec_axis.transform(mid) < value) == increasing {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+
+        (low + high) / 2.0
+    }
+}
+
+impl Ranged for SecondaryCoord {
+    type FormatOption = NoDefaultFormatting;
+    type ValueType = f64;
+
+    fn map(&self, value: &f64, limit: (i32, i32)) -> i32 {
+        RangedCoordf64::from(self.primary.clone()).map(&self.inverse(*value), limit)
+    }
+
+    fn key_points<Hint: KeyPointHint>(&self, hint: Hint) -> Vec<f64> {
+        if self.breaks.len() <= hint.max_num_points() || self.sec_axis.breaks().is_some() {
+            return self.breaks.clone();
+        }
+
+        let Range { start, end } = self.range();
+        breaks(start, end, hint.max_num_points()).0
+    }
+
+    fn range(&self) -> Range<f64> {
+        let (start, end) = (
+            self.sec_axis.transform(self.primary.start),
+            self.sec_axis.transform(self.primary.end),
+        );
+
+        start.min(end)..start.max(end)
+    }
+}
+
+impl ValueFormatter<f64> for SecondaryCoord {
+    fn format_ext(&self, value: &f64) -> String {
+        match &self.labels {
+            Some(labels) => labels.format(*value),
+            None => format_break(*value, self.step),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breaks_of_range() {
+        assert_eq!(breaks(0.0, 1.0, 4), (vec![0.0, 0.5, 1.0], 0.5));
+        assert_eq!(breaks(3.0, 97.0, 4), (vec![20.0, 40.0, 60.0, 80.0], 20.0));
+        assert_eq!(breaks(2.0, 2.0, 4), (vec![2.0], 1.0));
+    }
+
+    #[test]
+    fn secondary_coord_inverse() {
+        let sec_axis = SecAxis::builder()
+            .transform(|y| 100.0 - y * y)
+            .build()
+            .unwrap();
+        let coord = SecondaryCoord::new(&sec_axis, 0.0..10.0);
+
+        assert_eq!(coord.range(), 0.0..100.0);
+        assert_eq!(coord.map(&100.0, (0, 100)), 0);
+        assert_eq!(coord.map(&64.0, (0, 100)), 60);
+        assert_eq!(coord.map(&0.0, (0, 100)), 100);
+        assert_eq!(coord.format_ext(&20.0), "20");
+    }
+
+    #[test]
+    fn axis_coord_breaks() {
+        let scale = Continuous::builder()
+            .breaks(vec![0.0, 2.0, 4.0, 20.0])
+            .labels(|value: f64| format!("{value}s"))
+            .build()
+            .unwrap();
+        let coord = AxisCoord::new(&scale, 0.0..10.0);
+
+        assert_eq!(
+            coord.key_points(plotters::coord::ranged1d::BoldPoints(4)),
+            [0.0, 2.0, 4.0]
+        );
+        assert_eq!(coord.format_ext(&2.0), "2s");
+        assert_eq!(coord.minor_breaks, Some(vec![1.0, 3.0]));
+    }
+
+    #[test]
+    fn format_break_decimals() {
+        assert_eq!(format_break(0.5, 0.5), "0.5");
+        assert_eq!(format_break(40.0, 20.0), "40");
+        assert_eq!(format_break(0.02, 0.01), "0.02");
+    }
+}
@@ -7,17 +7,40 @@ use derive_builder::Builder;
 /// # Label layer
 ///
 /// This layer is used to add important information to the plot.
+///
+/// Legends are only drawn for `color` and `fill`, so only these have titles here.
+/// `size` and `shape` can only be set to a constant for now, their legend titles
+/// are out of scope until they can be mapped to columns.
 #[derive(Clone, Debug, Default, PartialEq, Builder)]
 #[builder(default, setter(into, strip_option))]
 pub struct Label {
-    /// The caption of the label.
+    /// The title of the plot, drawn at the top left.
+    pub title: Option<String>,
+
+    /// The subtitle of the plot, drawn below the title.
+    pub subtitle: Option<String>,
+
+    /// The caption of the plot, drawn at the bottom right, e.g. the source of the data.
     pub caption: Option<String>,
 
+    /// The tag of the plot, drawn at the top left corner, e.g. "A" for the first panel of a figure.
+    pub tag: Option<String>,
+
     /// The x-axis description.
     pub x: Option<String>,
 
     /// The y-axis description.
     pub y: Option<String>,
+
+    /// The title of the legend of `color`.
+    ///
+    /// Defaults to the column mapped to `color`.
+    pub color: Option<String>,
+
+    /// The title of the legend of `fill`.
+    ///
+    /// Defaults to the column mapped to `fill`.
+    pub fill: Option<String>,
 }
 
 impl Label {
@@ -36,17 +59,26 @@ impl Label {
 ///
 /// ```ignore
 /// labs!(
+///     title = <Title>,
+///     subtitle = <Subtitle>,
 ///     caption = <Caption>,
+///     tag = <Tag>,
 ///     x = <X>,
 ///     y = <Y>,
+///     color = <Color>,
+///     fill = <Fill>,
 /// )
 /// ```
 ///
 /// ### Arguments
 ///
-/// - `caption`: Also known as the title of the plot.
+/// - `title`: The title of the plot, at the top left.
+/// - `subtitle`: The subtitle of the plot, below the title.
+/// - `caption`: The caption of the plot, at the bottom right.
+/// - `tag`: The tag of the plot, at the top left corner.
 /// - `x`: The x-axis description.
 /// - `y`: The y-axis description.
+/// - `color`, `fill`: The titles of the legends of these aesthetics.
 #[macro_export]
 macro_rules! labs {
     ($($arg: ident = $val: expr),* $(,)?) => {
@@ -62,9 +94,14 @@ impl Add for Label {
 
     fn add(self, rhs: Self) -> Self::Output {
         Self {
+            title: self.title.or(rhs.title),
+            subtitle: self.subtitle.or(rhs.subtitle),
             caption: self.caption.or(rhs.caption),
+            tag: self.tag.or(rhs.tag),
             x: self.x.or(rhs.x),
             y: self.y.or(rhs.y),
+            color: self.color.or(rhs.color),
+            fill: self.fill.or(rhs.fill),
         }
     }
 }
@@ -79,4 +116,14 @@ mod tests {
         assert_eq!(label.x, Some("X".to_string()));
         assert_eq!(label.y, Some("Y".to_string()));
     }
+
+    #[test]
+    fn test_label_add() {
+        let label = labs!(title = "Title", color = "Color") + labs!(title = "Other", tag = "A");
+
+        assert_eq!(label.title, Some("Title".to_string()));
+        assert_eq!(label.tag, Some("A".to_string()));
+        assert_eq!(label.color, Some("Color".to_string()));
+        assert_eq!(label.subtitle, None);
+    }
 }
@@ -1,51 +1,136 @@
 //! # Layer module
 //!
 //! This module contains the [`Layer`] trait and many geometric layers that implement it.
+//! One-off texts and shapes can be added with the [`annotate`] layer.
 //!
 //! ## List of geometric layers
 //!
 //! - [`geom::point`]
 //! - [`geom::line`]
+//! - [`geom::text`]
+//! - [`geom::label`]
+//! - [`geom::reference`]
+//! - [`geom::bar`]
+//! - [`geom::tile`]
+//! - [`geom::raster`]
+//! - [`geom::bin2d`]
+//! - [`geom::hex`]
+//! - [`geom::density`]
+//! - [`geom::density_2d`]
+//! - [`geom::contour`]
 
-use std::{fmt::Debug, rc::Rc};
+use std::{fmt::Debug, sync::Arc};
 
 use dyn_clone::DynClone;
-use plotters::{
-    chart::ChartContext,
-    coord::types::RangedCoordf64,
-    prelude::{BitMapBackend, Cartesian2d, SVGBackend},
-};
+#[cfg(feature = "png")]
+use plotters::prelude::BitMapBackend;
+#[cfg(feature = "svg")]
+use plotters::prelude::SVGBackend;
+use plotters::{chart::ChartContext, coord::types::RangedCoordf64, prelude::Cartesian2d};
 
+use crate::{coord::Panel, scale::Scales};
+
+pub mod annotate;
+pub mod downsample;
 pub mod geom;
+pub(crate) mod stat;
 
 /// # Layer trait
 ///
 /// This trait abstracts the concept of a layer in a plot.
-pub trait Layer: DynClone + Debug {
+///
+/// The layers of a plot are computed in parallel, so they must be `Send` and `Sync`.
+pub trait Layer: DynClone + Debug + Send + Sync {
+    /// Whether the layer needs data to be drawn.
+    ///
+    /// This method is called when adding a layer to a plot.
+    /// A layer requiring data panics if neither the layer nor the plot has data.
+    fn requires_data(&self) -> bool {
+        true
+    }
+
     /// Get the mutable reference to the mapping.
     ///
     /// This method is called when adding a layer to a plot.
     /// It allows the layer inheriting the mapping from the plot.
     fn mapping_mut(&mut self) -> &mut crate::aes::Aes;
 
+    /// Get the names of the columns read by the layer besides its mapping, e.g. the intercepts of reference lines.
+    ///
+    /// This method is called when adding a layer to a plot.
+    /// The columns of the mapping and these columns are read from a lazy data source in one query.
+    fn columns(&self) -> Vec<String> {
+        Vec::new()
+    }
+
     /// Get the mutable reference to the data.
     ///
     /// This method is called when adding a layer to a plot.
     /// It allows the layer inheriting the data from the plot.
-    fn data_mut(&mut self) -> &mut Option<Rc<dyn crate::data::Data>>;
+    fn data_mut(&mut self) -> &mut Option<Arc<dyn crate::data::Data>>;
+
+    /// Compute the statistics of the layer ahead of drawing, e.g. the counts of bins.
+    ///
+    /// This method is called for all layers in parallel, before the scales are trained.
+    /// The statistics are kept by the layer for its range, scales and drawing.
+    fn compute(&self) {}
 
     /// Get the range of the x-axis and y-axis needed to draw the layer.
     ///
     /// This method is called when drawing the layer.
-    fn range_2d(&self) -> (f64, f64, f64, f64);
+    /// Returns `None` if the layer does not affect the range of the axes,
+    /// and the empty range `(f64::INFINITY, f64::NEG_INFINITY)` for an axis it does not affect,
+    /// e.g. the x-axis of horizontal lines.
+    fn range_2d(&self) -> Option<(f64, f64, f64, f64)>;
+
+    /// Get the range of the values mapped to the `fill` aesthetic.
+    ///
+    /// This method is called when training the fill scale of the plot.
+    /// Returns `None` if the layer does not map any column to `fill`.
+    fn fill_range(&self) -> Option<(f64, f64)> {
+        None
+    }
+
+    /// Get the column mapped to the `color` aesthetic and its distinct values.
+    ///
+    /// This method is called when training the color scale of the plot.
+    /// Returns `None` if the layer does not map any column to `color`.
+    fn color_levels(&self) -> Option<(&str, Vec<String>)> {
+        None
+    }
+
+    /// Get the title of the `fill` guide for the values computed by the layer.
+    ///
+    /// This method is called when drawing the guides of the plot.
+    /// Returns `None` if the layer does not compute the values mapped to `fill`, e.g. counts.
+    fn fill_title(&self) -> Option<&str> {
+        None
+    }
+
+    /// Get the smallest distances between the distinct x positions and y positions of the layer.
+    ///
+    /// This method is called by polar coordinates to leave a step between the
+    /// last and the first positions around the circle.
+    /// Returns `None` if the layer already leaves space around its positions, e.g. bars.
+    fn resolution_2d(&self) -> Option<(f64, f64)> {
+        None
+    }
 
     /// Draw the layer on a 2D SVG chart context.
+    ///
+    /// The positions of the data are mapped to the chart through the panel.
+    #[cfg(feature = "svg")]
     fn draw_svg_2d<'a>(
         &'a self,
         chart: &mut ChartContext<'a, SVGBackend<'a>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+        panel: &Panel,
+        scales: &Scales,
     ) -> anyhow::Result<()>;
 
     /// Draw the layer on a 2D PNG chart context.
+    ///
+    /// The positions of the data are mapped to the chart through the panel.
+    #[cfg(feature = "png")]
     fn draw_png_2d<'a>(
         &'a self,
         chart: &mut ChartContext<
@@ -53,6 +138,8 @@ pub trait Layer: DynClone + Debug {
             BitMapBackend<'a>,
             Cartesian2d<RangedCoordf64, RangedCoordf64>,
         >,
+        panel: &Panel,
+        scales: &Scales,
     ) -> anyhow::Result<()>;
 }
 
@@ -0,0 +1,532 @@
+//! # Annotation layer
+//!
+//! The annotation layer adds one-off texts and shapes at data coordinates.
+//! Unlike geom layers, the positions are given directly instead of mapped
+//! from a data source, so no data frame is needed.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use gongbi::*;
+//! # fn main() -> anyhow::Result<()> {
+//! let p = plot!(csv = "examples/mpg.csv", mapping = aes!(displ, hwy))
+//!     + geom_point!()
+//!     + annotate!("rect", xmin = 5, xmax = 7, ymin = 21, ymax = 27, alpha = 0.2)
+//!     + annotate!("text", x = 6, y = 28, label = "2seater");
+//!
+//! p.to_svg("annotate.svg", (800, 600))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! See [`annotate!`](crate::annotate!) or [`Annotate::builder`] for more details.
+
+use std::{ops::Range, str::FromStr, sync::Arc};
+
+use derive_builder::Builder;
+use plotters::{
+    chart::ChartContext,
+    coord::types::RangedCoordf64,
+    element::Text as TextElement,
+    prelude::{Cartesian2d, Circle, DrawingBackend, PathElement},
+    style::{
+        text_anchor::{HPos, Pos, VPos},
+        Color as _, IntoFont,
+    },
+};
+
+use crate::{aes::color::Color, coord::Panel};
+
+use super::{
+    geom::{draw_rects, Values},
+    Layer,
+};
+
+/// The geometric object of an annotation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnnotateGeom {
+    /// Texts at `x` and `y`
+    Text,
+
+    /// Points at `x` and `y`
+    Point,
+
+    /// Rectangles from (`xmin`, `ymin`) to (`xmax`, `ymax`)
+    Rect,
+
+    /// Line segments from (`x`, `y`) to (`xend`, `yend`)
+    Segment,
+}
+
+impl FromStr for AnnotateGeom {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(AnnotateGeom::Text),
+            "point" => Ok(AnnotateGeom::Point),
+            "rect" => Ok(AnnotateGeom::Rect),
+            "segment" => Ok(AnnotateGeom::Segment),
+            _ => Err(format!("Unsupported annotation geom: {s}")),
+        }
+    }
+}
+
+impl From<&str> for AnnotateGeom {
+    fn from(value: &str) -> Self {
+        AnnotateGeom::from_str(value).unwrap()
+    }
+}
+
+/// # Annotate layer
+///
+/// The annotation layer is used to add one-off texts and shapes to the plot.
+///
+/// The position parameters accept a single value or multiple values, and a
+/// single value is shared by all annotations. Infinite values stretch the
+/// annotation to the edge of the plot.
+///
+/// To create an annotation layer, [`annotate!`](crate::annotate!) and [`Annotate::builder`] can be used.
+#[derive(Clone, Debug, Builder)]
+#[builder(setter(into, strip_option))]
+pub struct Annotate {
+    /// The geometric object of the annotation.
+    geom: AnnotateGeom,
+
+    /// The data source for the layer.
+    ///
+    /// Only used when a position parameter is a column.
+    #[builder(default)]
+    data: Option<Arc<dyn crate::data::Data>>,
+
+    /// The aes mapping for the layer.
+    ///
+    /// Annotations do not use aesthetic mappings, so the mapping inherited
+    /// from the plot is ignored.
+    #[builder(default, setter(skip))]
+    mapping: crate::aes::Aes,
+
+    /// The x positions of texts, points and the start of segments.
+    #[builder(default)]
+    x: Option<Values>,
+
+    /// The y positions of texts, points and the start of segments.
+    #[builder(default)]
+    y: Option<Values>,
+
+    /// The x positions of the end of segments.
+    #[builder(default)]
+    xend: Option<Values>,
+
+    /// The y positions of the end of segments.
+    #[builder(default)]
+    yend: Option<Values>,
+
+    /// The left edges of rectangles.
+    #[builder(default)]
+    xmin: Option<Values>,
+
+    /// The right edges of rectangles.
+    #[builder(default)]
+    xmax: Option<Values>,
+
+    /// The bottom edges of rectangles.
+    #[builder(default)]
+    ymin: Option<Values>,
+
+    /// The top edges of rectangles.
+    #[builder(default)]
+    ymax: Option<Values>,
+
+    /// The text of text annotations.
+    #[builder(default)]
+    label: Option<String>,
+
+    /// The color of texts, points, segments and the border of rectangles.
+    #[builder(default)]
+    color: Option<Color>,
+
+    /// The fill color of rectangles.
+    #[builder(default)]
+    fill: Option<Color>,
+
+    /// The opacity of the annotation, from `0.0` to `1.0`.
+    #[builder(default)]
+    alpha: Option<f64>,
+
+    /// The size of texts and points, or the width of segments.
+    #[builder(default)]
+    size: Option<i32>,
+}
+
+impl AnnotateBuilder {
+    /// Set the color, alias for [`AnnotateBuilder::color`]
+    pub fn colour<T: Into<Color>>(&mut self, colour: T) -> &mut Self {
+        self.color(colour)
+    }
+}
+
+impl Annotate {
+    /// Create a new [`Annotate`] via the builder pattern.
+    pub fn builder() -> AnnotateBuilder {
+        AnnotateBuilder::default()
+    }
+
+    fn resolve(&self, values: &Option<Values>, name: &str) -> Vec<f64> {
+        values
+            .as_ref()
+            .unwrap_or_else(|| panic!("{name} must be provided for annotate({:?})", self.geom))
+            .resolve(self.data.as_ref())
+    }
+
+    /// Get the values of the position parameters, repeating single values.
+    ///
+    /// There are no positions if a parameter has no values, e.g. a column of empty data.
+    fn positions<const N: usize>(&self, params: [(&Option<Values>, &str); N]) -> Vec<[f64; N]> {
+        let values = params.map(|(values, name)| self.resolve(values, name));
+        if values.iter().any(Vec::is_empty) {
+            return Vec::new();
+        }
+        let n = values.iter().map(|v| v.len()).max().unwrap_or(0);
+
+        (0..n)
+            .map(|i| std::array::from_fn(|j| values[j][i.min(values[j].len() - 1)]))
+            .collect()
+    }
+
+    fn draw_2d<'a, DB>(
+        &'a self,
+        chart: &mut ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+        panel: &Panel,
+    ) -> anyhow::Result<()>
+    where
+        DB: DrawingBackend + 'a,
+        <DB as plotters::prelude::DrawingBackend>::ErrorType: 'static,
+    {
+        let (x_range, y_range) = (panel.x_range(), panel.y_range());
+        let clamp_x = |x: f64| x.clamp(x_range.start, x_range.end);
+        let clamp_y = |y: f64| y.clamp(y_range.start, y_range.end);
+        // Infinite positions are at the edge of the panel, finite ones are kept
+        let edge_x = |x: f64| if x.is_infinite() { clamp_x(x) } else { x };
+        let edge_y = |y: f64| if y.is_infinite() { clamp_y(y) } else { y };
+
+        let alpha = self.alpha.unwrap_or(1.0);
+        let color = self.color.clone().unwrap_or_default().as_rgb();
+
+        match self.geom {
+            AnnotateGeom::Text => {
+                let label = self
+                    .label
+                    .as_deref()
+                    .expect("label must be provided for annotate(Text)");
+                let style = ("sans-serif", self.size.unwrap_or(16))
+                    .into_font()
+                    .color(&color.mix(alpha))
+                    .pos(Pos::new(HPos::Center, VPos::Center));
+
+                chart.draw_series(
+                    self.positions([(&self.x, "x"), (&self.y, "y")])
+                        .into_iter()
+                        .map(|[x, y]| {
+                            TextElement::new(label.to_string(), panel.map((x, y)), style.clone())
+                        }),
+                )?;
+            }
+            AnnotateGeom::Point => {
+                let style = color.mix(alpha).filled();
+
+                chart.draw_series(
+                    self.positions([(&self.x, "x"), (&self.y, "y")])
+                        .into_iter()
+                        .map(|[x, y]| {
+                            Circle::new(panel.map((x, y)), self.size.unwrap_or(5), style)
+                        }),
+                )?;
+            }
+            AnnotateGeom::Rect => {
+                let fill = self
+                    .fill
+                    .clone()
+                    .unwrap_or(Color::RGB(plotters::style::RGBColor(89, 89, 89)))
+                    .as_rgb()
+                    .mix(alpha)
+                    .filled();
+
+                let rects: Vec<_> = self
+                    .positions([
+                        (&self.xmin, "xmin"),
+                        (&self.ymin, "ymin"),
+                        (&self.xmax, "xmax"),
+                        (&self.ymax, "ymax"),
+                    ])
+                    .into_iter()
+                    .map(|[x0, y0, x1, y1]| {
+                        [(clamp_x(x0), clamp_y(y0)), (clamp_x(x1), clamp_y(y1))]
+                    })
+                    .collect();
+
+                draw_rects(chart, panel, rects.iter().map(|&rect| (rect, fill)))?;
+
+                if self.color.is_some() {
+                    draw_rects(chart, panel, rects.iter().map(|&rect| (rect, color.into())))?;
+                }
+            }
+            AnnotateGeom::Segment => {
+                let style = color
+                    .mix(alpha)
+                    .stroke_width(self.size.unwrap_or(1).max(1) as u32);
+
+                chart.draw_series(
+                    self.positions([
+                        (&self.x, "x"),
+                        (&self.y, "y"),
+                        (&self.xend, "xend"),
+                        (&self.yend, "yend"),
+                    ])
+                    .into_iter()
+                    .filter_map(|[x0, y0, x1, y1]| {
+                        let start = (edge_x(x0), edge_y(y0));
+                        let end = (edge_x(x1), edge_y(y1));
+                        clip_segment(start, end, &x_range, &y_range)
+                    })
+                    .map(|segment| PathElement::new(panel.path(&segment), style)),
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Clip the segment from `start` to `end` to the given ranges, keeping its direction.
+///
+/// Returns `None` if the segment does not cross the area.
+fn clip_segment(
+    start: (f64, f64),
+    end: (f64, f64),
+    x_range: &Range<f64>,
+    y_range: &Range<f64>,
+) -> Option<[(f64, f64); 2]> {
+    let (x0, y0) = start;
+    let (dx, dy) = (end.0 - x0, end.1 - y0);
+    let (mut t0, mut t1) = (0.0f64, 1.0f64);
+
+    // Liang–Barsky: the part of the segment inside each edge is a range of `t` in `[0, 1]`
+    for (p, q) in [
+        (-dx, x0 - x_range.start),
+        (dx, x_range.end - x0),
+        (-dy, y0 - y_range.start),
+        (dy, y_range.end - y0),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else if p < 0.0 {
+            t0 = t0.max(q / p);
+        } else {
+            t1 = t1.min(q / p);
+        }
+    }
+
+    (t0 <= t1).then_some([(x0 + t0 * dx, y0 + t0 * dy), (x0 + t1 * dx, y0 + t1 * dy)])
+}
+
+/// # annotate!: Construct an [`Annotate`] layer
+///
+/// This macro is used to create an [`Annotate`] layer in a more concise way like `ggplot2`.
+/// It is a wrapper around [`Annotate::builder`].
+///
+/// ## Usage
+///
+/// ```ignore
+/// annotate!(
+///     <"text" | "point" | "rect" | "segment">,
+///     [...Positions and constant aesthetics]
+/// )
+/// ```
+///
+/// ### Arguments
+///
+/// #### geom
+///
+/// The first argument is the geometric object of the annotation:
+///
+/// - `"text"`: texts of `label` at `x` and `y`
+/// - `"point"`: points at `x` and `y`
+/// - `"rect"`: rectangles from (`xmin`, `ymin`) to (`xmax`, `ymax`)
+/// - `"segment"`: line segments from (`x`, `y`) to (`xend`, `yend`)
+///
+/// #### Positions
+///
+/// Positions are in data coordinates. They accept a single value or multiple
+/// values like `vec![1.0, 2.0]`, and a single value is shared by all
+/// annotations. `f64::INFINITY` and `f64::NEG_INFINITY` stretch the annotation
+/// to the edge of the plot.
+///
+/// #### Constant aesthetics
+///
+/// - `color`: the color of texts, points, segments and the border of rectangles
+/// - `fill`: the fill color of rectangles
+/// - `alpha`: the opacity, from `0.0` to `1.0`
+/// - `size`: the size of texts and points, or the width of segments
+///
+/// ## Example
+///
+/// ```
+/// # use gongbi::*;
+/// let text = annotate!("text", x = 3, y = 40, label = "outlier");
+/// let band = annotate!("rect", xmin = f64::NEG_INFINITY, xmax = f64::INFINITY, ymin = 20, ymax = 30, alpha = 0.2);
+/// let arrow = annotate!("segment", x = 2.5, y = 38, xend = 3, yend = 40, color = "red");
+///
+/// let p = plot!(mapping = aes!(x, y)) + text + band + arrow;
+/// ```
+#[macro_export]
+macro_rules! annotate {
+    ($geom: expr $(, $($param: ident = $value: expr),+ $(,)?)?) => {
+        $crate::layer::annotate::Annotate::builder()
+            .geom($geom)
+            $($(.$param($value))+)?
+            .build()
+            .unwrap()
+    };
+}
+
+impl Layer for Annotate {
+    fn requires_data(&self) -> bool {
+        [
+            &self.x, &self.y, &self.xend, &self.yend, &self.xmin, &self.xmax, &self.ymin,
+            &self.ymax,
+        ]
+        .into_iter()
+        .flatten()
+        .any(Values::is_column)
+    }
+
+    fn columns(&self) -> Vec<String> {
+        [
+            &self.x, &self.y, &self.xend, &self.yend, &self.xmin, &self.xmax, &self.ymin,
+            &self.ymax,
+        ]
+        .into_iter()
+        .flatten()
+        .filter_map(Values::as_column)
+        .map(String::from)
+        .collect()
+    }
+
+    fn data_mut(&mut self) -> &mut Option<Arc<dyn crate::data::Data>> {
+        &mut self.data
+    }
+
+    fn mapping_mut(&mut self) -> &mut crate::aes::Aes {
+        &mut self.mapping
+    }
+
+    fn range_2d(&self) -> Option<(f64, f64, f64, f64)> {
+        let finite_range = |params: [&Option<Values>; 4]| {
+            params
+                .into_iter()
+                .flatten()
+                .flat_map(|values| values.resolve(self.data.as_ref()))
+                .filter(|v| v.is_finite())
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+                    (min.min(v), max.max(v))
+                })
+        };
+
+        let x = finite_range([&self.x, &self.xend, &self.xmin, &self.xmax]);
+        let y = finite_range([&self.y, &self.yend, &self.ymin, &self.ymax]);
+
+        Some((x.0, x.1, y.0, y.1))
+    }
+
+    #[cfg(feature = "svg")]
+    fn draw_svg_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::SVGBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        _scales: &crate::scale::Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel)
+    }
+
+    #[cfg(feature = "png")]
+    fn draw_png_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::BitMapBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        _scales: &crate::scale::Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions_repeat_single_values() {
+        let anno = Annotate::builder()
+            .geom("segment")
+            .x(1.0)
+            .y(&[1.0, 2.0, 3.0][..])
+            .xend(&[4.0, 5.0, 6.0][..])
+            .yend(0)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            anno.positions([
+                (&anno.x, "x"),
+                (&anno.y, "y"),
+                (&anno.xend, "xend"),
+                (&anno.yend, "yend")
+            ]),
+            [
+                [1.0, 1.0, 4.0, 0.0],
+                [1.0, 2.0, 5.0, 0.0],
+                [1.0, 3.0, 6.0, 0.0]
+            ]
+        );
+    }
+
+    #[test]
+    fn positions_of_empty_values() {
+        let anno = Annotate::builder()
+            .geom("point")
+            .x(&[][..])
+            .y(&[1.0, 2.0][..])
+            .build()
+            .unwrap();
+
+        assert!(anno.positions([(&anno.x, "x"), (&anno.y, "y")]).is_empty());
+    }
+
+    #[test]
+    fn clip_segment_to_area() {
+        let (x_range, y_range) = (0.0..10.0, 0.0..10.0);
+
+        // Endpoints outside the area are moved along the segment
+        let segment = clip_segment((0.0, 0.0), (20.0, 10.0), &x_range, &y_range).unwrap();
+        assert_eq!(segment, [(0.0, 0.0), (10.0, 5.0)]);
+
+        let segment = clip_segment((5.0, -5.0), (5.0, 15.0), &x_range, &y_range).unwrap();
+        assert_eq!(segment, [(5.0, 0.0), (5.0, 10.0)]);
+
+        let segment = clip_segment((2.0, 3.0), (4.0, 5.0), &x_range, &y_range).unwrap();
+        assert_eq!(segment, [(2.0, 3.0), (4.0, 5.0)]);
+
+        assert!(clip_segment((0.0, 12.0), (10.0, 11.0), &x_range, &y_range).is_none());
+        assert!(clip_segment((8.0, -4.0), (14.0, 2.0), &x_range, &y_range).is_none());
+    }
+}
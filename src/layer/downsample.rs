@@ -0,0 +1,190 @@
+//! Downsample module
+//!
+//! This module contains the reduction of huge series to the points which are
+//! visible at the size of the output. The points are selected by their pixels
+//! in the backend, so the reduction follows the size of the plotting area and
+//! the coordinate system.
+
+use std::collections::HashSet;
+
+/// The downsampling of a line
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Downsample {
+    /// Keep the first, lowest, highest and last points of each run of points
+    /// in the same pixel column.
+    ///
+    /// The drawn line covers the same pixels as the full series.
+    MinMax,
+
+    /// Keep two points per pixel column by Largest-Triangle-Three-Buckets,
+    /// which preserves the shape of the series with fewer points.
+    Lttb,
+}
+
+impl std::str::FromStr for Downsample {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "minmax" => Ok(Downsample::MinMax),
+            "lttb" => Ok(Downsample::Lttb),
+            _ => Err(format!("Unsupported downsample: {s}")),
+        }
+    }
+}
+
+impl From<&str> for Downsample {
+    fn from(value: &str) -> Self {
+        value.parse().unwrap()
+    }
+}
+
+impl Downsample {
+    /// Get the indices of the points to keep, in order, given the pixels of
+    /// the points and the width of the plotting area in pixels.
+    pub(crate) fn indices(&self, pixels: &[(i32, i32)], width: u32) -> Vec<usize> {
+        match self {
+            Downsample::MinMax => min_max(pixels),
+            Downsample::Lttb => lttb(pixels, 2 * width as usize),
+        }
+    }
+}
+
+/// Keep the first, lowest, highest and last points of each run of consecutive
+/// points in the same pixel column.
+fn min_max(pixels: &[(i32, i32)]) -> Vec<usize> {
+    let mut indices = Vec::new();
+    let mut start = 0;
+
+    while start < pixels.len() {
+        let column = pixels[start].0;
+        let end = start
+            + pixels[start..]
+                .iter()
+                .take_while(|(x, _)| *x == column)
+                .count();
+
+        let run = start..end;
+        let lowest = run.clone().min_by_key(|&i| pixels[i].1).unwrap();
+        let highest = run.clone().max_by_key(|&i| pixels[i].1).unwrap();
+
+        let mut kept = [start, lowest, highest, end - 1];
+        kept.sort_unstable();
+        indices.extend(kept);
+        indices.dedup();
+
+        start = end;
+    }
+
+    indices
+}
+
+/// Keep `threshold` points by Largest-Triangle-Three-Buckets.
+///
+/// The first and last points are kept, and the other points are split into
+/// buckets. From each bucket, the point forming the largest triangle with the
+/// previous kept point and the average of the next bucket is kept.
+fn lttb(pixels: &[(i32, i32)], threshold: usize) -> Vec<usize> {
+    let len = pixels.len();
+    if threshold < 3 || len <= threshold {
+        return (0..len).collect();
+    }
+
+    let point = |i: usize| (pixels[i].0 as f64, pixels[i].1 as f64);
+    let bucket = |k: usize| {
+        let size = (len - 2) as f64 / (threshold - 2) as f64;
+        (1 + (k as f64 * size) as usize)..(1 + ((k + 1) as f64 * size) as usize).min(len - 1)
+    };
+
+    let mut indices = Vec::with_capacity(threshold);
+    indices.push(0);
+
+    let mut previous = 0;
+    for k in 0..threshold - 2 {
+        // The average of the next bucket, or the last point after the last bucket
+        let next = match k + 3 < threshold {
+            true => bucket(k + 1),
+            false => len - 1..len,
+        };
+        let n = next.len() as f64;
+        let (x_avg, y_avg) = next.map(point).fold((0.0, 0.0), |(x_sum, y_sum), (x, y)| {
+            (x_sum + x / n, y_sum + y / n)
+        });
+
+        let (x_a, y_a) = point(previous);
+        let area = |i: usize| {
+            let (x, y) = point(i);
+            ((x_a - x_avg) * (y - y_a) - (x_a - x) * (y_avg - y_a)).abs()
+        };
+
+        previous = bucket(k)
+            .max_by(|&i, &j| area(i).total_cmp(&area(j)))
+            .unwrap_or(previous);
+        indices.push(previous);
+    }
+
+    indices.push(len - 1);
+    indices.dedup();
+
+    indices
+}
+
+/// Get the indices of the points to draw, dropping the points hidden by a
+/// later point with the same key at the same pixel.
+///
+/// The indices are in order, and the last point of each pixel and key is kept
+/// since it is drawn on top.
+pub(crate) fn dedup_pixels<K>(pixels: &[(i32, i32)], keys: &[K]) -> Vec<usize>
+where
+    K: Eq + std::hash::Hash,
+{
+    let mut seen = HashSet::with_capacity(pixels.len());
+    let mut indices: Vec<usize> = (0..pixels.len())
+        .rev()
+        .filter(|&i| seen.insert((pixels[i], &keys[i])))
+        .collect();
+    indices.reverse();
+
+    indices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn min_max_per_pixel_column() {
+        let pixels = [(0, 5), (0, 1), (0, 9), (0, 4), (1, 3), (2, 7), (2, 7)];
+
+        assert_eq!(min_max(&pixels), [0, 1, 2, 3, 4, 5, 6]);
+
+        let pixels = [(0, 5), (0, 1), (0, 2), (0, 9), (0, 6), (0, 4), (1, 3)];
+
+        assert_eq!(min_max(&pixels), [0, 1, 3, 5, 6]);
+    }
+
+    #[test]
+    fn lttb_keeps_peaks() {
+        let pixels: Vec<(i32, i32)> = (0..100)
+            .map(|x| (x, if x == 42 { 100 } else { 0 }))
+            .collect();
+        let indices = lttb(&pixels, 10);
+
+        assert_eq!(indices.len(), 10);
+        assert_eq!(indices.first(), Some(&0));
+        assert_eq!(indices.last(), Some(&99));
+        assert!(indices.contains(&42));
+        assert!(indices.windows(2).all(|w| w[0] < w[1]));
+
+        assert_eq!(lttb(&pixels[..5], 10), [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn dedup_keeps_last_point() {
+        let pixels = [(0, 0), (1, 1), (0, 0), (0, 0)];
+        let keys = ["a", "a", "b", "a"];
+
+        assert_eq!(dedup_pixels(&pixels, &keys), [1, 2, 3]);
+        assert_eq!("lttb".parse(), Ok(Downsample::Lttb));
+    }
+}
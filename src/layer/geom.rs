@@ -2,27 +2,174 @@
 //!
 //! This module contains multiple geometric layers implementations and utility functions to implement them.
 
-use std::rc::Rc;
+use std::{collections::BTreeMap, sync::Arc};
 
-use crate::{aes::Aes, data::Data};
+use derive_more::From;
+use plotters::{
+    chart::ChartContext,
+    coord::types::RangedCoordf64,
+    prelude::{Cartesian2d, DrawingBackend, PathElement, Polygon, Rectangle},
+    style::{RGBColor, ShapeStyle},
+};
 
+use crate::{
+    aes::{color::ColorMapping, position::PositionMapping, Aes},
+    coord::Panel,
+    data::Data,
+    scale::Scales,
+};
+
+pub mod bar;
+pub mod bin2d;
+pub mod contour;
+pub mod density;
+pub mod density_2d;
+pub mod hex;
+pub mod label;
 pub mod line;
 pub mod point;
+#[cfg(feature = "raster")]
+pub mod raster;
+pub mod reference;
+pub mod text;
+pub mod tile;
+
+/// The values of a layer parameter
+///
+/// The values are either constants, or read from a column of the layer's data.
+#[derive(Clone, Debug, PartialEq, From)]
+pub enum Values {
+    /// Constant values
+    Constant(Vec<f64>),
+
+    /// The column name to read the values from
+    Column(String),
+}
+
+impl From<&str> for Values {
+    fn from(value: &str) -> Self {
+        Values::Column(value.to_string())
+    }
+}
+
+impl From<f64> for Values {
+    fn from(value: f64) -> Self {
+        Values::Constant(vec![value])
+    }
+}
+
+impl From<i32> for Values {
+    fn from(value: i32) -> Self {
+        Values::Constant(vec![value as f64])
+    }
+}
+
+impl From<&[f64]> for Values {
+    fn from(value: &[f64]) -> Self {
+        Values::Constant(value.to_vec())
+    }
+}
+
+impl Values {
+    /// Whether the values are read from a column of the data.
+    pub fn is_column(&self) -> bool {
+        matches!(self, Values::Column(_))
+    }
+
+    /// Get the column name if the values are read from a column.
+    pub fn as_column(&self) -> Option<&str> {
+        match self {
+            Values::Constant(_) => None,
+            Values::Column(column) => Some(column),
+        }
+    }
+
+    /// Resolve the values, reading the column from the data if needed.
+    pub fn resolve(&self, data: Option<&Arc<dyn Data>>) -> Vec<f64> {
+        match self {
+            Values::Constant(values) => values.clone(),
+            Values::Column(column) => data
+                .unwrap_or_else(|| panic!("data is required to read column {column}"))
+                .column_f64(column)
+                .into_owned(),
+        }
+    }
+}
+
+/// The positions of the rows of a layer, read once from the data with their range
+///
+/// Without a y mapping, the x values are the y positions at the row indices, like [`range_2d_x_xy`].
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Positions {
+    /// The position of each row
+    pub points: Vec<(f64, f64)>,
+
+    /// The minimum and maximum x and y positions, ignoring `NaN`
+    pub range: (f64, f64, f64, f64),
+}
+
+impl Positions {
+    /// Read the positions of the x and y mappings from the data.
+    ///
+    /// The mappings are read or evaluated once, e.g. the expressions of a lazy frame.
+    pub fn read(data: &Arc<dyn Data>, x: &PositionMapping, y: Option<&PositionMapping>) -> Self {
+        let points: Vec<(f64, f64)> = match y {
+            Some(y) => data
+                .position_f64(x)
+                .iter()
+                .copied()
+                .zip(data.position_f64(y).iter().copied())
+                .collect(),
+            None => data
+                .position_f64(x)
+                .iter()
+                .enumerate()
+                .map(|(i, &x)| (i as f64, x))
+                .collect(),
+        };
+
+        // `f64::min` and `f64::max` skip `NaN`
+        let (x0, x1, y0, y1) = points.iter().fold(
+            (
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+            ),
+            |(x0, x1, y0, y1), &(x, y)| (x0.min(x), x1.max(x), y0.min(y), y1.max(y)),
+        );
+        let range = match y {
+            Some(_) => (x0, x1, y0, y1),
+            None => (0.0, points.len() as f64, y0, y1),
+        };
+
+        Positions { points, range }
+    }
+
+    /// Get the resolution of the x and y positions, like [`resolution_2d_x_xy`].
+    pub fn resolution(&self) -> (f64, f64) {
+        let (x, y): (Vec<f64>, Vec<f64>) = self.points.iter().copied().unzip();
+
+        (resolution(&x), resolution(&y))
+    }
+}
 
 /// Get the range of the x and y columns of the data.
 ///
 /// If the mapping does not have a x or y column, this function will panic.
-pub fn range_2d_xy(data: &Rc<dyn Data>, mapping: &Aes) -> (f64, f64, f64, f64) {
+pub fn range_2d_xy(data: &Arc<dyn Data>, mapping: &Aes) -> (f64, f64, f64, f64) {
     let x = mapping
         .x
+        .as_ref()
         .unwrap_or_else(|| panic!("Layer does not have x mapping"));
 
     let y = mapping
         .y
+        .as_ref()
         .unwrap_or_else(|| panic!("Layer does not have y mapping"));
 
-    let x_range = data.column_range_f64(x);
-    let y_range = data.column_range_f64(y);
+    let x_range = data.position_range_f64(x);
+    let y_range = data.position_range_f64(y);
 
     (x_range.0, x_range.1, y_range.0, y_range.1)
 }
@@ -32,23 +179,228 @@ pub fn range_2d_xy(data: &Rc<dyn Data>, mapping: &Aes) -> (f64, f64, f64, f64) {
 /// If the mapping does not have a x column, this function will panic.
 ///
 /// If the mapping does not have a y column, the y range will be the range of the x column, and the x range will be the range of the length of the x column.
-pub fn range_2d_x_xy(data: &Rc<dyn Data>, mapping: &Aes) -> (f64, f64, f64, f64) {
+pub fn range_2d_x_xy(data: &Arc<dyn Data>, mapping: &Aes) -> (f64, f64, f64, f64) {
     let x = mapping
         .x
+        .as_ref()
         .unwrap_or_else(|| panic!("Layer does not have x mapping"));
 
-    match mapping.y {
+    match &mapping.y {
         Some(y) => {
-            let x_range = data.column_range_f64(x);
-            let y_range = data.column_range_f64(y);
+            let x_range = data.position_range_f64(x);
+            let y_range = data.position_range_f64(y);
 
             (x_range.0, x_range.1, y_range.0, y_range.1)
         }
         None => {
-            let x_len = data.column_len(x);
-            let x_range = data.column_range_f64(x);
+            let x_len = data.position_len(x);
+            let x_range = data.position_range_f64(x);
 
             (0.0, x_len as f64, x_range.0, x_range.1)
         }
     }
 }
+
+/// Get the resolution of the x and y columns of the data.
+///
+/// If the mapping does not have a y column, the x resolution is the one of the
+/// row indices, like [`range_2d_x_xy`].
+pub fn resolution_2d_x_xy(data: &Arc<dyn Data>, mapping: &Aes) -> (f64, f64) {
+    let x = mapping
+        .x
+        .as_ref()
+        .unwrap_or_else(|| panic!("Layer does not have x mapping"));
+
+    match &mapping.y {
+        Some(y) => (
+            resolution(&data.position_f64(x)),
+            resolution(&data.position_f64(y)),
+        ),
+        None => (1.0, resolution(&data.position_f64(x))),
+    }
+}
+
+/// Get the range of the column mapped to the fill aesthetic.
+///
+/// Returns `None` if the mapping does not map a column to fill.
+pub fn fill_range(data: &Arc<dyn Data>, mapping: &Aes) -> Option<(f64, f64)> {
+    match &mapping.fill {
+        Some(ColorMapping::Column(column_fill)) => Some(data.column_range_f64(column_fill)),
+        _ => None,
+    }
+}
+
+/// Get the fill color of each row of the data.
+///
+/// `None` means the row is not filled. If the mapping does not have fill,
+/// all rows are filled with the default color.
+pub fn fill_colors(
+    data: &Arc<dyn Data>,
+    mapping: &Aes,
+    scales: &Scales,
+    len: usize,
+    default: RGBColor,
+) -> Vec<Option<RGBColor>> {
+    match &mapping.fill {
+        Some(ColorMapping::None) => vec![None; len],
+        Some(ColorMapping::Constant(fill)) => vec![Some(fill.as_rgb()); len],
+        Some(ColorMapping::Column(column_fill)) => data
+            .column_f64(column_fill)
+            .iter()
+            .map(|&value| Some(scales.fill.map(value)))
+            .collect(),
+        None => vec![Some(default); len],
+    }
+}
+
+/// Get the constant color of the mapping.
+///
+/// The default color is returned if the color is not set or mapped from a column.
+pub fn constant_color(mapping: &Aes, default: RGBColor) -> RGBColor {
+    mapping
+        .color
+        .as_ref()
+        .and_then(ColorMapping::as_constant)
+        .unwrap_or(default)
+}
+
+/// Get the column mapped to the color aesthetic and its distinct values.
+///
+/// Returns `None` if the mapping does not map a column to color.
+pub fn color_levels<'a>(data: &Arc<dyn Data>, mapping: &'a Aes) -> Option<(&'a str, Vec<String>)> {
+    let column = mapping.color.as_ref()?.as_column()?;
+
+    Some((column, data.column_levels(column)))
+}
+
+/// Get the color of each row of the data.
+///
+/// If the mapping maps a column to color, the values are mapped by the color
+/// scale. Otherwise, all rows have the constant color or the default color.
+pub fn colors(
+    data: &Arc<dyn Data>,
+    mapping: &Aes,
+    scales: &Scales,
+    len: usize,
+    default: RGBColor,
+) -> Vec<RGBColor> {
+    match mapping.color.as_ref().and_then(ColorMapping::as_column) {
+        Some(column) => data
+            .column_string(column)
+            .iter()
+            .map(|level| scales.color.map(level))
+            .collect(),
+        None => vec![constant_color(mapping, default); len],
+    }
+}
+
+/// Group the rows of the data by the values of a column.
+///
+/// Returns the sorted groups with the indices of their rows. Without a column,
+/// all `len` rows are in a single group without value.
+pub fn groups(
+    data: &Arc<dyn Data>,
+    column: Option<&str>,
+    len: usize,
+) -> Vec<(Option<String>, Vec<usize>)> {
+    let Some(column) = column else {
+        return vec![(None, (0..len).collect())];
+    };
+
+    let mut groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (i, level) in data.column_string(column).into_iter().enumerate() {
+        groups.entry(level).or_default().push(i);
+    }
+
+    groups
+        .into_iter()
+        .map(|(level, rows)| (Some(level), rows))
+        .collect()
+}
+
+/// Get the resolution of the values, i.e. the smallest distance between two
+/// distinct values.
+///
+/// If there are less than two distinct values, the resolution is `1.0`.
+pub fn resolution(values: &[f64]) -> f64 {
+    let mut values: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+    values.sort_by(f64::total_cmp);
+
+    values
+        .windows(2)
+        .map(|w| w[1] - w[0])
+        .filter(|d| *d > 0.0)
+        .reduce(f64::min)
+        .unwrap_or(1.0)
+}
+
+/// Draw the rectangles of the data through the panel.
+///
+/// The rectangles are drawn as polygons if the panel is not linear, e.g. the
+/// sectors of polar coordinates. The styles without fill draw the outlines.
+pub fn draw_rects<'a, DB, I>(
+    chart: &mut ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+    panel: &Panel,
+    rects: I,
+) -> anyhow::Result<()>
+where
+    DB: DrawingBackend + 'a,
+    <DB as plotters::prelude::DrawingBackend>::ErrorType: 'static,
+    I: IntoIterator<Item = ([(f64, f64); 2], ShapeStyle)>,
+{
+    let rects = rects.into_iter();
+
+    if panel.is_linear() {
+        chart.draw_series(rects.map(|([corner0, corner1], style)| {
+            Rectangle::new([panel.map(corner0), panel.map(corner1)], style)
+        }))?;
+        return Ok(());
+    }
+
+    for (rect, style) in rects {
+        let mut polygon = panel.rect(rect);
+
+        if style.filled {
+            chart.draw_series(std::iter::once(Polygon::new(polygon, style)))?;
+        } else {
+            polygon.push(polygon[0]);
+            chart.draw_series(std::iter::once(PathElement::new(polygon, style)))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolution_of_values() {
+        assert_eq!(resolution(&[3.0, 1.0, 2.0, 2.0]), 1.0);
+        assert_eq!(resolution(&[0.0, 0.5, 1.5]), 0.5);
+        assert_eq!(resolution(&[4.0, 4.0]), 1.0);
+        assert_eq!(resolution(&[]), 1.0);
+    }
+
+    #[test]
+    fn positions_match_ranges() {
+        let data: Arc<dyn Data> = Arc::new(std::collections::HashMap::from([
+            ("x", vec![3.0, f64::NAN, 1.0]),
+            ("y", vec![2.0, 5.0, f64::NAN]),
+        ]));
+        let mapping = Aes::builder().x("x").y("y").build().unwrap();
+        let (x, y) = (mapping.x.as_ref().unwrap(), mapping.y.as_ref());
+
+        let positions = Positions::read(&data, x, y);
+        assert_eq!(positions.range, (1.0, 3.0, 2.0, 5.0));
+        assert_eq!(positions.range, range_2d_x_xy(&data, &mapping));
+        assert_eq!(positions.resolution(), (2.0, 3.0));
+
+        let mapping = Aes::builder().x("x").build().unwrap();
+        let positions = Positions::read(&data, x, None);
+        assert_eq!(positions.points[2], (2.0, 1.0));
+        assert_eq!(positions.range, range_2d_x_xy(&data, &mapping));
+        assert_eq!(positions.resolution(), resolution_2d_x_xy(&data, &mapping));
+    }
+}
@@ -0,0 +1,409 @@
+//! # Bar geom layer
+//!
+//! The bar geom draws a bar at each x position, with the height of the number
+//! of rows, or the sum of the y values if y is mapped. The rows of each bar are
+//! stacked by the column mapped to `fill`.
+//!
+//! With [`coord_polar!`](crate::coord_polar!), a single stacked bar becomes a
+//! pie chart.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use gongbi::*;
+//! # fn main() -> anyhow::Result<()> {
+//! let p = plot!(csv = "examples/mpg.csv", mapping = aes!(cyl, fill = drv))
+//!     + geom_bar!();
+//!
+//! p.to_svg("geom_bar.svg", (800, 600))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! See [`geom_bar!`](crate::geom_bar!) or [`Bar::builder`] for more details.
+
+use std::{borrow::Cow, sync::Arc};
+
+use derive_builder::Builder;
+use plotters::{
+    chart::ChartContext,
+    coord::types::RangedCoordf64,
+    prelude::{Cartesian2d, DrawingBackend},
+    style::{Color, RGBColor},
+};
+
+#[cfg(feature = "polars")]
+use polars::prelude::{col, lit, DataType, LazyFrame};
+
+#[cfg(feature = "polars")]
+use crate::data::polars::collect;
+use crate::{
+    aes::color::ColorMapping,
+    coord::Panel,
+    data::Data,
+    layer::{stat, Layer},
+    scale::Scales,
+};
+
+use super::{draw_rects, groups, resolution};
+
+/// A stacked rectangle of a bar, with the value of its group if any
+type Stack = (Option<String>, [(f64, f64); 2]);
+
+/// The sum of the heights of the rows of a group at a x position
+type Sum = (Option<String>, f64, f64);
+
+/// The stacked rectangles of the bars, computed once from the data
+#[derive(Clone, Debug)]
+struct Stacks {
+    /// The stacked rectangles, from the bottom to the top of each bar
+    stacks: Vec<Stack>,
+
+    /// The distinct values of the group column
+    levels: Vec<String>,
+}
+
+/// # Bar layer
+///
+/// The bar geom layer is used to draw stacked bars of counts or sums.
+///
+/// To create a bar layer, [`geom_bar!`](crate::geom_bar!) and [`Bar::builder`] can be used.
+#[derive(Clone, Debug, Default, Builder)]
+pub struct Bar {
+    /// The data source for the layer.
+    #[builder(default, setter(strip_option))]
+    data: Option<Arc<dyn crate::data::Data>>,
+
+    /// The aes mapping for the layer.
+    #[builder(default)]
+    mapping: crate::aes::Aes,
+
+    /// The width of the bars in data units.
+    ///
+    /// If not provided, 90% of the resolution of the x values is used.
+    #[builder(default, setter(strip_option))]
+    width: Option<f64>,
+
+    /// The stacked bars, computed once from the data.
+    #[builder(setter(skip))]
+    stat: stat::Cache<Stacks>,
+}
+
+impl Bar {
+    /// Create a new [`Bar`] via the builder pattern.
+    pub fn builder() -> BarBuilder {
+        BarBuilder::default()
+    }
+
+    /// Get the column to stack the rows by, i.e. the column mapped to fill or color.
+    fn group_column(&self) -> Option<&str> {
+        let mapping = &self.mapping;
+
+        [&mapping.fill, &mapping.color]
+            .into_iter()
+            .find_map(|mapping| mapping.as_ref()?.as_column())
+    }
+
+    /// Get the sums of the heights of the rows of each group at each x position.
+    ///
+    /// Returns the levels of the group column and the sums `(level, x, sum)` sorted by level and x.
+    fn sums(&self, data: &Arc<dyn Data>) -> (Vec<String>, Vec<Sum>) {
+        // A lazy frame sums the heights in its query
+        #[cfg(feature = "polars")]
+        if let Some(lazy) = data.as_lazy() {
+            return self.sums_lazy(lazy);
+        }
+
+        let mapping = &self.mapping;
+
+        let column_group = self.group_column();
+        let len = [&mapping.x, &mapping.y]
+            .into_iter()
+            .flatten()
+            .map(|position| data.position_len(position))
+            .chain(column_group.map(|column| data.column_len(column)))
+            .next()
+            .expect("x, y or a fill column must be provided for geom_bar");
+
+        // Without x, all rows are in a single bar
+        let x = match &mapping.x {
+            Some(column_x) => data.position_f64(column_x),
+            None => Cow::Owned(vec![1.0; len]),
+        };
+        let heights = match &mapping.y {
+            Some(column_y) => data.position_f64(column_y),
+            None => Cow::Owned(vec![1.0; len]),
+        };
+
+        let mut positions: Vec<f64> = x.iter().copied().filter(|x| x.is_finite()).collect();
+        positions.sort_by(f64::total_cmp);
+        positions.dedup();
+
+        let mut levels = vec![];
+        let mut sums = vec![];
+        for (level, rows) in groups(data, column_group, len) {
+            let mut level_sums = vec![None; positions.len()];
+            for i in rows {
+                if let Ok(j) = positions.binary_search_by(|x_j| x_j.total_cmp(&x[i])) {
+                    *level_sums[j].get_or_insert(0.0) += heights[i];
+                }
+            }
+
+            sums.extend(
+                level_sums
+                    .into_iter()
+                    .zip(&positions)
+                    .filter_map(|(sum, &x)| Some((level.clone(), x, sum?))),
+            );
+            levels.extend(level);
+        }
+
+        (levels, sums)
+    }
+
+    /// Get the sums of the heights in the query of a lazy frame, grouped by the x positions and the groups.
+    #[cfg(feature = "polars")]
+    fn sums_lazy(&self, lazy: &LazyFrame) -> (Vec<String>, Vec<Sum>) {
+        let mapping = &self.mapping;
+
+        let column_group = self.group_column();
+        assert!(
+            mapping.x.is_some() || mapping.y.is_some() || column_group.is_some(),
+            "x, y or a fill column must be provided for geom_bar"
+        );
+
+        // Without x, all rows are in a single bar
+        let x = match &mapping.x {
+            Some(column_x) => column_x.expr().cast(DataType::Float64),
+            None => lit(1.0),
+        };
+        let height = match &mapping.y {
+            Some(column_y) => column_y
+                .expr()
+                .cast(DataType::Float64)
+                .fill_null(lit(f64::NAN)),
+            None => lit(1.0),
+        };
+        let group = match column_group {
+            Some(column) => col(column).cast(DataType::String).fill_null(lit("")),
+            None => lit(""),
+        };
+
+        let query = lazy
+            .clone()
+            .select([x.alias("x"), height.alias("height"), group.alias("group")])
+            .group_by([col("group"), col("x")])
+            .agg([col("height").sum()]);
+        let result = collect(query);
+
+        let x = result.column_f64("x");
+        let heights = result.column_f64("height");
+        let mut sums: Vec<Sum> = result
+            .column_string("group")
+            .into_iter()
+            .zip(x.iter().zip(heights.iter()))
+            .map(|(level, (&x, &sum))| (column_group.map(|_| level), x, sum))
+            .collect();
+        sums.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+
+        // The levels are kept from the rows without a finite x position
+        let mut levels: Vec<String> = sums.iter().filter_map(|sum| sum.0.clone()).collect();
+        levels.dedup();
+        sums.retain(|&(_, x, _)| x.is_finite());
+
+        (levels, sums)
+    }
+
+    /// Get the stacked rectangles of the bars, from the bottom to the top, and the levels of the groups.
+    fn stacks(&self) -> &Stacks {
+        self.stat.get_or_init(|| {
+            let data = self.data.as_ref().expect("data is not provided");
+            let (levels, sums) = self.sums(data);
+
+            let mut positions: Vec<f64> = sums.iter().map(|&(_, x, _)| x).collect();
+            positions.sort_by(f64::total_cmp);
+            positions.dedup();
+
+            let width = self.width.unwrap_or_else(|| 0.9 * resolution(&positions));
+
+            let mut tops = vec![0.0; positions.len()];
+            let mut stacks = vec![];
+            for (level, x, sum) in sums {
+                if sum == 0.0 || !sum.is_finite() {
+                    continue;
+                }
+
+                let Ok(j) = positions.binary_search_by(|x_j| x_j.total_cmp(&x)) else {
+                    continue;
+                };
+                let bottom = tops[j];
+                tops[j] += sum;
+
+                stacks.push((
+                    level,
+                    [(x - width / 2.0, bottom), (x + width / 2.0, tops[j])],
+                ));
+            }
+
+            Stacks { stacks, levels }
+        })
+    }
+
+    fn draw_2d<'a, DB>(
+        &'a self,
+        chart: &mut ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+        panel: &Panel,
+        scales: &Scales,
+    ) -> anyhow::Result<()>
+    where
+        DB: DrawingBackend + 'a,
+        <DB as plotters::prelude::DrawingBackend>::ErrorType: 'static,
+    {
+        let mapping = &self.mapping;
+        let stacks = &self.stacks().stacks;
+
+        // The groups are filled by the color scale, the others by the constant fill
+        let fill = match &mapping.fill {
+            Some(ColorMapping::None) => None,
+            Some(ColorMapping::Constant(fill)) => Some(fill.as_rgb()),
+            _ => Some(RGBColor(89, 89, 89)),
+        };
+
+        draw_rects(
+            chart,
+            panel,
+            stacks.iter().filter_map(|(level, rect)| {
+                let fill = match level {
+                    Some(level) => scales.color.map(level),
+                    None => fill?,
+                };
+
+                Some((*rect, fill.filled()))
+            }),
+        )?;
+
+        if let Some(color) = mapping.color.as_ref().and_then(ColorMapping::as_constant) {
+            draw_rects(
+                chart,
+                panel,
+                stacks.iter().map(|&(_, rect)| (rect, color.into())),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// # geom_bar!: Construct a [`Bar`] layer
+///
+/// This macro is used to create a [`Bar`] layer in a more concise way like `ggplot2`.
+/// It is a wrapper around [`Bar::builder`].
+///
+/// ## Usage
+///
+/// ```ignore
+/// geom_bar!(
+///     mapping = aes!(x, fill = <GROUP_COLUMN>),
+///     data = <Data>,
+///     width = <f64>,
+/// )
+/// ```
+///
+/// ### Arguments
+///
+/// #### mapping
+///
+/// Set of aesthetic mappings created by [`aes!`](crate::aes!) or [`Aes::builder`](crate::aes::Aes::builder).
+///
+/// `x` is the position of the bars. Without `x`, all rows are stacked in a
+/// single bar, e.g. for a pie chart. The heights are the numbers of rows, or
+/// the sums of `y` if it is mapped.
+///
+/// If a column is mapped to `fill` (or `color`), the rows of each bar are
+/// stacked by its values, filled by the color scale, e.g. [`scale_color_manual!`](crate::scale_color_manual!).
+/// A constant `fill` fills all bars, and a constant `color` draws their borders.
+///
+/// #### data
+///
+/// The data to be displayed in this layer.
+///
+/// If not provided, it will be inherited from the [`Plot`](crate::Plot).
+///
+/// #### width
+///
+/// The width of the bars in data units. Defaults to 90% of the smallest
+/// distance between the x values.
+#[macro_export]
+macro_rules! geom_bar {
+    ($($param: ident = $value: expr),* $(,)?) => {
+        $crate::layer::geom::bar::Bar::builder()
+            $(.$param($value))*
+            .build()
+            .unwrap()
+    };
+
+    ($mapping: expr $(, $($param: ident = $value: expr),+ $(,)?)?) => {
+        geom_bar!(mapping = $mapping $(, $($param = $value),+)?)
+    };
+}
+
+impl Layer for Bar {
+    fn data_mut(&mut self) -> &mut Option<Arc<dyn crate::data::Data>> {
+        self.stat.reset();
+        &mut self.data
+    }
+
+    fn mapping_mut(&mut self) -> &mut crate::aes::Aes {
+        self.stat.reset();
+        &mut self.mapping
+    }
+
+    fn compute(&self) {
+        self.stacks();
+    }
+
+    fn range_2d(&self) -> Option<(f64, f64, f64, f64)> {
+        self.stacks()
+            .stacks
+            .iter()
+            .fold(None, |acc, &(_, [(x0, y0), (x1, y1)])| {
+                let (a, b, c, d) = acc.unwrap_or((x0, x1, 0.0, 0.0));
+
+                Some((a.min(x0), b.max(x1), c.min(y0), d.max(y1)))
+            })
+    }
+
+    fn color_levels(&self) -> Option<(&str, Vec<String>)> {
+        let column = self.group_column()?;
+
+        Some((column, self.stacks().levels.clone()))
+    }
+
+    #[cfg(feature = "svg")]
+    fn draw_svg_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::SVGBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        scales: &Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel, scales)
+    }
+
+    #[cfg(feature = "png")]
+    fn draw_png_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::BitMapBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        scales: &Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel, scales)
+    }
+}
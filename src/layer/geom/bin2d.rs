@@ -0,0 +1,269 @@
+//! # Bin2d geom layer
+//!
+//! The bin2d geom divides the plane into rectangular bins, counts the points
+//! in each bin and fills the bins by their counts. It is an alternative to
+//! [scatter plots](super::point) of large datasets, where the points overlap.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use gongbi::*;
+//! # fn main() -> anyhow::Result<()> {
+//! let p = plot!(csv = "examples/mpg.csv", mapping = aes!(displ, hwy))
+//!     + geom_bin2d!(bins = 10);
+//!
+//! p.to_svg("geom_bin2d.svg", (800, 600))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! See [`geom_bin2d!`](crate::geom_bin2d!) or [`Bin2d::builder`] for more details.
+
+use std::sync::Arc;
+
+use derive_builder::Builder;
+use plotters::{
+    chart::ChartContext,
+    coord::types::RangedCoordf64,
+    prelude::{Cartesian2d, DrawingBackend},
+    style::Color,
+};
+
+use crate::{
+    aes::color::ColorMapping,
+    coord::Panel,
+    layer::{stat, Layer},
+    scale::Scales,
+};
+
+use super::draw_rects;
+
+/// A non-empty bin with its count
+type Bin = ([(f64, f64); 2], f64);
+
+/// # Bin2d layer
+///
+/// The bin2d geom layer is used to draw the counts of points in rectangular bins.
+///
+/// To create a bin2d layer, [`geom_bin2d!`](crate::geom_bin2d!) and [`Bin2d::builder`] can be used.
+#[derive(Clone, Debug, Builder)]
+pub struct Bin2d {
+    /// The data source for the layer.
+    #[builder(default, setter(strip_option))]
+    data: Option<Arc<dyn crate::data::Data>>,
+
+    /// The aes mapping for the layer.
+    #[builder(default)]
+    mapping: crate::aes::Aes,
+
+    /// The number of bins in both directions.
+    ///
+    /// It is overridden by `binwidth`.
+    #[builder(default = "30")]
+    bins: usize,
+
+    /// The width and the height of the bins in data units.
+    #[builder(default, setter(strip_option))]
+    binwidth: Option<(f64, f64)>,
+
+    /// The non-empty bins and their counts, computed once from the data.
+    #[builder(setter(skip))]
+    stat: stat::Cache<Vec<Bin>>,
+}
+
+impl Default for Bin2d {
+    fn default() -> Self {
+        Bin2d::builder().build().unwrap()
+    }
+}
+
+impl Bin2d {
+    /// Create a new [`Bin2d`] via the builder pattern.
+    pub fn builder() -> Bin2dBuilder {
+        Bin2dBuilder::default()
+    }
+
+    /// Get the non-empty bins and their counts.
+    fn bins(&self) -> &[Bin] {
+        self.stat.get_or_init(|| {
+            let data = self.data.as_ref().expect("data is not provided");
+            let mapping = &self.mapping;
+
+            let column_x = mapping
+                .x
+                .as_ref()
+                .expect("x must be provided for geom_bin2d");
+            let column_y = mapping
+                .y
+                .as_ref()
+                .expect("y must be provided for geom_bin2d");
+
+            // A lazy frame counts the points in its query
+            #[cfg(feature = "polars")]
+            if let Some(lazy) = data.as_lazy() {
+                let positions = (column_x.expr(), column_y.expr());
+                return stat::bin_2d_lazy(lazy, positions, self.bins, self.binwidth);
+            }
+
+            let points =
+                stat::finite_pairs(&data.position_f64(column_x), &data.position_f64(column_y));
+
+            stat::bin_2d(&points, self.bins, self.binwidth)
+        })
+    }
+
+    fn draw_2d<'a, DB>(
+        &'a self,
+        chart: &mut ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+        panel: &Panel,
+        scales: &Scales,
+    ) -> anyhow::Result<()>
+    where
+        DB: DrawingBackend + 'a,
+        <DB as plotters::prelude::DrawingBackend>::ErrorType: 'static,
+    {
+        let bins = self.bins();
+
+        draw_rects(
+            chart,
+            panel,
+            bins.iter()
+                .map(|&(rect, count)| (rect, scales.fill.map(count).filled())),
+        )?;
+
+        if let Some(color) = self
+            .mapping
+            .color
+            .as_ref()
+            .and_then(ColorMapping::as_constant)
+        {
+            draw_rects(
+                chart,
+                panel,
+                bins.iter().map(|&(rect, _)| (rect, color.into())),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// # geom_bin2d!: Construct a [`Bin2d`] layer
+///
+/// This macro is used to create a [`Bin2d`] layer in a more concise way like `ggplot2`.
+/// It is a wrapper around [`Bin2d::builder`].
+///
+/// ## Usage
+///
+/// ```ignore
+/// geom_bin2d!(
+///     mapping = aes!(x, y),
+///     data = <Data>,
+///     bins = <usize>,
+///     binwidth = (<f64>, <f64>),
+/// )
+/// ```
+///
+/// ### Arguments
+///
+/// #### mapping
+///
+/// Set of aesthetic mappings created by [`aes!`](crate::aes!) or [`Aes::builder`](crate::aes::Aes::builder).
+///
+/// The bins are filled by their counts through the fill scale, e.g.
+/// [`scale_fill_viridis_c!`](crate::scale_fill_viridis_c!). If `color` is set,
+/// the border of the bins is drawn.
+///
+/// #### data
+///
+/// The data to be displayed in this layer.
+///
+/// If not provided, it will be inherited from the [`Plot`](crate::Plot).
+///
+/// #### bins, binwidth
+///
+/// The number of bins in both directions, defaults to 30, or the size of the
+/// bins in data units.
+#[macro_export]
+macro_rules! geom_bin2d {
+    ($($param: ident = $value: expr),* $(,)?) => {
+        $crate::layer::geom::bin2d::Bin2d::builder()
+            $(.$param($value))*
+            .build()
+            .unwrap()
+    };
+
+    ($mapping: expr $(, $($param: ident = $value: expr),+ $(,)?)?) => {
+        geom_bin2d!(mapping = $mapping $(, $($param = $value),+)?)
+    };
+}
+
+impl Layer for Bin2d {
+    fn data_mut(&mut self) -> &mut Option<Arc<dyn crate::data::Data>> {
+        self.stat.reset();
+        &mut self.data
+    }
+
+    fn mapping_mut(&mut self) -> &mut crate::aes::Aes {
+        self.stat.reset();
+        &mut self.mapping
+    }
+
+    fn compute(&self) {
+        self.bins();
+    }
+
+    fn range_2d(&self) -> Option<(f64, f64, f64, f64)> {
+        self.bins()
+            .iter()
+            .map(|&(rect, _)| rect)
+            .fold(None, |acc, [a, b]| {
+                let (x0, x1, y0, y1) = acc.unwrap_or((a.0, b.0, a.1, b.1));
+
+                Some((x0.min(a.0), x1.max(b.0), y0.min(a.1), y1.max(b.1)))
+            })
+    }
+
+    fn fill_range(&self) -> Option<(f64, f64)> {
+        self.bins()
+            .iter()
+            .map(|&(_, count)| count)
+            .fold(None, |acc, c| {
+                let (low, high) = acc.unwrap_or((c, c));
+
+                Some((low.min(c), high.max(c)))
+            })
+    }
+
+    fn fill_title(&self) -> Option<&str> {
+        Some("count")
+    }
+
+    #[cfg(feature = "svg")]
+    fn draw_svg_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::SVGBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        scales: &Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel, scales)
+    }
+
+    #[cfg(feature = "png")]
+    fn draw_png_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::BitMapBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        scales: &Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel, scales)
+    }
+}
@@ -0,0 +1,354 @@
+//! # Contour geom layer
+//!
+//! The contour geom draws the iso-lines of values on a regular (x, y) grid,
+//! e.g. the error of a model against two hyperparameters. The filled variant
+//! fills the bands between the iso-lines by their level.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use gongbi::*;
+//! # use polars::prelude::*;
+//! # fn main() -> anyhow::Result<()> {
+//! let (x, y): (Vec<f64>, Vec<f64>) = (0..21 * 21)
+//!     .map(|i| ((i % 21) as f64 / 10.0 - 1.0, (i / 21) as f64 / 10.0 - 1.0))
+//!     .unzip();
+//! let z: Vec<f64> = x.iter().zip(&y).map(|(x, y)| x * x + y * y).collect();
+//!
+//! let df = df!("x" => x, "y" => y, "z" => z)?;
+//!
+//! let p = plot!(df, aes!(x, y, z = z))
+//!     + geom_contour_filled!(bins = 5)
+//!     + geom_contour!(aes!(color = "white"), bins = 5);
+//!
+//! p.to_svg("geom_contour.svg", (800, 600))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! See [`geom_contour!`](crate::geom_contour!), [`geom_contour_filled!`](crate::geom_contour_filled!)
+//! or [`Contour::builder`] for more details.
+
+use std::sync::Arc;
+
+use derive_builder::Builder;
+use plotters::{
+    chart::ChartContext,
+    coord::types::RangedCoordf64,
+    prelude::{Cartesian2d, DrawingBackend, PathElement, Polygon},
+    style::{Color, RGBColor},
+};
+
+use crate::{
+    aes::Aes,
+    coord::Panel,
+    layer::{stat, Layer},
+    scale::Scales,
+};
+
+use super::constant_color;
+
+/// # Contour layer
+///
+/// The contour geom layer is used to draw the iso-lines of the z column on a
+/// regular (x, y) grid, or the bands between them if `filled` is set.
+///
+/// To create a contour layer, [`geom_contour!`](crate::geom_contour!),
+/// [`geom_contour_filled!`](crate::geom_contour_filled!) and [`Contour::builder`] can be used.
+#[derive(Clone, Debug, Builder)]
+pub struct Contour {
+    /// The data source for the layer.
+    #[builder(default, setter(strip_option))]
+    data: Option<Arc<dyn crate::data::Data>>,
+
+    /// The aes mapping for the layer.
+    #[builder(default)]
+    mapping: crate::aes::Aes,
+
+    /// Whether to fill the bands between the iso-lines.
+    #[builder(default)]
+    filled: bool,
+
+    /// The approximate number of levels.
+    ///
+    /// It is overridden by `breaks`.
+    #[builder(default = "10")]
+    bins: usize,
+
+    /// The levels of the iso-lines.
+    #[builder(default, setter(into, strip_option))]
+    breaks: Option<Vec<f64>>,
+
+    /// The grid of the values and the levels of the contours, computed once from the data.
+    #[builder(setter(skip))]
+    stat: stat::Cache<(stat::Grid, Vec<f64>)>,
+}
+
+impl Default for Contour {
+    fn default() -> Self {
+        Contour::builder().build().unwrap()
+    }
+}
+
+impl Contour {
+    /// Create a new [`Contour`] via the builder pattern.
+    pub fn builder() -> ContourBuilder {
+        ContourBuilder::default()
+    }
+
+    /// Get the grid of the values and the levels.
+    fn grid(&self) -> &(stat::Grid, Vec<f64>) {
+        self.stat.get_or_init(|| {
+            let data = self.data.as_ref().expect("data is not provided");
+            let mapping = &self.mapping;
+
+            let column_x = mapping
+                .x
+                .as_ref()
+                .expect("x must be provided for geom_contour");
+            let column_y = mapping
+                .y
+                .as_ref()
+                .expect("y must be provided for geom_contour");
+            let column_z = mapping
+                .z
+                .as_deref()
+                .expect("z must be provided for geom_contour");
+
+            let grid = stat::Grid::from_points(
+                &data.position_f64(column_x),
+                &data.position_f64(column_y),
+                &data.column_f64(column_z),
+            );
+            let levels = stat::levels(
+                grid.range_z().unwrap_or((0.0, 0.0)),
+                self.bins,
+                self.breaks.as_deref(),
+            );
+
+            (grid, levels)
+        })
+    }
+
+    fn draw_2d<'a, DB>(
+        &'a self,
+        chart: &mut ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+        panel: &Panel,
+        scales: &Scales,
+    ) -> anyhow::Result<()>
+    where
+        DB: DrawingBackend + 'a,
+        <DB as plotters::prelude::DrawingBackend>::ErrorType: 'static,
+    {
+        let (grid, levels) = self.grid();
+
+        draw_contours(
+            chart,
+            panel,
+            grid,
+            levels,
+            self.filled,
+            &self.mapping,
+            scales,
+        )
+    }
+}
+
+/// Get the bands between consecutive levels.
+pub(super) fn bands(levels: &[f64]) -> impl Iterator<Item = (f64, f64)> + '_ {
+    levels.windows(2).map(|w| (w[0], w[1]))
+}
+
+/// Get the range of the middle values of the bands, which are mapped to the fill colors.
+pub(super) fn bands_fill_range(levels: &[f64]) -> Option<(f64, f64)> {
+    let mids: Vec<f64> = bands(levels)
+        .map(|(low, high)| (low + high) / 2.0)
+        .collect();
+
+    Some((*mids.first()?, *mids.last()?))
+}
+
+/// Draw the iso-lines of the grid at the levels, or the bands between them if `filled`.
+///
+/// The bands are filled by their middle values through the fill scale. The
+/// iso-lines are drawn on top of the bands only if a constant color is set.
+pub(super) fn draw_contours<'a, DB>(
+    chart: &mut ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+    panel: &Panel,
+    grid: &stat::Grid,
+    levels: &[f64],
+    filled: bool,
+    mapping: &Aes,
+    scales: &Scales,
+) -> anyhow::Result<()>
+where
+    DB: DrawingBackend + 'a,
+    <DB as plotters::prelude::DrawingBackend>::ErrorType: 'static,
+{
+    if filled {
+        for (low, high) in bands(levels) {
+            let style = scales.fill.map((low + high) / 2.0).filled();
+
+            chart.draw_series(
+                stat::contour_band(grid, low, high)
+                    .into_iter()
+                    .map(|polygon| Polygon::new(panel.polygon(&polygon), style)),
+            )?;
+        }
+    }
+
+    let has_color = mapping
+        .color
+        .as_ref()
+        .is_some_and(|color| color.as_constant().is_some());
+
+    if !filled || has_color {
+        let color = constant_color(mapping, RGBColor(0x33, 0x66, 0xFF));
+        let style = color.stroke_width(mapping.size.unwrap_or(1).max(1) as u32);
+
+        for &level in levels {
+            chart.draw_series(
+                stat::contour_lines(grid, level)
+                    .into_iter()
+                    .map(|path| PathElement::new(panel.path(&path), style)),
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// # geom_contour!: Construct a [`Contour`] layer
+///
+/// This macro is used to create a [`Contour`] layer drawing iso-lines in a
+/// more concise way like `ggplot2`.
+/// It is a wrapper around [`Contour::builder`].
+///
+/// ## Usage
+///
+/// ```ignore
+/// geom_contour!(
+///     mapping = aes!(x, y, z = <Z_COLUMN>),
+///     data = <Data>,
+///     bins = <usize>,
+///     breaks = vec![<f64>, ...],
+/// )
+/// ```
+///
+/// ### Arguments
+///
+/// #### mapping
+///
+/// Set of aesthetic mappings created by [`aes!`](crate::aes!) or [`Aes::builder`](crate::aes::Aes::builder).
+///
+/// `x` and `y` should form a regular grid, and `z` is the value at each
+/// position. `color` and `size` set the color and the width of the iso-lines.
+///
+/// #### data
+///
+/// The data to be displayed in this layer.
+///
+/// If not provided, it will be inherited from the [`Plot`](crate::Plot).
+///
+/// #### bins, breaks
+///
+/// The approximate number of levels, defaults to 10, or the levels.
+#[macro_export]
+macro_rules! geom_contour {
+    ($($param: ident = $value: expr),* $(,)?) => {
+        $crate::layer::geom::contour::Contour::builder()
+            $(.$param($value))*
+            .build()
+            .unwrap()
+    };
+
+    ($mapping: expr $(, $($param: ident = $value: expr),+ $(,)?)?) => {
+        geom_contour!(mapping = $mapping $(, $($param = $value),+)?)
+    };
+}
+
+/// # geom_contour_filled!: Construct a filled [`Contour`] layer
+///
+/// This macro is used to create a [`Contour`] layer filling the bands between
+/// the iso-lines in a more concise way like `ggplot2`. The bands are filled
+/// by their level through the fill scale, e.g. [`scale_fill_viridis_c!`](crate::scale_fill_viridis_c!).
+///
+/// It accepts the same arguments as [`geom_contour!`](crate::geom_contour!).
+/// The iso-lines are drawn on top of the bands if `color` is set.
+#[macro_export]
+macro_rules! geom_contour_filled {
+    ($($param: ident = $value: expr),* $(,)?) => {
+        geom_contour!(filled = true $(, $param = $value)*)
+    };
+
+    ($mapping: expr $(, $($param: ident = $value: expr),+ $(,)?)?) => {
+        geom_contour!(mapping = $mapping, filled = true $(, $($param = $value),+)?)
+    };
+}
+
+impl Layer for Contour {
+    fn data_mut(&mut self) -> &mut Option<Arc<dyn crate::data::Data>> {
+        self.stat.reset();
+        &mut self.data
+    }
+
+    fn mapping_mut(&mut self) -> &mut crate::aes::Aes {
+        self.stat.reset();
+        &mut self.mapping
+    }
+
+    fn compute(&self) {
+        self.grid();
+    }
+
+    fn range_2d(&self) -> Option<(f64, f64, f64, f64)> {
+        let (grid, _) = self.grid();
+
+        Some((
+            *grid.x.first()?,
+            *grid.x.last()?,
+            *grid.y.first()?,
+            *grid.y.last()?,
+        ))
+    }
+
+    fn fill_range(&self) -> Option<(f64, f64)> {
+        if !self.filled {
+            return None;
+        }
+
+        bands_fill_range(&self.grid().1)
+    }
+
+    fn fill_title(&self) -> Option<&str> {
+        self.filled.then_some("level")
+    }
+
+    #[cfg(feature = "svg")]
+    fn draw_svg_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::SVGBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        scales: &Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel, scales)
+    }
+
+    #[cfg(feature = "png")]
+    fn draw_png_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::BitMapBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        scales: &Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel, scales)
+    }
+}
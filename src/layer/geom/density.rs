@@ -0,0 +1,320 @@
+//! # Density geom layer
+//!
+//! The density geom estimates the distribution of a continuous variable with a
+//! kernel density estimate, and draws it as a smooth curve. It is a smoothed
+//! alternative to a histogram.
+//!
+//! The rows can be grouped by the column mapped to `color` (or `fill`) to
+//! overlay the distributions of the groups.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use gongbi::*;
+//! # fn main() -> anyhow::Result<()> {
+//! let p = plot!(csv = "examples/mpg.csv", mapping = aes!(hwy, color = drv, fill = drv))
+//!     + geom_density!(alpha = 0.3);
+//!
+//! p.to_svg("geom_density.svg", (800, 600))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! See [`geom_density!`](crate::geom_density!) or [`Density::builder`] for more details.
+
+use std::sync::Arc;
+
+use derive_builder::Builder;
+use plotters::{
+    chart::ChartContext,
+    coord::types::RangedCoordf64,
+    prelude::{Cartesian2d, DrawingBackend, Polygon},
+    series::LineSeries,
+    style::{Color, BLACK},
+};
+
+use crate::{
+    aes::color::ColorMapping,
+    coord::Panel,
+    layer::{stat, Layer},
+    scale::Scales,
+};
+
+pub use crate::layer::stat::Kernel;
+
+use super::groups;
+
+/// The density curve of a group, with the value of the group if any
+type Curve = (Option<String>, Vec<(f64, f64)>);
+
+/// # Density layer
+///
+/// The density geom layer is used to draw kernel density estimates of the x column.
+///
+/// To create a density layer, [`geom_density!`](crate::geom_density!) and [`Density::builder`] can be used.
+#[derive(Clone, Debug, Builder)]
+pub struct Density {
+    /// The data source for the layer.
+    #[builder(default, setter(strip_option))]
+    data: Option<Arc<dyn crate::data::Data>>,
+
+    /// The aes mapping for the layer.
+    #[builder(default)]
+    mapping: crate::aes::Aes,
+
+    /// The kernel of the estimation.
+    #[builder(default, setter(into))]
+    kernel: Kernel,
+
+    /// The standard deviation of the kernel.
+    ///
+    /// If not provided, it is chosen by Silverman's rule of thumb
+    /// `0.9 * min(sd, IQR / 1.34) * n^(-1/5)` for each group.
+    #[builder(default, setter(strip_option))]
+    bw: Option<f64>,
+
+    /// The multiplier of the bandwidth.
+    #[builder(default = "1.0")]
+    adjust: f64,
+
+    /// The number of points to estimate the density at.
+    #[builder(default = "512")]
+    n: usize,
+
+    /// The opacity of the fill, from `0.0` to `1.0`.
+    #[builder(default = "1.0")]
+    alpha: f64,
+
+    /// The density curves of the groups, computed once from the data.
+    #[builder(setter(skip))]
+    stat: stat::Cache<Vec<Curve>>,
+}
+
+impl Default for Density {
+    fn default() -> Self {
+        Density::builder().build().unwrap()
+    }
+}
+
+impl Density {
+    /// Create a new [`Density`] via the builder pattern.
+    pub fn builder() -> DensityBuilder {
+        DensityBuilder::default()
+    }
+
+    /// Get the column to group the rows by, i.e. the column mapped to color or fill.
+    fn group_column(&self) -> Option<&str> {
+        let mapping = &self.mapping;
+
+        [&mapping.color, &mapping.fill]
+            .into_iter()
+            .find_map(|mapping| mapping.as_ref()?.as_column())
+    }
+
+    /// Get the density curve of each group.
+    fn curves(&self) -> &[Curve] {
+        self.stat.get_or_init(|| {
+            let data = self.data.as_ref().expect("data is not provided");
+            let column_x = self
+                .mapping
+                .x
+                .as_ref()
+                .expect("x must be provided for geom_density");
+
+            let x = data.position_f64(column_x);
+
+            groups(data, self.group_column(), x.len())
+                .into_iter()
+                .map(|(level, rows)| {
+                    let values: Vec<f64> = rows
+                        .into_iter()
+                        .map(|i| x[i])
+                        .filter(|v| v.is_finite())
+                        .collect();
+                    let bw = self.bw.unwrap_or_else(|| stat::bandwidth_nrd0(&values));
+
+                    (
+                        level,
+                        stat::kde(&values, self.n, self.kernel, bw * self.adjust),
+                    )
+                })
+                .collect()
+        })
+    }
+
+    fn draw_2d<'a, DB>(
+        &'a self,
+        chart: &mut ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+        panel: &Panel,
+        scales: &Scales,
+    ) -> anyhow::Result<()>
+    where
+        DB: DrawingBackend + 'a,
+        <DB as plotters::prelude::DrawingBackend>::ErrorType: 'static,
+    {
+        let mapping = &self.mapping;
+
+        // The mapped colors come from the groups, the others are constant
+        let resolve = |color: &Option<ColorMapping>, level: &Option<String>| match color {
+            Some(ColorMapping::Column(_)) => level.as_ref().map(|level| scales.color.map(level)),
+            Some(color) => color.as_constant(),
+            None => None,
+        };
+
+        for (level, curve) in self.curves() {
+            let (Some(first), Some(last)) = (curve.first(), curve.last()) else {
+                continue;
+            };
+
+            if let Some(fill) = resolve(&mapping.fill, level) {
+                let mut area = curve.clone();
+                area.extend([(last.0, 0.0), (first.0, 0.0)]);
+
+                chart.draw_series(std::iter::once(Polygon::new(
+                    panel.polygon(&area),
+                    fill.mix(self.alpha).filled(),
+                )))?;
+            }
+
+            if mapping.color == Some(ColorMapping::None) {
+                continue;
+            }
+
+            let color = resolve(&mapping.color, level).unwrap_or(BLACK);
+            let style = color.stroke_width(mapping.size.unwrap_or(1).max(1) as u32);
+
+            chart.draw_series(LineSeries::new(panel.path(curve), style))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// # geom_density!: Construct a [`Density`] layer
+///
+/// This macro is used to create a [`Density`] layer in a more concise way like `ggplot2`.
+/// It is a wrapper around [`Density::builder`].
+///
+/// ## Usage
+///
+/// ```ignore
+/// geom_density!(
+///     mapping = aes!(x, color = <GROUP_COLUMN>, fill = <Color or GROUP_COLUMN>),
+///     data = <Data>,
+///     kernel = "gaussian",
+///     bw = <f64>,
+///     adjust = <f64>,
+///     n = <usize>,
+///     alpha = <f64>,
+/// )
+/// ```
+///
+/// ### Arguments
+///
+/// #### mapping
+///
+/// Set of aesthetic mappings created by [`aes!`](crate::aes!) or [`Aes::builder`](crate::aes::Aes::builder).
+///
+/// If a column is mapped to `color` or `fill`, the rows are grouped by its
+/// values, and a density is estimated for each group. The groups are colored
+/// by the color scale, e.g. [`scale_color_manual!`](crate::scale_color_manual!).
+/// A constant `fill` fills the area under all curves. `size` sets the width of the curves.
+///
+/// #### data
+///
+/// The data to be displayed in this layer.
+///
+/// If not provided, it will be inherited from the [`Plot`](crate::Plot).
+///
+/// #### kernel
+///
+/// The kernel of the estimation, one of `"gaussian"` (default), `"rectangular"`,
+/// `"triangular"`, `"epanechnikov"`, `"biweight"`, `"cosine"` and `"optcosine"`.
+///
+/// #### bw, adjust
+///
+/// The standard deviation of the kernel, chosen by Silverman's rule of thumb
+/// by default, and a multiplier of it, defaults to 1.
+///
+/// #### n
+///
+/// The number of points to estimate the density at, defaults to 512.
+///
+/// #### alpha
+///
+/// The opacity of the fill, defaults to 1.
+#[macro_export]
+macro_rules! geom_density {
+    ($($param: ident = $value: expr),* $(,)?) => {
+        $crate::layer::geom::density::Density::builder()
+            $(.$param($value))*
+            .build()
+            .unwrap()
+    };
+
+    ($mapping: expr $(, $($param: ident = $value: expr),+ $(,)?)?) => {
+        geom_density!(mapping = $mapping $(, $($param = $value),+)?)
+    };
+}
+
+impl Layer for Density {
+    fn data_mut(&mut self) -> &mut Option<Arc<dyn crate::data::Data>> {
+        self.stat.reset();
+        &mut self.data
+    }
+
+    fn mapping_mut(&mut self) -> &mut crate::aes::Aes {
+        self.stat.reset();
+        &mut self.mapping
+    }
+
+    fn compute(&self) {
+        self.curves();
+    }
+
+    fn range_2d(&self) -> Option<(f64, f64, f64, f64)> {
+        self.curves()
+            .iter()
+            .flat_map(|(_, curve)| curve)
+            .fold(None, |acc, &(x, y)| {
+                let (x0, x1, y0, y1) = acc.unwrap_or((x, x, 0.0, y));
+
+                Some((x0.min(x), x1.max(x), y0, y1.max(y)))
+            })
+    }
+
+    fn color_levels(&self) -> Option<(&str, Vec<String>)> {
+        let data = self.data.as_ref().expect("data is not provided");
+        let column = self.group_column()?;
+
+        Some((column, data.column_levels(column)))
+    }
+
+    #[cfg(feature = "svg")]
+    fn draw_svg_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::SVGBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        scales: &Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel, scales)
+    }
+
+    #[cfg(feature = "png")]
+    fn draw_png_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::BitMapBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        scales: &Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel, scales)
+    }
+}
@@ -0,0 +1,311 @@
+//! # Density 2d geom layer
+//!
+//! The density 2d geom estimates the 2D density of the points with a gaussian
+//! kernel and draws the contours of the density. Unlike a [scatter plot](super::point),
+//! it stays readable for large datasets, where the points overlap.
+//!
+//! The filled variant fills the bands between the contours by their density level.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use gongbi::*;
+//! # fn main() -> anyhow::Result<()> {
+//! let p = plot!(csv = "examples/mpg.csv", mapping = aes!(displ, hwy))
+//!     + geom_density_2d_filled!()
+//!     + geom_point!();
+//!
+//! p.to_svg("geom_density_2d.svg", (800, 600))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! See [`geom_density_2d!`](crate::geom_density_2d!), [`geom_density_2d_filled!`](crate::geom_density_2d_filled!)
+//! or [`Density2d::builder`] for more details.
+
+use std::sync::Arc;
+
+use derive_builder::Builder;
+use plotters::{
+    chart::ChartContext,
+    coord::types::RangedCoordf64,
+    prelude::{Cartesian2d, DrawingBackend},
+};
+
+use crate::{
+    coord::Panel,
+    layer::{stat, Layer},
+    scale::Scales,
+};
+
+use super::contour::{bands_fill_range, draw_contours};
+
+/// # Density 2d layer
+///
+/// The density 2d geom layer is used to draw the contours of the 2D kernel
+/// density of the points, or the bands between them if `filled` is set.
+///
+/// To create a density 2d layer, [`geom_density_2d!`](crate::geom_density_2d!),
+/// [`geom_density_2d_filled!`](crate::geom_density_2d_filled!) and [`Density2d::builder`] can be used.
+#[derive(Clone, Debug, Builder)]
+pub struct Density2d {
+    /// The data source for the layer.
+    #[builder(default, setter(strip_option))]
+    data: Option<Arc<dyn crate::data::Data>>,
+
+    /// The aes mapping for the layer.
+    #[builder(default)]
+    mapping: crate::aes::Aes,
+
+    /// Whether to fill the bands between the contours.
+    #[builder(default)]
+    filled: bool,
+
+    /// The number of grid points in both directions.
+    #[builder(default = "100")]
+    n: usize,
+
+    /// The standard deviations of the gaussian kernel in x and y.
+    ///
+    /// If not provided, they are estimated by the rule of thumb
+    /// `1.06 * min(sd, IQR / 1.34) * n^(-1/5)`.
+    #[builder(default, setter(strip_option))]
+    bandwidth: Option<(f64, f64)>,
+
+    /// The multiplier of the bandwidth.
+    #[builder(default = "1.0")]
+    adjust: f64,
+
+    /// The approximate number of contour levels.
+    ///
+    /// It is overridden by `breaks`.
+    #[builder(default = "10")]
+    bins: usize,
+
+    /// The contour levels.
+    #[builder(default, setter(into, strip_option))]
+    breaks: Option<Vec<f64>>,
+
+    /// The estimated density and the levels of the contours, computed once from the data.
+    #[builder(setter(skip))]
+    stat: stat::Cache<(stat::Grid, Vec<f64>)>,
+}
+
+impl Default for Density2d {
+    fn default() -> Self {
+        Density2d::builder().build().unwrap()
+    }
+}
+
+impl Density2d {
+    /// Create a new [`Density2d`] via the builder pattern.
+    pub fn builder() -> Density2dBuilder {
+        Density2dBuilder::default()
+    }
+
+    /// Get the estimated density on a grid and the contour levels.
+    fn density(&self) -> &(stat::Grid, Vec<f64>) {
+        self.stat.get_or_init(|| {
+            let data = self.data.as_ref().expect("data is not provided");
+            let mapping = &self.mapping;
+
+            let column_x = mapping
+                .x
+                .as_ref()
+                .expect("x must be provided for geom_density_2d");
+            let column_y = mapping
+                .y
+                .as_ref()
+                .expect("y must be provided for geom_density_2d");
+
+            let points =
+                stat::finite_pairs(&data.position_f64(column_x), &data.position_f64(column_y));
+
+            let (sd_x, sd_y) = self.bandwidth.unwrap_or_else(|| {
+                let x: Vec<f64> = points.iter().map(|p| p.0).collect();
+                let y: Vec<f64> = points.iter().map(|p| p.1).collect();
+
+                (stat::bandwidth_nrd(&x), stat::bandwidth_nrd(&y))
+            });
+
+            let grid = stat::kde_2d(&points, self.n, (sd_x * self.adjust, sd_y * self.adjust));
+            let levels = stat::levels(
+                grid.range_z().unwrap_or((0.0, 0.0)),
+                self.bins,
+                self.breaks.as_deref(),
+            );
+
+            (grid, levels)
+        })
+    }
+
+    fn draw_2d<'a, DB>(
+        &'a self,
+        chart: &mut ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+        panel: &Panel,
+        scales: &Scales,
+    ) -> anyhow::Result<()>
+    where
+        DB: DrawingBackend + 'a,
+        <DB as plotters::prelude::DrawingBackend>::ErrorType: 'static,
+    {
+        let (grid, levels) = self.density();
+
+        draw_contours(
+            chart,
+            panel,
+            grid,
+            levels,
+            self.filled,
+            &self.mapping,
+            scales,
+        )
+    }
+}
+
+/// # geom_density_2d!: Construct a [`Density2d`] layer
+///
+/// This macro is used to create a [`Density2d`] layer drawing the contours of
+/// the density in a more concise way like `ggplot2`.
+/// It is a wrapper around [`Density2d::builder`].
+///
+/// ## Usage
+///
+/// ```ignore
+/// geom_density_2d!(
+///     mapping = aes!(x, y),
+///     data = <Data>,
+///     n = <usize>,
+///     bandwidth = (<f64>, <f64>),
+///     adjust = <f64>,
+///     bins = <usize>,
+///     breaks = vec![<f64>, ...],
+/// )
+/// ```
+///
+/// ### Arguments
+///
+/// #### mapping
+///
+/// Set of aesthetic mappings created by [`aes!`](crate::aes!) or [`Aes::builder`](crate::aes::Aes::builder).
+///
+/// `color` and `size` set the color and the width of the contours.
+///
+/// #### data
+///
+/// The data to be displayed in this layer.
+///
+/// If not provided, it will be inherited from the [`Plot`](crate::Plot).
+///
+/// #### n
+///
+/// The number of grid points in both directions, defaults to 100.
+///
+/// #### bandwidth, adjust
+///
+/// The standard deviations of the gaussian kernel in x and y, estimated from
+/// the data by default, and a multiplier of them, defaults to 1.
+///
+/// #### bins, breaks
+///
+/// The approximate number of contour levels, defaults to 10, or the levels.
+#[macro_export]
+macro_rules! geom_density_2d {
+    ($($param: ident = $value: expr),* $(,)?) => {
+        $crate::layer::geom::density_2d::Density2d::builder()
+            $(.$param($value))*
+            .build()
+            .unwrap()
+    };
+
+    ($mapping: expr $(, $($param: ident = $value: expr),+ $(,)?)?) => {
+        geom_density_2d!(mapping = $mapping $(, $($param = $value),+)?)
+    };
+}
+
+/// # geom_density_2d_filled!: Construct a filled [`Density2d`] layer
+///
+/// This macro is used to create a [`Density2d`] layer filling the bands
+/// between the contours of the density in a more concise way like `ggplot2`.
+/// The bands are filled by their density level through the fill scale, e.g.
+/// [`scale_fill_viridis_c!`](crate::scale_fill_viridis_c!).
+///
+/// It accepts the same arguments as [`geom_density_2d!`](crate::geom_density_2d!).
+/// The contours are drawn on top of the bands if `color` is set.
+#[macro_export]
+macro_rules! geom_density_2d_filled {
+    ($($param: ident = $value: expr),* $(,)?) => {
+        geom_density_2d!(filled = true $(, $param = $value)*)
+    };
+
+    ($mapping: expr $(, $($param: ident = $value: expr),+ $(,)?)?) => {
+        geom_density_2d!(mapping = $mapping, filled = true $(, $($param = $value),+)?)
+    };
+}
+
+impl Layer for Density2d {
+    fn data_mut(&mut self) -> &mut Option<Arc<dyn crate::data::Data>> {
+        self.stat.reset();
+        &mut self.data
+    }
+
+    fn mapping_mut(&mut self) -> &mut crate::aes::Aes {
+        self.stat.reset();
+        &mut self.mapping
+    }
+
+    fn compute(&self) {
+        self.density();
+    }
+
+    fn range_2d(&self) -> Option<(f64, f64, f64, f64)> {
+        let (grid, _) = self.density();
+
+        Some((
+            grid.x[0],
+            grid.x[grid.x.len() - 1],
+            grid.y[0],
+            grid.y[grid.y.len() - 1],
+        ))
+    }
+
+    fn fill_range(&self) -> Option<(f64, f64)> {
+        if !self.filled {
+            return None;
+        }
+
+        bands_fill_range(&self.density().1)
+    }
+
+    fn fill_title(&self) -> Option<&str> {
+        self.filled.then_some("level")
+    }
+
+    #[cfg(feature = "svg")]
+    fn draw_svg_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::SVGBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        scales: &Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel, scales)
+    }
+
+    #[cfg(feature = "png")]
+    fn draw_png_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::BitMapBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        scales: &Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel, scales)
+    }
+}
@@ -0,0 +1,267 @@
+//! # Hex geom layer
+//!
+//! The hex geom divides the plane into hexagonal bins, counts the points
+//! in each bin and fills the bins by their counts. Like the [bin2d geom](super::bin2d),
+//! it is an alternative to scatter plots of large datasets, where the points
+//! overlap.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use gongbi::*;
+//! # fn main() -> anyhow::Result<()> {
+//! let p = plot!(csv = "examples/mpg.csv", mapping = aes!(displ, hwy))
+//!     + geom_hex!(bins = 10);
+//!
+//! p.to_svg("geom_hex.svg", (800, 600))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! See [`geom_hex!`](crate::geom_hex!) or [`Hex::builder`] for more details.
+
+use std::sync::Arc;
+
+use derive_builder::Builder;
+use plotters::{
+    chart::ChartContext,
+    coord::types::RangedCoordf64,
+    prelude::{Cartesian2d, DrawingBackend, PathElement, Polygon},
+    style::Color,
+};
+
+use crate::{
+    aes::color::ColorMapping,
+    coord::Panel,
+    layer::{stat, Layer},
+    scale::Scales,
+};
+
+/// # Hex layer
+///
+/// The hex geom layer is used to draw the counts of points in hexagonal bins.
+///
+/// To create a hex layer, [`geom_hex!`](crate::geom_hex!) and [`Hex::builder`] can be used.
+#[derive(Clone, Debug, Builder)]
+pub struct Hex {
+    /// The data source for the layer.
+    #[builder(default, setter(strip_option))]
+    data: Option<Arc<dyn crate::data::Data>>,
+
+    /// The aes mapping for the layer.
+    #[builder(default)]
+    mapping: crate::aes::Aes,
+
+    /// The number of bins in both directions.
+    ///
+    /// It is overridden by `binwidth`.
+    #[builder(default = "30")]
+    bins: usize,
+
+    /// The width and the height of the bins in data units.
+    ///
+    /// The width is the distance between the centers of two neighbouring
+    /// hexagons in a row, and the height is the distance between two rows.
+    #[builder(default, setter(strip_option))]
+    binwidth: Option<(f64, f64)>,
+
+    /// The hexagonal bins and their counts, computed once from the data.
+    #[builder(setter(skip))]
+    stat: stat::Cache<stat::HexBins>,
+}
+
+impl Default for Hex {
+    fn default() -> Self {
+        Hex::builder().build().unwrap()
+    }
+}
+
+impl Hex {
+    /// Create a new [`Hex`] via the builder pattern.
+    pub fn builder() -> HexBuilder {
+        HexBuilder::default()
+    }
+
+    /// Get the non-empty bins and their counts.
+    fn bins(&self) -> &stat::HexBins {
+        self.stat.get_or_init(|| {
+            let data = self.data.as_ref().expect("data is not provided");
+            let mapping = &self.mapping;
+
+            let column_x = mapping.x.as_ref().expect("x must be provided for geom_hex");
+            let column_y = mapping.y.as_ref().expect("y must be provided for geom_hex");
+
+            // A lazy frame counts the points in its query
+            #[cfg(feature = "polars")]
+            if let Some(lazy) = data.as_lazy() {
+                let positions = (column_x.expr(), column_y.expr());
+                return stat::hex_bin_lazy(lazy, positions, self.bins, self.binwidth);
+            }
+
+            let points =
+                stat::finite_pairs(&data.position_f64(column_x), &data.position_f64(column_y));
+
+            stat::hex_bin(&points, self.bins, self.binwidth)
+        })
+    }
+
+    fn draw_2d<'a, DB>(
+        &'a self,
+        chart: &mut ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+        panel: &Panel,
+        scales: &Scales,
+    ) -> anyhow::Result<()>
+    where
+        DB: DrawingBackend + 'a,
+        <DB as plotters::prelude::DrawingBackend>::ErrorType: 'static,
+    {
+        let bins = self.bins();
+
+        chart.draw_series(bins.counts.iter().map(|&(center, count)| {
+            Polygon::new(
+                panel.polygon(&bins.hexagon(center)),
+                scales.fill.map(count).filled(),
+            )
+        }))?;
+
+        if let Some(color) = self
+            .mapping
+            .color
+            .as_ref()
+            .and_then(ColorMapping::as_constant)
+        {
+            chart.draw_series(bins.counts.iter().map(|&(center, _)| {
+                let mut hexagon = bins.hexagon(center);
+                hexagon.push(hexagon[0]);
+
+                PathElement::new(panel.path(&hexagon), color)
+            }))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// # geom_hex!: Construct a [`Hex`] layer
+///
+/// This macro is used to create a [`Hex`] layer in a more concise way like `ggplot2`.
+/// It is a wrapper around [`Hex::builder`].
+///
+/// ## Usage
+///
+/// ```ignore
+/// geom_hex!(
+///     mapping = aes!(x, y),
+///     data = <Data>,
+///     bins = <usize>,
+///     binwidth = (<f64>, <f64>),
+/// )
+/// ```
+///
+/// ### Arguments
+///
+/// #### mapping
+///
+/// Set of aesthetic mappings created by [`aes!`](crate::aes!) or [`Aes::builder`](crate::aes::Aes::builder).
+///
+/// The bins are filled by their counts through the fill scale, e.g.
+/// [`scale_fill_viridis_c!`](crate::scale_fill_viridis_c!). If `color` is set,
+/// the border of the bins is drawn.
+///
+/// #### data
+///
+/// The data to be displayed in this layer.
+///
+/// If not provided, it will be inherited from the [`Plot`](crate::Plot).
+///
+/// #### bins, binwidth
+///
+/// The number of bins in both directions, defaults to 30, or the size of the
+/// bins in data units, i.e. the distance between two hexagons in a row and
+/// the distance between two rows.
+#[macro_export]
+macro_rules! geom_hex {
+    ($($param: ident = $value: expr),* $(,)?) => {
+        $crate::layer::geom::hex::Hex::builder()
+            $(.$param($value))*
+            .build()
+            .unwrap()
+    };
+
+    ($mapping: expr $(, $($param: ident = $value: expr),+ $(,)?)?) => {
+        geom_hex!(mapping = $mapping $(, $($param = $value),+)?)
+    };
+}
+
+impl Layer for Hex {
+    fn data_mut(&mut self) -> &mut Option<Arc<dyn crate::data::Data>> {
+        self.stat.reset();
+        &mut self.data
+    }
+
+    fn mapping_mut(&mut self) -> &mut crate::aes::Aes {
+        self.stat.reset();
+        &mut self.mapping
+    }
+
+    fn compute(&self) {
+        self.bins();
+    }
+
+    fn range_2d(&self) -> Option<(f64, f64, f64, f64)> {
+        let bins = self.bins();
+
+        bins.counts
+            .iter()
+            .flat_map(|&(center, _)| bins.hexagon(center))
+            .fold(None, |acc, (x, y)| {
+                let (x0, x1, y0, y1) = acc.unwrap_or((x, x, y, y));
+
+                Some((x0.min(x), x1.max(x), y0.min(y), y1.max(y)))
+            })
+    }
+
+    fn fill_range(&self) -> Option<(f64, f64)> {
+        self.bins()
+            .counts
+            .iter()
+            .map(|&(_, count)| count)
+            .fold(None, |acc, c| {
+                let (low, high) = acc.unwrap_or((c, c));
+
+                Some((low.min(c), high.max(c)))
+            })
+    }
+
+    fn fill_title(&self) -> Option<&str> {
+        Some("count")
+    }
+
+    #[cfg(feature = "svg")]
+    fn draw_svg_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::SVGBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        scales: &Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel, scales)
+    }
+
+    #[cfg(feature = "png")]
+    fn draw_png_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::BitMapBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        scales: &Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel, scales)
+    }
+}
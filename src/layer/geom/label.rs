@@ -0,0 +1,245 @@
+//! # Label geom layer
+//!
+//! The label geom draws the strings of a data column at the (x, y) positions,
+//! like the [text geom](super::text), but each string is drawn in a rounded
+//! box to make it easier to read on a busy plot.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use gongbi::*;
+//! # fn main() -> anyhow::Result<()> {
+//! let p = plot!(csv = "examples/mpg.csv", mapping = aes!(displ, hwy))
+//!     + geom_point!()
+//!     + geom_label!(aes!(label = model), repel = true);
+//!
+//! p.to_svg("geom_label.svg", (800, 600))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! See [`geom_label!`](crate::geom_label!) or [`Label::builder`] for more details.
+
+use std::{f64::consts::FRAC_PI_2, sync::Arc};
+
+use derive_builder::Builder;
+use plotters::{
+    chart::ChartContext,
+    coord::types::RangedCoordf64,
+    element::{Polygon, Text as TextElement},
+    prelude::{Cartesian2d, DrawingBackend, PathElement},
+    style::{IntoFont, BLACK, WHITE},
+};
+
+use crate::{coord::Panel, layer::Layer};
+
+use super::{
+    constant_color, range_2d_x_xy,
+    text::{place_texts, placement_setters, Placement},
+};
+
+/// The number of segments used to draw a rounded corner.
+const CORNER_SEGMENTS: usize = 4;
+
+/// # Label layer
+///
+/// The label geom layer is used to draw the strings of the `label` column in
+/// rounded boxes at the (x, y) positions.
+///
+/// To create a label layer, [`geom_label!`](crate::geom_label!) and [`Label::builder`] can be used.
+#[derive(Clone, Debug, Builder)]
+pub struct Label {
+    /// The data source for the layer.
+    #[builder(default, setter(strip_option))]
+    data: Option<Arc<dyn crate::data::Data>>,
+
+    /// The aes mapping for the layer.
+    ///
+    /// The `label` aesthetic is the column name of the strings to draw.
+    #[builder(default)]
+    mapping: crate::aes::Aes,
+
+    /// Where to draw the labels relative to their positions, and how to handle overlaps.
+    #[builder(default, setter(custom))]
+    placement: Placement,
+
+    /// The space in pixels between the text and the border of the box.
+    #[builder(default = "4")]
+    padding: i32,
+
+    /// The radius in pixels of the rounded corners.
+    #[builder(default = "4")]
+    radius: i32,
+}
+
+impl Label {
+    /// Create a new [`Label`] via the builder pattern.
+    pub fn builder() -> LabelBuilder {
+        LabelBuilder::default()
+    }
+
+    fn draw_2d<'a, DB>(
+        &'a self,
+        chart: &mut ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+        panel: &Panel,
+    ) -> anyhow::Result<()>
+    where
+        DB: DrawingBackend + 'a,
+        <DB as plotters::prelude::DrawingBackend>::ErrorType: 'static,
+    {
+        let data = self.data.as_ref().expect("data is not provided");
+        let mapping = &self.mapping;
+
+        let color = constant_color(mapping, BLACK);
+        let style = ("sans-serif", mapping.size.unwrap_or(16))
+            .into_font()
+            .color(&color);
+
+        let texts = place_texts(
+            chart,
+            panel,
+            data,
+            mapping,
+            &self.placement,
+            &style,
+            self.padding,
+        )?;
+
+        let area = chart.plotting_area().use_screen_coord();
+        for text in texts {
+            if let Some(segment) = text.segment() {
+                area.draw(&PathElement::new(segment, color))?;
+            }
+
+            let (upper_left, lower_right) = text.rect();
+            let outline = rounded_rect(upper_left, lower_right, self.radius);
+            area.draw(&Polygon::new(outline.clone(), WHITE))?;
+            area.draw(&PathElement::new(outline, color))?;
+
+            area.draw(&TextElement::new(text.text.clone(), text.pos(), &style))?;
+        }
+
+        Ok(())
+    }
+}
+
+placement_setters!(LabelBuilder);
+
+/// Get the closed outline of a rectangle with rounded corners.
+fn rounded_rect(upper_left: (i32, i32), lower_right: (i32, i32), radius: i32) -> Vec<(i32, i32)> {
+    let (x0, y0) = upper_left;
+    let (x1, y1) = lower_right;
+    let r = radius.min((x1 - x0) / 2).min((y1 - y0) / 2).max(0);
+
+    // The center and start angle of each corner, clockwise from upper left.
+    // The y-axis of the backend points downwards.
+    let corners = [
+        ((x0 + r, y0 + r), 2.0 * FRAC_PI_2),
+        ((x1 - r, y0 + r), 3.0 * FRAC_PI_2),
+        ((x1 - r, y1 - r), 0.0),
+        ((x0 + r, y1 - r), FRAC_PI_2),
+    ];
+
+    let mut points: Vec<(i32, i32)> = corners
+        .iter()
+        .flat_map(|&((cx, cy), start)| {
+            (0..=CORNER_SEGMENTS).map(move |i| {
+                let angle = start + FRAC_PI_2 * i as f64 / CORNER_SEGMENTS as f64;
+                (
+                    cx + (r as f64 * angle.cos()).round() as i32,
+                    cy + (r as f64 * angle.sin()).round() as i32,
+                )
+            })
+        })
+        .collect();
+    points.push(points[0]);
+
+    points
+}
+
+/// # geom_label!: Construct a [`Label`] layer
+///
+/// This macro is used to create a [`Label`] layer in a more concise way like `ggplot2`.
+/// It is a wrapper around [`Label::builder`].
+///
+/// ## Usage
+///
+/// ```ignore
+/// geom_label!(
+///     mapping = aes!(label = <LABEL_COLUMN>, ...),
+///     data = <Data>,
+///     [...Additional arguments]
+/// )
+/// ```
+///
+/// ### Arguments
+///
+/// Accepts the same arguments as [`geom_text!`](crate::geom_text!), and:
+///
+/// #### padding
+///
+/// The space in pixels between the text and the border of the box.
+///
+/// #### radius
+///
+/// The radius in pixels of the rounded corners.
+#[macro_export]
+macro_rules! geom_label {
+    ($($param: ident = $value: expr),* $(,)?) => {
+        $crate::layer::geom::label::Label::builder()
+            $(.$param($value))*
+            .build()
+            .unwrap()
+    };
+
+    ($mapping: expr $(, $($param: ident = $value: expr),+ $(,)?)?) => {
+        geom_label!(mapping = $mapping $(, $($param = $value),+)?)
+    };
+}
+
+impl Layer for Label {
+    fn data_mut(&mut self) -> &mut Option<Arc<dyn crate::data::Data>> {
+        &mut self.data
+    }
+
+    fn mapping_mut(&mut self) -> &mut crate::aes::Aes {
+        &mut self.mapping
+    }
+
+    fn range_2d(&self) -> Option<(f64, f64, f64, f64)> {
+        let data = self.data.as_ref().expect("data is not provided");
+
+        Some(
+            self.placement
+                .nudge_range(range_2d_x_xy(data, &self.mapping)),
+        )
+    }
+
+    #[cfg(feature = "svg")]
+    fn draw_svg_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::SVGBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        _scales: &crate::scale::Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel)
+    }
+
+    #[cfg(feature = "png")]
+    fn draw_png_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::BitMapBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        _scales: &crate::scale::Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel)
+    }
+}
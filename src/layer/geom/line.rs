@@ -6,13 +6,8 @@
 //!
 //! ```no_run
 //! # use gongbi::*;
-//! # use polars::prelude::*;
 //! # fn main() -> anyhow::Result<()> {
-//! # let mpg = CsvReadOptions::default()
-//! #     .with_has_header(true)
-//! #     .try_into_reader_with_file_path(Some("examples/mpg.csv".into()))?
-//! #     .finish()?;
-//! let p = plot!(mpg, aes!(displ, hwy))
+//! let p = plot!(csv = "examples/mpg.csv", mapping = aes!(displ, hwy))
 //!     + geom_line!();
 //!
 //! p.to_svg("geom_line.svg", (800, 600))?;
@@ -22,7 +17,7 @@
 //!
 //! See [`geom_line!`](crate::geom_line) or [`Line::builder`] for more details.
 
-use std::rc::Rc;
+use std::sync::Arc;
 
 use derive_builder::Builder;
 use plotters::{
@@ -31,11 +26,17 @@ use plotters::{
     element::DashedPathElement,
     prelude::{Cartesian2d, DrawingBackend, IntoDynElement, PathElement},
     series::{DashedLineSeries, LineSeries},
+    style::BLACK,
 };
 
-use crate::layer::Layer;
+use crate::{
+    aes::color::ColorMapping,
+    coord::Panel,
+    layer::{downsample::Downsample, stat, Layer},
+    scale::Scales,
+};
 
-use super::range_2d_x_xy;
+use super::{color_levels, constant_color, groups, Positions};
 
 /// # Line layer
 ///
@@ -46,11 +47,19 @@ use super::range_2d_x_xy;
 pub struct Line {
     /// The data source for the layer.
     #[builder(default, setter(strip_option))]
-    data: Option<Rc<dyn crate::data::Data>>,
+    data: Option<Arc<dyn crate::data::Data>>,
 
     /// The aes mapping for the layer.
     #[builder(default)]
     mapping: crate::aes::Aes,
+
+    /// The downsampling of the lines to the pixels of the output.
+    #[builder(default, setter(into, strip_option))]
+    downsample: Option<Downsample>,
+
+    /// The positions of the lines, read once from the data.
+    #[builder(setter(skip))]
+    stat: stat::Cache<Positions>,
 }
 
 impl Line {
@@ -59,9 +68,26 @@ impl Line {
         LineBuilder::default()
     }
 
+    /// Get the positions of the lines, with their range.
+    fn positions(&self) -> &Positions {
+        self.stat.get_or_init(|| {
+            let data = self.data.as_ref().expect("data is not provided");
+            let mapping = &self.mapping;
+
+            let column_x = mapping
+                .x
+                .as_ref()
+                .expect("x must be provided for geom_line");
+
+            Positions::read(data, column_x, mapping.y.as_ref())
+        })
+    }
+
     fn draw_2d<'a, DB>(
         &'a self,
         chart: &mut ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+        panel: &Panel,
+        scales: &Scales,
     ) -> anyhow::Result<()>
     where
         DB: DrawingBackend + 'a,
@@ -73,37 +99,62 @@ impl Line {
         let data = self.data.as_ref().expect("data is not provided");
         let mapping = &self.mapping;
 
-        let column_x = mapping.x.expect("x must be provided for geom_line");
-        let column_y = mapping.y;
+        let points = &self.positions().points;
 
-        let points: Vec<(f64, f64)> = match column_y {
-            Some(column_y) => data
-                .column_f64(column_x)
-                .into_iter()
-                .zip(data.column_f64(column_y))
-                .collect(),
-            None => {
-                let x = data.column_f64(column_x);
+        // The rows are grouped by the column mapped to color, one line per group
+        let column_color = mapping.color.as_ref().and_then(ColorMapping::as_column);
 
-                (0..x.len()).map(|u| u as f64).zip(x).collect()
-            }
-        };
+        for (level, rows) in groups(data, column_color, points.len()) {
+            let color = match &level {
+                Some(level) => scales.color.map(level),
+                None => constant_color(mapping, BLACK),
+            };
+            let mut points: Vec<(f64, f64)> = rows.into_iter().map(|i| points[i]).collect();
+
+            // Around a circle, the lines are straight and closed like radar charts
+            let points = if panel.wraps_x() {
+                if points.len() > 2 {
+                    points.push(points[0]);
+                }
+                points.into_iter().map(|point| panel.map(point)).collect()
+            } else {
+                panel.path(&points)
+            };
 
-        let color = mapping.color.clone().unwrap_or_default().as_rgb();
+            // Huge series are reduced to the points visible at the size of the output
+            let points: Vec<(f64, f64)> = match self.downsample {
+                Some(downsample) => {
+                    let pixels: Vec<(i32, i32)> = points
+                        .iter()
+                        .map(|point| chart.backend_coord(point))
+                        .collect();
+                    let (width, _) = chart.plotting_area().dim_in_pixel();
 
-        let anno = match mapping.shape {
-            None | Some(1) => chart.draw_series(LineSeries::new(points, color))?,
-            Some(2) => chart.draw_series(DashedLineSeries::new(points, 5, 5, color.into()))?,
+                    downsample
+                        .indices(&pixels, width)
+                        .into_iter()
+                        .map(|i| points[i])
+                        .collect()
+                }
+                None => points,
+            };
 
-            _ => todo!(),
-        };
+            let anno = match mapping.shape {
+                None | Some(1) => chart.draw_series(LineSeries::new(points, color))?,
+                Some(2) => chart.draw_series(DashedLineSeries::new(points, 5, 5, color.into()))?,
 
-        if let Some(label) = &mapping.label {
-            anno.label(label).legend(move |(x, y)| match mapping.shape {
-                None | Some(1) => PathElement::new([(x, y), (x + 20, y)], color).into_dyn(),
-                Some(2) => DashedPathElement::new([(x, y), (x + 20, y)], 5, 5, color).into_dyn(),
                 _ => todo!(),
-            });
+            };
+
+            if let (Some(label), None) = (&mapping.legend, level) {
+                anno.label(label).legend(move |(x, y)| match mapping.shape {
+                    None | Some(1) => PathElement::new([(x, y), (x + 20, y)], color).into_dyn(),
+                    Some(2) => {
+                        DashedPathElement::new([(x, y), (x + 20, y)], 5, 5, color).into_dyn()
+                    }
+                    _ => todo!(),
+                });
+            }
         }
 
         Ok(())
@@ -120,7 +171,8 @@ impl Line {
 /// ```ignore
 /// geom_line!(
 ///     mapping = aes!(...),
-///     data = <Data>
+///     data = <Data>,
+///     downsample = "minmax",
 /// )
 /// ```
 ///
@@ -130,6 +182,12 @@ impl Line {
 ///
 /// Set of aesthetic mappings created by [`aes!`](crate::aes!) or [`Aes::builder`](crate::aes::Aes::builder).
 ///
+/// If a column is mapped to `color`, a line is drawn for each of its values,
+/// colored by the color scale, e.g. [`scale_color_manual!`](crate::scale_color_manual!).
+///
+/// With [`coord_polar!`](crate::coord_polar!) mapping x to the angle, the lines
+/// are straight between the positions and closed around the circle like radar charts.
+///
 /// #### data
 ///
 /// The data to be displayed in this layer.
@@ -137,6 +195,16 @@ impl Line {
 /// If not provided, it will be inherited from the [`Plot`](crate::Plot).
 ///
 /// If provided, it will override the data from the [`Plot`](crate::Plot).
+///
+/// #### downsample
+///
+/// The downsampling of huge series to the pixels of the output, one of:
+///
+/// - `"minmax"`: keep the first, lowest, highest and last points of each pixel column,
+///   the drawn line covers the same pixels
+/// - `"lttb"`: keep two points per pixel column by Largest-Triangle-Three-Buckets
+///
+/// If not provided, all the points are drawn.
 #[macro_export]
 macro_rules! geom_line {
     ($($param: ident = $value: expr),* $(,)?) => {
@@ -152,20 +220,35 @@ macro_rules! geom_line {
 }
 
 impl Layer for Line {
-    fn data_mut(&mut self) -> &mut Option<Rc<dyn crate::data::Data>> {
+    fn data_mut(&mut self) -> &mut Option<Arc<dyn crate::data::Data>> {
+        self.stat.reset();
         &mut self.data
     }
 
     fn mapping_mut(&mut self) -> &mut crate::aes::Aes {
+        self.stat.reset();
         &mut self.mapping
     }
 
-    fn range_2d(&self) -> (f64, f64, f64, f64) {
+    fn compute(&self) {
+        self.positions();
+    }
+
+    fn range_2d(&self) -> Option<(f64, f64, f64, f64)> {
+        Some(self.positions().range)
+    }
+
+    fn resolution_2d(&self) -> Option<(f64, f64)> {
+        Some(self.positions().resolution())
+    }
+
+    fn color_levels(&self) -> Option<(&str, Vec<String>)> {
         let data = self.data.as_ref().expect("data is not provided");
 
-        range_2d_x_xy(data, &self.mapping)
+        color_levels(data, &self.mapping)
     }
 
+    #[cfg(feature = "svg")]
     fn draw_svg_2d<'a>(
         &'a self,
         chart: &mut ChartContext<
@@ -173,10 +256,13 @@ impl Layer for Line {
             plotters::prelude::SVGBackend<'a>,
             Cartesian2d<RangedCoordf64, RangedCoordf64>,
         >,
+        panel: &Panel,
+        scales: &Scales,
     ) -> anyhow::Result<()> {
-        self.draw_2d(chart)
+        self.draw_2d(chart, panel, scales)
     }
 
+    #[cfg(feature = "png")]
     fn draw_png_2d<'a>(
         &'a self,
         chart: &mut ChartContext<
@@ -184,7 +270,9 @@ impl Layer for Line {
             plotters::prelude::BitMapBackend<'a>,
             Cartesian2d<RangedCoordf64, RangedCoordf64>,
         >,
+        panel: &Panel,
+        scales: &Scales,
     ) -> anyhow::Result<()> {
-        self.draw_2d(chart)
+        self.draw_2d(chart, panel, scales)
     }
 }
@@ -7,13 +7,8 @@
 //!
 //! ```no_run
 //! # use gongbi::*;
-//! # use polars::prelude::*;
 //! # fn main() -> anyhow::Result<()> {
-//! # let mpg = CsvReadOptions::default()
-//! #     .with_has_header(true)
-//! #     .try_into_reader_with_file_path(Some("examples/mpg.csv".into()))?
-//! #     .finish()?;
-//! let p = plot!(mpg, aes!(displ, hwy))
+//! let p = plot!(csv = "examples/mpg.csv", mapping = aes!(displ, hwy))
 //!     + geom_point!();
 //!
 //! p.to_svg("geom_point.svg", (800, 600))?;
@@ -23,7 +18,7 @@
 //!
 //! See [`geom_point!`](crate::geom_point!) or [`Point::builder`] for more details.
 
-use std::rc::Rc;
+use std::sync::Arc;
 
 use derive_builder::Builder;
 use plotters::{
@@ -33,13 +28,16 @@ use plotters::{
         Cartesian2d, Circle, Cross, DrawingBackend, EmptyElement, IntoDynElement, Rectangle,
         TriangleMarker,
     },
-    series::PointSeries,
-    style::Color,
+    style::{Color, ShapeStyle, BLACK},
 };
 
-use crate::layer::Layer;
+use crate::{
+    coord::Panel,
+    layer::{downsample::dedup_pixels, stat, Layer},
+    scale::Scales,
+};
 
-use super::range_2d_x_xy;
+use super::{color_levels, colors, constant_color, fill_colors, fill_range, Positions};
 
 /// # Point layer
 ///
@@ -50,11 +48,19 @@ use super::range_2d_x_xy;
 pub struct Point {
     /// The data source for the layer.
     #[builder(default, setter(strip_option))]
-    data: Option<Rc<dyn crate::data::Data>>,
+    data: Option<Arc<dyn crate::data::Data>>,
 
     /// The aes mapping for the layer.
     #[builder(default)]
     mapping: crate::aes::Aes,
+
+    /// Whether to skip the points hidden by a later point of the same style at the same pixel.
+    #[builder(default)]
+    dedup: bool,
+
+    /// The positions of the points, read once from the data.
+    #[builder(setter(skip))]
+    stat: stat::Cache<Positions>,
 }
 
 impl Point {
@@ -63,9 +69,26 @@ impl Point {
         PointBuilder::default()
     }
 
+    /// Get the positions of the points, with their range.
+    fn positions(&self) -> &Positions {
+        self.stat.get_or_init(|| {
+            let data = self.data.as_ref().expect("data is not provided");
+            let mapping = &self.mapping;
+
+            let column_x = mapping
+                .x
+                .as_ref()
+                .expect("x must be provided for geom_point");
+
+            Positions::read(data, column_x, mapping.y.as_ref())
+        })
+    }
+
     fn draw_2d<'a, DB>(
         &'a self,
         chart: &mut ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+        panel: &Panel,
+        scales: &Scales,
     ) -> anyhow::Result<()>
     where
         DB: DrawingBackend + 'a,
@@ -77,47 +100,62 @@ impl Point {
         let data = self.data.as_ref().expect("data is not provided");
         let mapping = &self.mapping;
 
-        let column_x = mapping.x.expect("x must be provided for geom_point");
-        let column_y = mapping.y;
+        let points = &self.positions().points;
 
-        let points: Vec<(f64, f64)> = match column_y {
-            Some(column_y) => data
-                .column_f64(column_x)
-                .into_iter()
-                .zip(data.column_f64(column_y))
-                .collect(),
-            None => {
-                let x = data.column_f64(column_x);
+        let color = constant_color(mapping, BLACK);
+        let colors = colors(data, mapping, scales, points.len(), BLACK);
 
-                (0..x.len()).map(|u| u as f64).zip(x).collect()
-            }
+        // Without fill, the points are filled with their colors
+        let fills = match mapping.fill {
+            None => colors.iter().copied().map(Some).collect(),
+            Some(_) => fill_colors(data, mapping, scales, points.len(), BLACK),
         };
+        let styles: Vec<ShapeStyle> = fills
+            .into_iter()
+            .zip(colors)
+            .map(|(fill, color)| match fill {
+                Some(fill) => fill.filled(),
+                None => color.stroke_width(1),
+            })
+            .collect();
 
-        let color = mapping.color.clone().unwrap_or_default().as_rgb();
+        let points: Vec<(f64, f64)> = points.iter().map(|&point| panel.map(point)).collect();
 
-        let style = match mapping.fill {
-            Some(false) => color.stroke_width(1),
-            _ => color.filled(),
+        // The points of the same style at the same pixel are drawn identically
+        let (points, styles) = match self.dedup {
+            true => {
+                let pixels: Vec<(i32, i32)> = points
+                    .iter()
+                    .map(|point| chart.backend_coord(point))
+                    .collect();
+                let keys: Vec<_> = styles
+                    .iter()
+                    .map(|style| (style.color.rgb(), style.filled, style.stroke_width))
+                    .collect();
+
+                dedup_pixels(&pixels, &keys)
+                    .into_iter()
+                    .map(|i| (points[i], styles[i]))
+                    .unzip()
+            }
+            false => (points, styles),
         };
 
-        let anno = chart.draw_series(PointSeries::of_element(
-            points,
-            mapping.size.unwrap_or(5),
-            style,
-            &|c, s, st| {
-                EmptyElement::at(c)
-                    + match mapping.shape {
-                        Some(0) => Rectangle::new([(-s, -s), (s, s)], st).into_dyn(),
-                        None | Some(1) => Circle::new((0, 0), s, st).into_dyn(),
-                        Some(2) => TriangleMarker::new((0, 0), s, st).into_dyn(),
-                        Some(3) => Cross::new((0, 0), s, st).into_dyn(),
-
-                        _ => todo!(),
-                    }
-            },
-        ))?;
-
-        if let Some(label) = &mapping.label {
+        let s = mapping.size.unwrap_or(5);
+
+        let anno = chart.draw_series(points.into_iter().zip(styles).map(|(c, st)| {
+            EmptyElement::at(c)
+                + match mapping.shape {
+                    Some(0) => Rectangle::new([(-s, -s), (s, s)], st).into_dyn(),
+                    None | Some(1) => Circle::new((0, 0), s, st).into_dyn(),
+                    Some(2) => TriangleMarker::new((0, 0), s, st).into_dyn(),
+                    Some(3) => Cross::new((0, 0), s, st).into_dyn(),
+
+                    _ => todo!(),
+                }
+        }))?;
+
+        if let Some(label) = &mapping.legend {
             anno.label(label).legend(move |(x, y)| match mapping.shape {
                 Some(0) => Rectangle::new([(x - 5, y - 5), (x + 5, y + 5)], color).into_dyn(),
                 None | Some(1) => Circle::new((x, y), 5, color).into_dyn(),
@@ -141,7 +179,8 @@ impl Point {
 /// ```ignore
 /// geom_point!(
 ///     mapping = aes!(...),
-///     data = <Data>
+///     data = <Data>,
+///     dedup = <bool>,
 /// )
 /// ```
 ///
@@ -151,6 +190,9 @@ impl Point {
 ///
 /// Set of aesthetic mappings created by [`aes!`](crate::aes!) or [`Aes::builder`](crate::aes::Aes::builder).
 ///
+/// If a column is mapped to `color`, the points are colored by the color scale,
+/// e.g. [`scale_color_manual!`](crate::scale_color_manual!).
+///
 /// #### data
 ///
 /// The data to be displayed in this layer.
@@ -158,6 +200,12 @@ impl Point {
 /// If not provided, it will be inherited from the [`Plot`](crate::Plot).
 ///
 /// If provided, it will override the data from the [`Plot`](crate::Plot).
+///
+/// #### dedup
+///
+/// Whether to skip the points hidden by a later point of the same color at the
+/// same pixel of the output, which looks the same with far fewer points for huge data.
+/// Defaults to `false`.
 #[macro_export]
 macro_rules! geom_point {
     ($($param: ident = $value: expr),* $(,)?) => {
@@ -173,20 +221,41 @@ macro_rules! geom_point {
 }
 
 impl Layer for Point {
-    fn data_mut(&mut self) -> &mut Option<Rc<dyn crate::data::Data>> {
+    fn data_mut(&mut self) -> &mut Option<Arc<dyn crate::data::Data>> {
+        self.stat.reset();
         &mut self.data
     }
 
     fn mapping_mut(&mut self) -> &mut crate::aes::Aes {
+        self.stat.reset();
         &mut self.mapping
     }
 
-    fn range_2d(&self) -> (f64, f64, f64, f64) {
+    fn compute(&self) {
+        self.positions();
+    }
+
+    fn range_2d(&self) -> Option<(f64, f64, f64, f64)> {
+        Some(self.positions().range)
+    }
+
+    fn resolution_2d(&self) -> Option<(f64, f64)> {
+        Some(self.positions().resolution())
+    }
+
+    fn fill_range(&self) -> Option<(f64, f64)> {
+        let data = self.data.as_ref().expect("data is not provided");
+
+        fill_range(data, &self.mapping)
+    }
+
+    fn color_levels(&self) -> Option<(&str, Vec<String>)> {
         let data = self.data.as_ref().expect("data is not provided");
 
-        range_2d_x_xy(data, &self.mapping)
+        color_levels(data, &self.mapping)
     }
 
+    #[cfg(feature = "svg")]
     fn draw_svg_2d<'a>(
         &'a self,
         chart: &mut ChartContext<
@@ -194,10 +263,13 @@ impl Layer for Point {
             plotters::prelude::SVGBackend<'a>,
             Cartesian2d<RangedCoordf64, RangedCoordf64>,
         >,
+        panel: &Panel,
+        scales: &Scales,
     ) -> anyhow::Result<()> {
-        self.draw_2d(chart)
+        self.draw_2d(chart, panel, scales)
     }
 
+    #[cfg(feature = "png")]
     fn draw_png_2d<'a>(
         &'a self,
         chart: &mut ChartContext<
@@ -205,7 +277,9 @@ impl Layer for Point {
             plotters::prelude::BitMapBackend<'a>,
             Cartesian2d<RangedCoordf64, RangedCoordf64>,
         >,
+        panel: &Panel,
+        scales: &Scales,
     ) -> anyhow::Result<()> {
-        self.draw_2d(chart)
+        self.draw_2d(chart, panel, scales)
     }
 }
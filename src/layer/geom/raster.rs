@@ -0,0 +1,319 @@
+//! # Raster geom layer
+//!
+//! The raster geom is a faster [tile geom](super::tile) for regular grids.
+//! Instead of drawing a rectangle for each cell, the whole grid is drawn as a
+//! single bitmap, which keeps the output small for large grids.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use gongbi::*;
+//! # use polars::prelude::*;
+//! # fn main() -> anyhow::Result<()> {
+//! let (hour, day): (Vec<i32>, Vec<i32>) = (0..7 * 24).map(|i| (i % 24, i / 24)).unzip();
+//! let usage: Vec<f64> = hour.iter().map(|&h| (h as f64 / 24.0 * 3.14).sin()).collect();
+//!
+//! let df = df!("hour" => hour, "day" => day, "usage" => usage)?;
+//!
+//! let p = plot!(df, aes!(hour, day, fill = usage))
+//!     + geom_raster!();
+//!
+//! p.to_svg("geom_raster.svg", (800, 600))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! See [`geom_raster!`](crate::geom_raster!) or [`Raster::builder`] for more details.
+
+use std::sync::Arc;
+
+use derive_builder::Builder;
+use plotters::{
+    chart::ChartContext,
+    coord::types::RangedCoordf64,
+    element::BitMapElement,
+    prelude::{Cartesian2d, DrawingBackend},
+    style::{Color, RGBColor},
+};
+
+use crate::{coord::Panel, layer::Layer, scale::Scales};
+
+use super::{draw_rects, fill_colors, fill_range, resolution};
+
+/// # Raster layer
+///
+/// The raster geom layer is used to draw a regular grid of cells centered at
+/// the (x, y) positions as a bitmap.
+///
+/// The cells without data are drawn in white.
+///
+/// To create a raster layer, [`geom_raster!`](crate::geom_raster!) and [`Raster::builder`] can be used.
+#[derive(Clone, Debug, Default, Builder)]
+pub struct Raster {
+    /// The data source for the layer.
+    #[builder(default, setter(strip_option))]
+    data: Option<Arc<dyn crate::data::Data>>,
+
+    /// The aes mapping for the layer.
+    #[builder(default)]
+    mapping: crate::aes::Aes,
+}
+
+/// A regular grid of cells
+struct Grid {
+    /// The center of the lower left cell
+    origin: (f64, f64),
+
+    /// The size of the cells
+    cell: (f64, f64),
+
+    /// The number of columns and rows
+    dim: (usize, usize),
+}
+
+impl Grid {
+    /// Get the range of the grid, including the half cells at the edges.
+    fn range(&self) -> (f64, f64, f64, f64) {
+        (
+            self.origin.0 - self.cell.0 / 2.0,
+            self.origin.0 + (self.dim.0 as f64 - 0.5) * self.cell.0,
+            self.origin.1 - self.cell.1 / 2.0,
+            self.origin.1 + (self.dim.1 as f64 - 0.5) * self.cell.1,
+        )
+    }
+
+    /// Get the index of the cell containing the position.
+    fn index(&self, (x, y): (f64, f64)) -> Option<usize> {
+        let i = ((x - self.origin.0) / self.cell.0).round();
+        let j = ((y - self.origin.1) / self.cell.1).round();
+
+        (i >= 0.0 && j >= 0.0 && (i as usize) < self.dim.0 && (j as usize) < self.dim.1)
+            .then(|| j as usize * self.dim.0 + i as usize)
+    }
+}
+
+impl Raster {
+    /// Create a new [`Raster`] via the builder pattern.
+    pub fn builder() -> RasterBuilder {
+        RasterBuilder::default()
+    }
+
+    /// Get the positions of the data and the grid covering them.
+    fn grid(&self) -> (Vec<(f64, f64)>, Grid) {
+        let data = self.data.as_ref().expect("data is not provided");
+        let mapping = &self.mapping;
+
+        let column_x = mapping
+            .x
+            .as_ref()
+            .expect("x must be provided for geom_raster");
+        let column_y = mapping
+            .y
+            .as_ref()
+            .expect("y must be provided for geom_raster");
+
+        let x = data.position_f64(column_x);
+        let y = data.position_f64(column_y);
+
+        let (x_min, x_max) = data.position_range_f64(column_x);
+        let (y_min, y_max) = data.position_range_f64(column_y);
+        let cell = (resolution(&x), resolution(&y));
+
+        let grid = Grid {
+            origin: (x_min, y_min),
+            cell,
+            dim: (
+                ((x_max - x_min) / cell.0).round() as usize + 1,
+                ((y_max - y_min) / cell.1).round() as usize + 1,
+            ),
+        };
+
+        (x.iter().copied().zip(y.iter().copied()).collect(), grid)
+    }
+
+    fn draw_2d<'a, DB>(
+        &'a self,
+        chart: &mut ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+        panel: &Panel,
+        scales: &Scales,
+    ) -> anyhow::Result<()>
+    where
+        DB: DrawingBackend + 'a,
+        <DB as plotters::prelude::DrawingBackend>::ErrorType: 'static,
+    {
+        let data = self.data.as_ref().expect("data is not provided");
+
+        let (positions, grid) = self.grid();
+        let fills = fill_colors(
+            data,
+            &self.mapping,
+            scales,
+            positions.len(),
+            RGBColor(89, 89, 89),
+        );
+
+        // The cells are only pixels in linear coordinates, e.g. not the sectors of polar coordinates,
+        // and the rows of the bitmap are along the x axis
+        if !panel.is_linear() || panel.is_flipped() {
+            let (w, h) = grid.cell;
+
+            return draw_rects(
+                chart,
+                panel,
+                positions
+                    .into_iter()
+                    .zip(fills)
+                    .filter_map(|((x, y), fill)| {
+                        let rect = [(x - w / 2.0, y - h / 2.0), (x + w / 2.0, y + h / 2.0)];
+
+                        Some((rect, fill?.filled()))
+                    }),
+            );
+        }
+
+        let mut cells = vec![None; grid.dim.0 * grid.dim.1];
+        for (pos, fill) in positions.into_iter().zip(fills) {
+            if let Some(index) = grid.index(pos) {
+                cells[index] = fill;
+            }
+        }
+
+        // The pixels of the grid, clipped to the plotting area
+        let (x0, x1, y0, y1) = grid.range();
+        let upper_left = chart.backend_coord(&(x0, y1));
+        let lower_right = chart.backend_coord(&(x1, y0));
+        let (x_pixels, y_pixels) = chart.plotting_area().get_pixel_range();
+
+        let (px0, px1) = (
+            upper_left.0.max(x_pixels.start),
+            lower_right.0.min(x_pixels.end),
+        );
+        let (py0, py1) = (
+            upper_left.1.max(y_pixels.start),
+            lower_right.1.min(y_pixels.end),
+        );
+        if px0 >= px1 || py0 >= py1 {
+            return Ok(());
+        }
+
+        let (grid_w, grid_h) = (
+            (lower_right.0 - upper_left.0).max(1) as f64,
+            (lower_right.1 - upper_left.1).max(1) as f64,
+        );
+        let (w, h) = ((px1 - px0) as u32, (py1 - py0) as u32);
+
+        let mut buffer = Vec::with_capacity((w * h * 3) as usize);
+        for py in py0..py1 {
+            // The rows of the bitmap are from top to bottom
+            let v = (py - upper_left.1) as f64 + 0.5;
+            let j =
+                grid.dim.1 - 1 - ((v / grid_h * grid.dim.1 as f64) as usize).min(grid.dim.1 - 1);
+
+            for px in px0..px1 {
+                let u = (px - upper_left.0) as f64 + 0.5;
+                let i = ((u / grid_w * grid.dim.0 as f64) as usize).min(grid.dim.0 - 1);
+
+                let RGBColor(r, g, b) =
+                    cells[j * grid.dim.0 + i].unwrap_or(RGBColor(255, 255, 255));
+                buffer.extend([r, g, b]);
+            }
+        }
+
+        let bitmap = BitMapElement::with_owned_buffer((px0, py0), (w, h), buffer)
+            .expect("the size of the buffer matches the bitmap");
+        chart.plotting_area().use_screen_coord().draw(&bitmap)?;
+
+        Ok(())
+    }
+}
+
+/// # geom_raster!: Construct a [`Raster`] layer
+///
+/// This macro is used to create a [`Raster`] layer in a more concise way like `ggplot2`.
+/// It is a wrapper around [`Raster::builder`].
+///
+/// ## Usage
+///
+/// ```ignore
+/// geom_raster!(
+///     mapping = aes!(x, y, fill = <FILL_COLUMN>),
+///     data = <Data>,
+/// )
+/// ```
+///
+/// ### Arguments
+///
+/// #### mapping
+///
+/// Set of aesthetic mappings created by [`aes!`](crate::aes!) or [`Aes::builder`](crate::aes::Aes::builder).
+///
+/// `x` and `y` are the centers of the cells, and should form a regular grid.
+/// `fill` is usually mapped to a column, and the values are mapped to colors
+/// by the fill scale, e.g. [`scale_fill_gradient!`](crate::scale_fill_gradient!).
+///
+/// #### data
+///
+/// The data to be displayed in this layer.
+///
+/// If not provided, it will be inherited from the [`Plot`](crate::Plot).
+#[macro_export]
+macro_rules! geom_raster {
+    ($($param: ident = $value: expr),* $(,)?) => {
+        $crate::layer::geom::raster::Raster::builder()
+            $(.$param($value))*
+            .build()
+            .unwrap()
+    };
+
+    ($mapping: expr $(, $($param: ident = $value: expr),+ $(,)?)?) => {
+        geom_raster!(mapping = $mapping $(, $($param = $value),+)?)
+    };
+}
+
+impl Layer for Raster {
+    fn data_mut(&mut self) -> &mut Option<Arc<dyn crate::data::Data>> {
+        &mut self.data
+    }
+
+    fn mapping_mut(&mut self) -> &mut crate::aes::Aes {
+        &mut self.mapping
+    }
+
+    fn range_2d(&self) -> Option<(f64, f64, f64, f64)> {
+        Some(self.grid().1.range())
+    }
+
+    fn fill_range(&self) -> Option<(f64, f64)> {
+        let data = self.data.as_ref().expect("data is not provided");
+
+        fill_range(data, &self.mapping)
+    }
+
+    #[cfg(feature = "svg")]
+    fn draw_svg_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::SVGBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        scales: &Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel, scales)
+    }
+
+    #[cfg(feature = "png")]
+    fn draw_png_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::BitMapBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        scales: &Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel, scales)
+    }
+}
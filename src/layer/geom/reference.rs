@@ -0,0 +1,593 @@
+//! # Reference line geom layers
+//!
+//! The reference line geoms draw horizontal, vertical and diagonal lines
+//! across the whole plot. They are used to mark thresholds, dates and other
+//! references, and do not affect the range of the axes by default.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use gongbi::*;
+//! # fn main() -> anyhow::Result<()> {
+//! let p = plot!(csv = "examples/mpg.csv", mapping = aes!(cty, hwy))
+//!     + geom_point!()
+//!     + geom_hline!(aes!(color = "red"), yintercept = 30)
+//!     + geom_vline!(xintercept = vec![15.0, 25.0])
+//!     + geom_abline!(aes!(shape = 2), slope = 1.4, intercept = 0.0);
+//!
+//! p.to_svg("geom_hline.svg", (800, 600))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! See [`geom_hline!`](crate::geom_hline!), [`geom_vline!`](crate::geom_vline!)
+//! and [`geom_abline!`](crate::geom_abline!) for more details.
+
+use std::{ops::Range, sync::Arc};
+
+use derive_builder::Builder;
+use plotters::{
+    chart::ChartContext,
+    coord::types::RangedCoordf64,
+    element::DashedPathElement,
+    prelude::{Cartesian2d, DrawingBackend, IntoDynElement, PathElement},
+    style::{Color, RGBColor, ShapeStyle, BLACK},
+};
+
+use crate::{aes::Aes, coord::Panel, layer::Layer};
+
+use super::{constant_color, Values};
+
+/// Get the stroke style and the dash pattern of the reference lines.
+fn line_style(mapping: &Aes) -> (RGBColor, ShapeStyle) {
+    let color = constant_color(mapping, BLACK);
+    let style = color.stroke_width(mapping.size.unwrap_or(1).max(1) as u32);
+
+    (color, style)
+}
+
+/// Draw the segments as reference lines.
+fn draw_segments<'a, DB>(
+    chart: &mut ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+    panel: &Panel,
+    mapping: &'a Aes,
+    segments: Vec<[(f64, f64); 2]>,
+) -> anyhow::Result<()>
+where
+    DB: DrawingBackend + 'a,
+    <DB as plotters::prelude::DrawingBackend>::ErrorType: 'static,
+{
+    let (color, style) = line_style(mapping);
+
+    // The lines are solid unless dashed, also for the shapes without a line type
+    let anno = match mapping.shape {
+        Some(2) => chart.draw_series(
+            segments
+                .into_iter()
+                .map(|segment| DashedPathElement::new(panel.path(&segment), 5, 5, style)),
+        )?,
+        _ => chart.draw_series(
+            segments
+                .into_iter()
+                .map(|segment| PathElement::new(panel.path(&segment), style)),
+        )?,
+    };
+
+    if let Some(label) = &mapping.legend {
+        anno.label(label).legend(move |(x, y)| match mapping.shape {
+            Some(2) => DashedPathElement::new([(x, y), (x + 20, y)], 5, 5, color).into_dyn(),
+            _ => PathElement::new([(x, y), (x + 20, y)], color).into_dyn(),
+        });
+    }
+
+    Ok(())
+}
+
+/// # Horizontal reference line layer
+///
+/// To create a horizontal line layer, [`geom_hline!`](crate::geom_hline!) and [`HLine::builder`] can be used.
+#[derive(Clone, Debug, Builder)]
+pub struct HLine {
+    /// The data source for the layer.
+    ///
+    /// Only used when `yintercept` is a column.
+    #[builder(default, setter(strip_option))]
+    data: Option<Arc<dyn crate::data::Data>>,
+
+    /// The aes mapping for the layer.
+    #[builder(default)]
+    mapping: crate::aes::Aes,
+
+    /// The y positions of the lines.
+    #[builder(setter(into))]
+    yintercept: Values,
+
+    /// Whether the y-axis is expanded to include the lines.
+    #[builder(default)]
+    expand: bool,
+}
+
+impl HLine {
+    /// Create a new [`HLine`] via the builder pattern.
+    pub fn builder() -> HLineBuilder {
+        HLineBuilder::default()
+    }
+
+    fn draw_2d<'a, DB>(
+        &'a self,
+        chart: &mut ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+        panel: &Panel,
+    ) -> anyhow::Result<()>
+    where
+        DB: DrawingBackend + 'a,
+        <DB as plotters::prelude::DrawingBackend>::ErrorType: 'static,
+    {
+        let x_range = panel.x_range();
+
+        let segments = self
+            .yintercept
+            .resolve(self.data.as_ref())
+            .into_iter()
+            .map(|y| [(x_range.start, y), (x_range.end, y)])
+            .collect();
+
+        draw_segments(chart, panel, &self.mapping, segments)
+    }
+}
+
+/// # Vertical reference line layer
+///
+/// To create a vertical line layer, [`geom_vline!`](crate::geom_vline!) and [`VLine::builder`] can be used.
+#[derive(Clone, Debug, Builder)]
+pub struct VLine {
+    /// The data source for the layer.
+    ///
+    /// Only used when `xintercept` is a column.
+    #[builder(default, setter(strip_option))]
+    data: Option<Arc<dyn crate::data::Data>>,
+
+    /// The aes mapping for the layer.
+    #[builder(default)]
+    mapping: crate::aes::Aes,
+
+    /// The x positions of the lines.
+    #[builder(setter(into))]
+    xintercept: Values,
+
+    /// Whether the x-axis is expanded to include the lines.
+    #[builder(default)]
+    expand: bool,
+}
+
+impl VLine {
+    /// Create a new [`VLine`] via the builder pattern.
+    pub fn builder() -> VLineBuilder {
+        VLineBuilder::default()
+    }
+
+    fn draw_2d<'a, DB>(
+        &'a self,
+        chart: &mut ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+        panel: &Panel,
+    ) -> anyhow::Result<()>
+    where
+        DB: DrawingBackend + 'a,
+        <DB as plotters::prelude::DrawingBackend>::ErrorType: 'static,
+    {
+        let y_range = panel.y_range();
+
+        let segments = self
+            .xintercept
+            .resolve(self.data.as_ref())
+            .into_iter()
+            .map(|x| [(x, y_range.start), (x, y_range.end)])
+            .collect();
+
+        draw_segments(chart, panel, &self.mapping, segments)
+    }
+}
+
+/// # Diagonal reference line layer
+///
+/// The lines are defined by `y = intercept + slope * x`.
+///
+/// To create a diagonal line layer, [`geom_abline!`](crate::geom_abline!) and [`ABLine::builder`] can be used.
+#[derive(Clone, Debug, Builder)]
+pub struct ABLine {
+    /// The data source for the layer.
+    ///
+    /// Only used when `slope` or `intercept` is a column.
+    #[builder(default, setter(strip_option))]
+    data: Option<Arc<dyn crate::data::Data>>,
+
+    /// The aes mapping for the layer.
+    #[builder(default)]
+    mapping: crate::aes::Aes,
+
+    /// The slopes of the lines.
+    #[builder(default = "Values::Constant(vec![1.0])", setter(into))]
+    slope: Values,
+
+    /// The y-intercepts of the lines.
+    #[builder(default = "Values::Constant(vec![0.0])", setter(into))]
+    intercept: Values,
+}
+
+impl ABLine {
+    /// Create a new [`ABLine`] via the builder pattern.
+    pub fn builder() -> ABLineBuilder {
+        ABLineBuilder::default()
+    }
+
+    fn draw_2d<'a, DB>(
+        &'a self,
+        chart: &mut ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+        panel: &Panel,
+    ) -> anyhow::Result<()>
+    where
+        DB: DrawingBackend + 'a,
+        <DB as plotters::prelude::DrawingBackend>::ErrorType: 'static,
+    {
+        let (x_range, y_range) = (panel.x_range(), panel.y_range());
+
+        let slope = self.slope.resolve(self.data.as_ref());
+        let intercept = self.intercept.resolve(self.data.as_ref());
+
+        // A single slope or intercept is shared by all lines, and there are no lines without either
+        let n = match slope.is_empty() || intercept.is_empty() {
+            true => 0,
+            false => slope.len().max(intercept.len()),
+        };
+        let segments = (0..n)
+            .filter_map(|i| {
+                let slope = slope[i.min(slope.len() - 1)];
+                let intercept = intercept[i.min(intercept.len() - 1)];
+
+                clip_abline(slope, intercept, &x_range, &y_range)
+            })
+            .collect();
+
+        draw_segments(chart, panel, &self.mapping, segments)
+    }
+}
+
+/// Clip the line `y = intercept + slope * x` to the given ranges.
+///
+/// Returns `None` if the line does not cross the area.
+fn clip_abline(
+    slope: f64,
+    intercept: f64,
+    x_range: &Range<f64>,
+    y_range: &Range<f64>,
+) -> Option<[(f64, f64); 2]> {
+    let (mut x0, mut x1) = (x_range.start, x_range.end);
+
+    if slope != 0.0 {
+        let xa = (y_range.start - intercept) / slope;
+        let xb = (y_range.end - intercept) / slope;
+        x0 = x0.max(xa.min(xb));
+        x1 = x1.min(xa.max(xb));
+    } else if !(y_range.start..=y_range.end).contains(&intercept) {
+        return None;
+    }
+
+    (x0 <= x1).then_some([(x0, intercept + slope * x0), (x1, intercept + slope * x1)])
+}
+
+/// # geom_hline!: Construct a [`HLine`] layer
+///
+/// This macro is used to create a [`HLine`] layer in a more concise way like `ggplot2`.
+/// It is a wrapper around [`HLine::builder`].
+///
+/// ## Usage
+///
+/// ```ignore
+/// geom_hline!(
+///     mapping = aes!(...),
+///     yintercept = <Values>,
+///     data = <Data>,
+///     expand = <bool>,
+/// )
+/// ```
+///
+/// ### Arguments
+///
+/// #### yintercept
+///
+/// The y positions of the lines, either constants (`30`, `2.5` or `vec![1.0, 2.0]`)
+/// or a column name of the data (`"threshold"`).
+///
+/// #### mapping
+///
+/// Set of aesthetic mappings created by [`aes!`](crate::aes!) or [`Aes::builder`](crate::aes::Aes::builder).
+/// `color`, `size` (line width), `shape` (line type) and `legend` are supported.
+///
+/// #### data
+///
+/// The data to read the column from. If not provided, it will be inherited from the [`Plot`](crate::Plot).
+///
+/// #### expand
+///
+/// Whether the y-axis is expanded to include the lines. Defaults to `false`.
+#[macro_export]
+macro_rules! geom_hline {
+    ($($param: ident = $value: expr),* $(,)?) => {
+        $crate::layer::geom::reference::HLine::builder()
+            $(.$param($value))*
+            .build()
+            .unwrap()
+    };
+
+    ($mapping: expr $(, $($param: ident = $value: expr),+ $(,)?)?) => {
+        geom_hline!(mapping = $mapping $(, $($param = $value),+)?)
+    };
+}
+
+/// # geom_vline!: Construct a [`VLine`] layer
+///
+/// This macro is used to create a [`VLine`] layer in a more concise way like `ggplot2`.
+/// It is a wrapper around [`VLine::builder`].
+///
+/// ## Usage
+///
+/// ```ignore
+/// geom_vline!(
+///     mapping = aes!(...),
+///     xintercept = <Values>,
+///     data = <Data>,
+///     expand = <bool>,
+/// )
+/// ```
+///
+/// ### Arguments
+///
+/// The same as [`geom_hline!`](crate::geom_hline!), with `xintercept` for the x positions of the lines.
+#[macro_export]
+macro_rules! geom_vline {
+    ($($param: ident = $value: expr),* $(,)?) => {
+        $crate::layer::geom::reference::VLine::builder()
+            $(.$param($value))*
+            .build()
+            .unwrap()
+    };
+
+    ($mapping: expr $(, $($param: ident = $value: expr),+ $(,)?)?) => {
+        geom_vline!(mapping = $mapping $(, $($param = $value),+)?)
+    };
+}
+
+/// # geom_abline!: Construct a [`ABLine`] layer
+///
+/// This macro is used to create a [`ABLine`] layer in a more concise way like `ggplot2`.
+/// It is a wrapper around [`ABLine::builder`].
+///
+/// ## Usage
+///
+/// ```ignore
+/// geom_abline!(
+///     mapping = aes!(...),
+///     slope = <Values>,
+///     intercept = <Values>,
+///     data = <Data>,
+/// )
+/// ```
+///
+/// ### Arguments
+///
+/// #### slope, intercept
+///
+/// The lines are `y = intercept + slope * x`, defaulting to `slope = 1` and `intercept = 0`.
+/// Both accept constants or a column name of the data, like `yintercept` in [`geom_hline!`](crate::geom_hline!).
+///
+/// The diagonal lines never affect the range of the axes.
+#[macro_export]
+macro_rules! geom_abline {
+    ($($param: ident = $value: expr),* $(,)?) => {
+        $crate::layer::geom::reference::ABLine::builder()
+            $(.$param($value))*
+            .build()
+            .unwrap()
+    };
+
+    ($mapping: expr $(, $($param: ident = $value: expr),+ $(,)?)?) => {
+        geom_abline!(mapping = $mapping $(, $($param = $value),+)?)
+    };
+}
+
+impl Layer for HLine {
+    fn requires_data(&self) -> bool {
+        self.yintercept.is_column()
+    }
+
+    fn columns(&self) -> Vec<String> {
+        [&self.yintercept]
+            .into_iter()
+            .filter_map(Values::as_column)
+            .map(String::from)
+            .collect()
+    }
+
+    fn data_mut(&mut self) -> &mut Option<Arc<dyn crate::data::Data>> {
+        &mut self.data
+    }
+
+    fn mapping_mut(&mut self) -> &mut crate::aes::Aes {
+        &mut self.mapping
+    }
+
+    fn range_2d(&self) -> Option<(f64, f64, f64, f64)> {
+        if !self.expand {
+            return None;
+        }
+
+        let y = self.yintercept.resolve(self.data.as_ref());
+        let y_min = y.iter().copied().fold(f64::INFINITY, f64::min);
+        let y_max = y.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+        Some((f64::INFINITY, f64::NEG_INFINITY, y_min, y_max))
+    }
+
+    #[cfg(feature = "svg")]
+    fn draw_svg_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::SVGBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        _scales: &crate::scale::Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel)
+    }
+
+    #[cfg(feature = "png")]
+    fn draw_png_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::BitMapBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        _scales: &crate::scale::Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel)
+    }
+}
+
+impl Layer for VLine {
+    fn requires_data(&self) -> bool {
+        self.xintercept.is_column()
+    }
+
+    fn columns(&self) -> Vec<String> {
+        [&self.xintercept]
+            .into_iter()
+            .filter_map(Values::as_column)
+            .map(String::from)
+            .collect()
+    }
+
+    fn data_mut(&mut self) -> &mut Option<Arc<dyn crate::data::Data>> {
+        &mut self.data
+    }
+
+    fn mapping_mut(&mut self) -> &mut crate::aes::Aes {
+        &mut self.mapping
+    }
+
+    fn range_2d(&self) -> Option<(f64, f64, f64, f64)> {
+        if !self.expand {
+            return None;
+        }
+
+        let x = self.xintercept.resolve(self.data.as_ref());
+        let x_min = x.iter().copied().fold(f64::INFINITY, f64::min);
+        let x_max = x.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+        Some((x_min, x_max, f64::INFINITY, f64::NEG_INFINITY))
+    }
+
+    #[cfg(feature = "svg")]
+    fn draw_svg_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::SVGBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        _scales: &crate::scale::Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel)
+    }
+
+    #[cfg(feature = "png")]
+    fn draw_png_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::BitMapBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        _scales: &crate::scale::Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel)
+    }
+}
+
+impl Layer for ABLine {
+    fn requires_data(&self) -> bool {
+        self.slope.is_column() || self.intercept.is_column()
+    }
+
+    fn columns(&self) -> Vec<String> {
+        [&self.slope, &self.intercept]
+            .into_iter()
+            .filter_map(Values::as_column)
+            .map(String::from)
+            .collect()
+    }
+
+    fn data_mut(&mut self) -> &mut Option<Arc<dyn crate::data::Data>> {
+        &mut self.data
+    }
+
+    fn mapping_mut(&mut self) -> &mut crate::aes::Aes {
+        &mut self.mapping
+    }
+
+    fn range_2d(&self) -> Option<(f64, f64, f64, f64)> {
+        None
+    }
+
+    #[cfg(feature = "svg")]
+    fn draw_svg_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::SVGBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        _scales: &crate::scale::Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel)
+    }
+
+    #[cfg(feature = "png")]
+    fn draw_png_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::BitMapBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        _scales: &crate::scale::Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clip_abline_to_area() {
+        let segment = clip_abline(1.0, 0.0, &(0.0..10.0), &(2.0..5.0)).unwrap();
+        assert_eq!(segment, [(2.0, 2.0), (5.0, 5.0)]);
+
+        let segment = clip_abline(-1.0, 10.0, &(0.0..10.0), &(0.0..20.0)).unwrap();
+        assert_eq!(segment, [(0.0, 10.0), (10.0, 0.0)]);
+
+        let segment = clip_abline(0.0, 3.0, &(0.0..10.0), &(0.0..5.0)).unwrap();
+        assert_eq!(segment, [(0.0, 3.0), (10.0, 3.0)]);
+
+        assert!(clip_abline(0.0, 6.0, &(0.0..10.0), &(0.0..5.0)).is_none());
+        assert!(clip_abline(1.0, 20.0, &(0.0..10.0), &(0.0..5.0)).is_none());
+    }
+}
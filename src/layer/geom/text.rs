@@ -0,0 +1,607 @@
+//! # Text geom layer
+//!
+//! The text geom draws the strings of a data column at the (x, y) positions.
+//! It is used to annotate points with their names or values.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use gongbi::*;
+//! # fn main() -> anyhow::Result<()> {
+//! let p = plot!(csv = "examples/mpg.csv", mapping = aes!(displ, hwy))
+//!     + geom_point!()
+//!     + geom_text!(aes!(label = model), vjust = 0.0, check_overlap = true);
+//!
+//! p.to_svg("geom_text.svg", (800, 600))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! See [`geom_text!`](crate::geom_text!) or [`Text::builder`] for more details.
+
+use std::{collections::HashMap, sync::Arc};
+
+use derive_builder::Builder;
+use plotters::{
+    chart::ChartContext,
+    coord::types::RangedCoordf64,
+    element::Text as TextElement,
+    prelude::{Cartesian2d, DrawingBackend, PathElement},
+    style::{IntoFont, TextStyle, BLACK},
+};
+
+use crate::{aes::Aes, coord::Panel, data::Data, layer::Layer};
+
+use super::{constant_color, range_2d_x_xy};
+
+/// The number of iterations used to push overlapping texts away.
+const REPEL_ITERATIONS: usize = 200;
+
+/// # Text layer
+///
+/// The text geom layer is used to draw the strings of the `label` column at
+/// the (x, y) positions.
+///
+/// To create a text layer, [`geom_text!`](crate::geom_text!) and [`Text::builder`] can be used.
+#[derive(Clone, Debug, Builder)]
+pub struct Text {
+    /// The data source for the layer.
+    #[builder(default, setter(strip_option))]
+    data: Option<Arc<dyn crate::data::Data>>,
+
+    /// The aes mapping for the layer.
+    ///
+    /// The `label` aesthetic is the column name of the strings to draw.
+    #[builder(default)]
+    mapping: crate::aes::Aes,
+
+    /// Where to draw the texts relative to their positions, and how to handle overlaps.
+    #[builder(default, setter(custom))]
+    placement: Placement,
+}
+
+impl Text {
+    /// Create a new [`Text`] via the builder pattern.
+    pub fn builder() -> TextBuilder {
+        TextBuilder::default()
+    }
+
+    fn draw_2d<'a, DB>(
+        &'a self,
+        chart: &mut ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+        panel: &Panel,
+    ) -> anyhow::Result<()>
+    where
+        DB: DrawingBackend + 'a,
+        <DB as plotters::prelude::DrawingBackend>::ErrorType: 'static,
+    {
+        let data = self.data.as_ref().expect("data is not provided");
+        let mapping = &self.mapping;
+
+        let color = constant_color(mapping, BLACK);
+        let style = ("sans-serif", mapping.size.unwrap_or(16))
+            .into_font()
+            .color(&color);
+
+        let texts = place_texts(chart, panel, data, mapping, &self.placement, &style, 0)?;
+
+        let area = chart.plotting_area().use_screen_coord();
+        for text in texts {
+            if let Some(segment) = text.segment() {
+                area.draw(&PathElement::new(segment, color))?;
+            }
+            area.draw(&TextElement::new(text.text.clone(), text.pos(), &style))?;
+        }
+
+        Ok(())
+    }
+}
+
+placement_setters!(TextBuilder);
+
+/// # geom_text!: Construct a [`Text`] layer
+///
+/// This macro is used to create a [`Text`] layer in a more concise way like `ggplot2`.
+/// It is a wrapper around [`Text::builder`].
+///
+/// ## Usage
+///
+/// ```ignore
+/// geom_text!(
+///     mapping = aes!(label = <LABEL_COLUMN>, ...),
+///     data = <Data>,
+///     [...Additional arguments]
+/// )
+/// ```
+///
+/// ### Arguments
+///
+/// #### mapping
+///
+/// Set of aesthetic mappings created by [`aes!`](crate::aes!) or [`Aes::builder`](crate::aes::Aes::builder).
+///
+/// The `label` aesthetic is required, and is the column of strings to draw.
+///
+/// #### data
+///
+/// The data to be displayed in this layer.
+///
+/// If not provided, it will be inherited from the [`Plot`](crate::Plot).
+///
+/// #### hjust, vjust
+///
+/// The justification of the text relative to its position, from `0.0` to `1.0`.
+///
+/// #### nudge_x, nudge_y
+///
+/// The offset of the text in data units.
+///
+/// #### check_overlap, repel
+///
+/// How to handle overlapping texts. `check_overlap = true` skips the overlapping
+/// text, while `repel = true` pushes them away from each other.
+#[macro_export]
+macro_rules! geom_text {
+    ($($param: ident = $value: expr),* $(,)?) => {
+        $crate::layer::geom::text::Text::builder()
+            $(.$param($value))*
+            .build()
+            .unwrap()
+    };
+
+    ($mapping: expr $(, $($param: ident = $value: expr),+ $(,)?)?) => {
+        geom_text!(mapping = $mapping $(, $($param = $value),+)?)
+    };
+}
+
+impl Layer for Text {
+    fn data_mut(&mut self) -> &mut Option<Arc<dyn crate::data::Data>> {
+        &mut self.data
+    }
+
+    fn mapping_mut(&mut self) -> &mut crate::aes::Aes {
+        &mut self.mapping
+    }
+
+    fn range_2d(&self) -> Option<(f64, f64, f64, f64)> {
+        let data = self.data.as_ref().expect("data is not provided");
+
+        Some(
+            self.placement
+                .nudge_range(range_2d_x_xy(data, &self.mapping)),
+        )
+    }
+
+    #[cfg(feature = "svg")]
+    fn draw_svg_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::SVGBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        _scales: &crate::scale::Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel)
+    }
+
+    #[cfg(feature = "png")]
+    fn draw_png_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::BitMapBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        _scales: &crate::scale::Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel)
+    }
+}
+
+/// The placement options shared by the text-like layers.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Placement {
+    /// The horizontal justification of the text.
+    ///
+    /// `0.0` is left-aligned, `0.5` is centered, and `1.0` is right-aligned.
+    pub hjust: f64,
+
+    /// The vertical justification of the text.
+    ///
+    /// `0.0` is bottom-aligned, `0.5` is centered, and `1.0` is top-aligned.
+    pub vjust: f64,
+
+    /// The horizontal offset of the text in data units.
+    pub nudge_x: f64,
+
+    /// The vertical offset of the text in data units.
+    pub nudge_y: f64,
+
+    /// Whether to skip the text overlapping with the text drawn before.
+    pub check_overlap: bool,
+
+    /// Whether to push overlapping texts away from each other.
+    ///
+    /// A segment is drawn from the data point to the text if it is moved.
+    pub repel: bool,
+}
+
+impl Default for Placement {
+    fn default() -> Self {
+        Self {
+            hjust: 0.5,
+            vjust: 0.5,
+            nudge_x: 0.0,
+            nudge_y: 0.0,
+            check_overlap: false,
+            repel: false,
+        }
+    }
+}
+
+/// Implement the setters of the [`Placement`] options for the builder of a text-like layer.
+macro_rules! placement_setters {
+    ($builder: ident) => {
+        impl $builder {
+            /// Set the horizontal justification of the text.
+            ///
+            /// `0.0` is left-aligned, `0.5` is centered, and `1.0` is right-aligned.
+            pub fn hjust(&mut self, hjust: f64) -> &mut Self {
+                self.placement.get_or_insert_with(Default::default).hjust = hjust;
+                self
+            }
+
+            /// Set the vertical justification of the text.
+            ///
+            /// `0.0` is bottom-aligned, `0.5` is centered, and `1.0` is top-aligned.
+            pub fn vjust(&mut self, vjust: f64) -> &mut Self {
+                self.placement.get_or_insert_with(Default::default).vjust = vjust;
+                self
+            }
+
+            /// Set the horizontal offset of the text in data units.
+            pub fn nudge_x(&mut self, nudge_x: f64) -> &mut Self {
+                self.placement.get_or_insert_with(Default::default).nudge_x = nudge_x;
+                self
+            }
+
+            /// Set the vertical offset of the text in data units.
+            pub fn nudge_y(&mut self, nudge_y: f64) -> &mut Self {
+                self.placement.get_or_insert_with(Default::default).nudge_y = nudge_y;
+                self
+            }
+
+            /// Set whether to skip the text overlapping with the text drawn before.
+            pub fn check_overlap(&mut self, check_overlap: bool) -> &mut Self {
+                self.placement
+                    .get_or_insert_with(Default::default)
+                    .check_overlap = check_overlap;
+                self
+            }
+
+            /// Set whether to push overlapping texts away from each other.
+            ///
+            /// A segment is drawn from the data point to the text if it is moved.
+            pub fn repel(&mut self, repel: bool) -> &mut Self {
+                self.placement.get_or_insert_with(Default::default).repel = repel;
+                self
+            }
+        }
+    };
+}
+
+pub(crate) use placement_setters;
+
+impl Placement {
+    /// Shift the range of the layer by the nudges.
+    pub fn nudge_range(&self, range: (f64, f64, f64, f64)) -> (f64, f64, f64, f64) {
+        (
+            range.0 + self.nudge_x.min(0.0),
+            range.1 + self.nudge_x.max(0.0),
+            range.2 + self.nudge_y.min(0.0),
+            range.3 + self.nudge_y.max(0.0),
+        )
+    }
+}
+
+/// A text box placed in the backend coordinate.
+#[derive(Clone, Debug)]
+pub(crate) struct PlacedText {
+    /// The text to draw.
+    pub text: String,
+
+    /// The position of the data point.
+    pub anchor: (f64, f64),
+
+    /// The upper left corner of the box.
+    pub corner: (f64, f64),
+
+    /// The width and height of the box.
+    pub size: (f64, f64),
+
+    /// The padding between the box and the text.
+    pub padding: f64,
+}
+
+impl PlacedText {
+    /// The upper left corner of the text inside the box.
+    pub fn pos(&self) -> (i32, i32) {
+        (
+            (self.corner.0 + self.padding).round() as i32,
+            (self.corner.1 + self.padding).round() as i32,
+        )
+    }
+
+    /// The upper left and lower right corners of the box.
+    pub fn rect(&self) -> ((i32, i32), (i32, i32)) {
+        (
+            (self.corner.0.round() as i32, self.corner.1.round() as i32),
+            (
+                (self.corner.0 + self.size.0).round() as i32,
+                (self.corner.1 + self.size.1).round() as i32,
+            ),
+        )
+    }
+
+    fn overlaps(&self, other: &Self) -> bool {
+        self.corner.0 < other.corner.0 + other.size.0
+            && other.corner.0 < self.corner.0 + self.size.0
+            && self.corner.1 < other.corner.1 + other.size.1
+            && other.corner.1 < self.corner.1 + self.size.1
+    }
+
+    /// The segment from the anchor to the nearest point of the box.
+    ///
+    /// Returns `None` if the anchor is inside the box.
+    pub fn segment(&self) -> Option<Vec<(i32, i32)>> {
+        let nearest = (
+            self.anchor
+                .0
+                .clamp(self.corner.0, self.corner.0 + self.size.0),
+            self.anchor
+                .1
+                .clamp(self.corner.1, self.corner.1 + self.size.1),
+        );
+
+        if nearest == self.anchor {
+            return None;
+        }
+
+        Some(vec![
+            (self.anchor.0.round() as i32, self.anchor.1.round() as i32),
+            (nearest.0.round() as i32, nearest.1.round() as i32),
+        ])
+    }
+}
+
+/// Place the texts of the `label` column in the backend coordinate.
+///
+/// `padding` is the space in pixels between the text and its box.
+pub(crate) fn place_texts<DB>(
+    chart: &ChartContext<'_, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+    panel: &Panel,
+    data: &Arc<dyn Data>,
+    mapping: &Aes,
+    placement: &Placement,
+    style: &TextStyle,
+    padding: i32,
+) -> anyhow::Result<Vec<PlacedText>>
+where
+    DB: DrawingBackend,
+    <DB as plotters::prelude::DrawingBackend>::ErrorType: 'static,
+{
+    let column_x = mapping.x.as_ref().expect("x must be provided for text");
+    let column_label = mapping
+        .label
+        .as_deref()
+        .expect("label must be provided for text");
+
+    // If only x is provided, we use it as y, and use index as x
+    let (x, y) = match &mapping.y {
+        Some(column_y) => (data.position_f64(column_x), data.position_f64(column_y)),
+        None => {
+            let y = data.position_f64(column_x);
+
+            ((0..y.len()).map(|u| u as f64).collect(), y)
+        }
+    };
+    let labels = data.column_string(column_label);
+
+    let padding = padding as f64;
+    let mut texts = Vec::with_capacity(labels.len());
+    for ((&x, &y), text) in x.iter().zip(y.iter()).zip(labels) {
+        let anchor = chart.backend_coord(&panel.map((x, y)));
+        let pos = chart.backend_coord(&panel.map((x + placement.nudge_x, y + placement.nudge_y)));
+        let (w, h) = chart.plotting_area().estimate_text_size(&text, style)?;
+        let size = (w as f64 + 2.0 * padding, h as f64 + 2.0 * padding);
+
+        texts.push(PlacedText {
+            text,
+            anchor: (anchor.0 as f64, anchor.1 as f64),
+            corner: (
+                pos.0 as f64 - placement.hjust * size.0,
+                pos.1 as f64 - (1.0 - placement.vjust) * size.1,
+            ),
+            size,
+            padding,
+        });
+    }
+
+    if placement.repel {
+        let (x_range, y_range) = chart.plotting_area().get_pixel_range();
+        repel(
+            &mut texts,
+            (x_range.start as f64, x_range.end as f64),
+            (y_range.start as f64, y_range.end as f64),
+        );
+    }
+
+    if placement.check_overlap {
+        let mut kept: Vec<PlacedText> = Vec::with_capacity(texts.len());
+        for text in texts {
+            if kept.iter().all(|k| !k.overlaps(&text)) {
+                kept.push(text);
+            }
+        }
+        texts = kept;
+    }
+
+    Ok(texts)
+}
+
+/// Push the overlapping boxes away from each other.
+///
+/// Each pair of overlapping boxes is moved apart along the axis with the
+/// smaller overlap, and all boxes are kept inside the given bounds.
+///
+/// Two boxes only overlap if their corners are closer than the largest box,
+/// so the boxes are put into a grid of cells of that size at each iteration,
+/// and each box is only compared with the boxes in the neighboring cells.
+fn repel(texts: &mut [PlacedText], x_bound: (f64, f64), y_bound: (f64, f64)) {
+    let cell_size = texts.iter().fold((1.0_f64, 1.0_f64), |(w, h), text| {
+        (w.max(text.size.0), h.max(text.size.1))
+    });
+
+    for _ in 0..REPEL_ITERATIONS {
+        let cells: Vec<(i64, i64)> = texts
+            .iter()
+            .map(|text| {
+                (
+                    (text.corner.0 / cell_size.0).floor() as i64,
+                    (text.corner.1 / cell_size.1).floor() as i64,
+                )
+            })
+            .collect();
+        let mut grid: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+        for (i, &cell) in cells.iter().enumerate() {
+            grid.entry(cell).or_default().push(i);
+        }
+        let grid = &grid;
+
+        let mut moved = false;
+
+        for i in 0..texts.len() {
+            let (x, y) = cells[i];
+            let mut neighbors: Vec<usize> = (x - 1..=x + 1)
+                .flat_map(|x| (y - 1..=y + 1).filter_map(move |y| grid.get(&(x, y))))
+                .flatten()
+                .copied()
+                .filter(|&j| j > i)
+                .collect();
+            neighbors.sort_unstable();
+
+            for j in neighbors {
+                if !texts[i].overlaps(&texts[j]) {
+                    continue;
+                }
+                moved = true;
+
+                let (a, b) = (&texts[i], &texts[j]);
+                let dx =
+                    (a.corner.0 + a.size.0).min(b.corner.0 + b.size.0) - a.corner.0.max(b.corner.0);
+                let dy =
+                    (a.corner.1 + a.size.1).min(b.corner.1 + b.size.1) - a.corner.1.max(b.corner.1);
+
+                // Move along the axis with the smaller overlap, `a` towards
+                // the negative side if it is not on the positive side of `b`
+                let (axis, overlap) = if dx < dy { (0, dx) } else { (1, dy) };
+                let center = |t: &PlacedText| match axis {
+                    0 => t.corner.0 + t.size.0 / 2.0,
+                    _ => t.corner.1 + t.size.1 / 2.0,
+                };
+                let sign = if center(a) > center(b) { 1.0 } else { -1.0 };
+                let shift = sign * (overlap / 2.0 + 0.5);
+
+                match axis {
+                    0 => {
+                        texts[i].corner.0 += shift;
+                        texts[j].corner.0 -= shift;
+                    }
+                    _ => {
+                        texts[i].corner.1 += shift;
+                        texts[j].corner.1 -= shift;
+                    }
+                }
+            }
+        }
+
+        for text in texts.iter_mut() {
+            text.corner.0 = text.corner.0.min(x_bound.1 - text.size.0).max(x_bound.0);
+            text.corner.1 = text.corner.1.min(y_bound.1 - text.size.1).max(y_bound.0);
+        }
+
+        if !moved {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placed(corner: (f64, f64)) -> PlacedText {
+        PlacedText {
+            text: String::new(),
+            anchor: corner,
+            corner,
+            size: (20.0, 10.0),
+            padding: 0.0,
+        }
+    }
+
+    #[test]
+    fn repel_overlapping_texts() {
+        let mut texts = vec![
+            placed((50.0, 50.0)),
+            placed((55.0, 52.0)),
+            placed((52.0, 48.0)),
+        ];
+
+        repel(&mut texts, (0.0, 200.0), (0.0, 200.0));
+
+        for i in 0..texts.len() {
+            for j in (i + 1)..texts.len() {
+                assert!(!texts[i].overlaps(&texts[j]));
+            }
+        }
+    }
+
+    #[test]
+    fn repel_keeps_texts_in_bounds() {
+        let mut texts = vec![placed((0.0, 0.0)), placed((1.0, 1.0))];
+
+        repel(&mut texts, (0.0, 100.0), (0.0, 100.0));
+
+        for text in &texts {
+            assert!(text.corner.0 >= 0.0 && text.corner.1 >= 0.0);
+            assert!(text.corner.0 + text.size.0 <= 100.0);
+            assert!(text.corner.1 + text.size.1 <= 100.0);
+        }
+    }
+
+    #[test]
+    fn repel_only_moves_overlapping_texts() {
+        let mut texts = vec![
+            placed((10.0, 10.0)),
+            placed((12.0, 10.0)),
+            placed((150.0, 150.0)),
+        ];
+
+        repel(&mut texts, (0.0, 200.0), (0.0, 200.0));
+
+        assert!(!texts[0].overlaps(&texts[1]));
+        assert_eq!(texts[2].corner, (150.0, 150.0));
+    }
+
+    #[test]
+    fn placement_setters_keep_defaults() {
+        let text = Text::builder().vjust(0.0).repel(true).build().unwrap();
+
+        assert_eq!((text.placement.hjust, text.placement.vjust), (0.5, 0.0));
+        assert!(text.placement.repel && !text.placement.check_overlap);
+    }
+}
@@ -0,0 +1,262 @@
+//! # Tile geom layer
+//!
+//! The tile geom draws a rectangle centered at each (x, y) position, filled by
+//! the `fill` aesthetic. It is usually used to create heatmaps, e.g. of a
+//! correlation matrix.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use gongbi::*;
+//! # use polars::prelude::*;
+//! # fn main() -> anyhow::Result<()> {
+//! let df = df!(
+//!     "x" => [1, 2, 1, 2],
+//!     "y" => [1, 1, 2, 2],
+//!     "value" => [0.1, 0.4, 0.8, 0.3],
+//! )?;
+//!
+//! let p = plot!(df, aes!(x, y, fill = value))
+//!     + geom_tile!()
+//!     + scale_fill_viridis_c!();
+//!
+//! p.to_svg("geom_tile.svg", (800, 600))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! See [`geom_tile!`](crate::geom_tile!) or [`Tile::builder`] for more details.
+
+use std::sync::Arc;
+
+use derive_builder::Builder;
+use plotters::{
+    chart::ChartContext,
+    coord::types::RangedCoordf64,
+    prelude::{Cartesian2d, DrawingBackend},
+    style::{Color, RGBColor},
+};
+
+use crate::{aes::color::ColorMapping, coord::Panel, layer::Layer, scale::Scales};
+
+use super::{draw_rects, fill_colors, fill_range, resolution};
+
+/// # Tile layer
+///
+/// The tile geom layer is used to draw rectangles centered at the (x, y) positions.
+///
+/// To create a tile layer, [`geom_tile!`](crate::geom_tile!) and [`Tile::builder`] can be used.
+#[derive(Clone, Debug, Default, Builder)]
+pub struct Tile {
+    /// The data source for the layer.
+    #[builder(default, setter(strip_option))]
+    data: Option<Arc<dyn crate::data::Data>>,
+
+    /// The aes mapping for the layer.
+    #[builder(default)]
+    mapping: crate::aes::Aes,
+
+    /// The width of the tiles in data units.
+    ///
+    /// If not provided, the resolution of the x values is used.
+    #[builder(default, setter(strip_option))]
+    width: Option<f64>,
+
+    /// The height of the tiles in data units.
+    ///
+    /// If not provided, the resolution of the y values is used.
+    #[builder(default, setter(strip_option))]
+    height: Option<f64>,
+}
+
+impl Tile {
+    /// Create a new [`Tile`] via the builder pattern.
+    pub fn builder() -> TileBuilder {
+        TileBuilder::default()
+    }
+
+    /// Get the centers, the width and the height of the tiles.
+    fn tiles(&self) -> (Vec<(f64, f64)>, f64, f64) {
+        let data = self.data.as_ref().expect("data is not provided");
+        let mapping = &self.mapping;
+
+        let column_x = mapping
+            .x
+            .as_ref()
+            .expect("x must be provided for geom_tile");
+        let column_y = mapping
+            .y
+            .as_ref()
+            .expect("y must be provided for geom_tile");
+
+        let x = data.position_f64(column_x);
+        let y = data.position_f64(column_y);
+
+        let width = self.width.unwrap_or_else(|| resolution(&x));
+        let height = self.height.unwrap_or_else(|| resolution(&y));
+
+        (
+            x.iter().copied().zip(y.iter().copied()).collect(),
+            width,
+            height,
+        )
+    }
+
+    fn draw_2d<'a, DB>(
+        &'a self,
+        chart: &mut ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+        panel: &Panel,
+        scales: &Scales,
+    ) -> anyhow::Result<()>
+    where
+        DB: DrawingBackend + 'a,
+        <DB as plotters::prelude::DrawingBackend>::ErrorType: 'static,
+    {
+        let data = self.data.as_ref().expect("data is not provided");
+        let mapping = &self.mapping;
+
+        let (centers, width, height) = self.tiles();
+        let rects: Vec<[(f64, f64); 2]> = centers
+            .into_iter()
+            .map(|(x, y)| {
+                [
+                    (x - width / 2.0, y - height / 2.0),
+                    (x + width / 2.0, y + height / 2.0),
+                ]
+            })
+            .collect();
+
+        let fills = fill_colors(data, mapping, scales, rects.len(), RGBColor(89, 89, 89));
+
+        draw_rects(
+            chart,
+            panel,
+            rects
+                .iter()
+                .zip(fills)
+                .filter_map(|(&rect, fill)| Some((rect, fill?.filled()))),
+        )?;
+
+        if let Some(color) = mapping.color.as_ref().and_then(ColorMapping::as_constant) {
+            draw_rects(chart, panel, rects.iter().map(|&rect| (rect, color.into())))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// # geom_tile!: Construct a [`Tile`] layer
+///
+/// This macro is used to create a [`Tile`] layer in a more concise way like `ggplot2`.
+/// It is a wrapper around [`Tile::builder`].
+///
+/// ## Usage
+///
+/// ```ignore
+/// geom_tile!(
+///     mapping = aes!(x, y, fill = <FILL_COLUMN>),
+///     data = <Data>,
+///     width = <f64>,
+///     height = <f64>,
+/// )
+/// ```
+///
+/// ### Arguments
+///
+/// #### mapping
+///
+/// Set of aesthetic mappings created by [`aes!`](crate::aes!) or [`Aes::builder`](crate::aes::Aes::builder).
+///
+/// `x` and `y` are the centers of the tiles. `fill` is usually mapped to a
+/// column, and the values are mapped to colors by the fill scale, e.g.
+/// [`scale_fill_gradient!`](crate::scale_fill_gradient!). If `color` is set,
+/// the border of the tiles is drawn.
+///
+/// #### data
+///
+/// The data to be displayed in this layer.
+///
+/// If not provided, it will be inherited from the [`Plot`](crate::Plot).
+///
+/// #### width, height
+///
+/// The size of the tiles in data units. Defaults to the smallest distance
+/// between the x or y values.
+#[macro_export]
+macro_rules! geom_tile {
+    ($($param: ident = $value: expr),* $(,)?) => {
+        $crate::layer::geom::tile::Tile::builder()
+            $(.$param($value))*
+            .build()
+            .unwrap()
+    };
+
+    ($mapping: expr $(, $($param: ident = $value: expr),+ $(,)?)?) => {
+        geom_tile!(mapping = $mapping $(, $($param = $value),+)?)
+    };
+}
+
+impl Layer for Tile {
+    fn data_mut(&mut self) -> &mut Option<Arc<dyn crate::data::Data>> {
+        &mut self.data
+    }
+
+    fn mapping_mut(&mut self) -> &mut crate::aes::Aes {
+        &mut self.mapping
+    }
+
+    fn range_2d(&self) -> Option<(f64, f64, f64, f64)> {
+        let (centers, width, height) = self.tiles();
+
+        let range = centers.into_iter().fold(
+            (
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+            ),
+            |acc, (x, y)| (acc.0.min(x), acc.1.max(x), acc.2.min(y), acc.3.max(y)),
+        );
+
+        Some((
+            range.0 - width / 2.0,
+            range.1 + width / 2.0,
+            range.2 - height / 2.0,
+            range.3 + height / 2.0,
+        ))
+    }
+
+    fn fill_range(&self) -> Option<(f64, f64)> {
+        let data = self.data.as_ref().expect("data is not provided");
+
+        fill_range(data, &self.mapping)
+    }
+
+    #[cfg(feature = "svg")]
+    fn draw_svg_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::SVGBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        scales: &Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel, scales)
+    }
+
+    #[cfg(feature = "png")]
+    fn draw_png_2d<'a>(
+        &'a self,
+        chart: &mut ChartContext<
+            'a,
+            plotters::prelude::BitMapBackend<'a>,
+            Cartesian2d<RangedCoordf64, RangedCoordf64>,
+        >,
+        panel: &Panel,
+        scales: &Scales,
+    ) -> anyhow::Result<()> {
+        self.draw_2d(chart, panel, scales)
+    }
+}
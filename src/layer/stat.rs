@@ -0,0 +1,1078 @@
+//! Stat module
+//!
+//! This module contains the statistical transformations used by the geometric
+//! layers, e.g. counting the points in bins or estimating a density, and
+//! the computation of contours of gridded values.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::OnceLock,
+};
+
+#[cfg(feature = "polars")]
+use polars::prelude::{col, len, lit, when, DataType, Expr, LazyFrame};
+
+#[cfg(feature = "polars")]
+use crate::data::{polars::collect, Data};
+
+/// A statistic of a layer, computed once and shared by its range, scales and drawing
+///
+/// It is computed by [`Layer::compute`](crate::layer::Layer::compute), and reset
+/// when the data or the mapping of the layer is changed.
+#[derive(Clone, Debug)]
+pub(crate) struct Cache<T>(OnceLock<T>);
+
+impl<T> Default for Cache<T> {
+    fn default() -> Self {
+        Cache(OnceLock::new())
+    }
+}
+
+impl<T> Cache<T> {
+    /// Get the statistic, computing it if needed.
+    pub fn get_or_init(&self, compute: impl FnOnce() -> T) -> &T {
+        self.0.get_or_init(compute)
+    }
+
+    /// Drop the statistic, so that it is computed again.
+    pub fn reset(&mut self) {
+        self.0 = OnceLock::new();
+    }
+}
+
+/// A regular grid of values
+///
+/// `z` is stored row by row, i.e. the value at `(x[i], y[j])` is `z[j * x.len() + i]`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Grid {
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    pub z: Vec<f64>,
+}
+
+impl Grid {
+    /// Get the value at the node `(i, j)`.
+    fn at(&self, i: usize, j: usize) -> f64 {
+        self.z[j * self.x.len() + i]
+    }
+
+    /// Whether the values at the corners of the cell `(i, j)` are finite.
+    fn is_complete(&self, i: usize, j: usize) -> bool {
+        [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)]
+            .iter()
+            .all(|&(i, j)| self.at(i, j).is_finite())
+    }
+
+    /// Create a grid from the values at the points of a regular grid.
+    ///
+    /// The distinct x and y values are the nodes of the grid. The nodes
+    /// without a finite value are missing, and the cells around them are skipped.
+    pub fn from_points(x: &[f64], y: &[f64], z: &[f64]) -> Self {
+        let axis = |values: &[f64]| {
+            let mut axis: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+            axis.sort_by(f64::total_cmp);
+            axis.dedup();
+            axis
+        };
+        let (axis_x, axis_y) = (axis(x), axis(y));
+
+        let mut values = vec![f64::NAN; axis_x.len() * axis_y.len()];
+        for ((x, y), z) in x.iter().zip(y).zip(z) {
+            let i = axis_x.binary_search_by(|v| v.total_cmp(x));
+            let j = axis_y.binary_search_by(|v| v.total_cmp(y));
+
+            if let (Ok(i), Ok(j)) = (i, j) {
+                values[j * axis_x.len() + i] = *z;
+            }
+        }
+
+        Grid {
+            x: axis_x,
+            y: axis_y,
+            z: values,
+        }
+    }
+
+    /// Get the range of the finite values.
+    pub fn range_z(&self) -> Option<(f64, f64)> {
+        self.z
+            .iter()
+            .filter(|z| z.is_finite())
+            .fold(None, |acc, &z| match acc {
+                None => Some((z, z)),
+                Some((low, high)) => Some((z.min(low), z.max(high))),
+            })
+    }
+}
+
+/// Keep the pairs of finite values.
+pub(crate) fn finite_pairs(x: &[f64], y: &[f64]) -> Vec<(f64, f64)> {
+    x.iter()
+        .copied()
+        .zip(y.iter().copied())
+        .filter(|(x, y)| x.is_finite() && y.is_finite())
+        .collect()
+}
+
+/// Get the range of the values, or `None` if there are no values.
+fn range(values: impl Iterator<Item = f64>) -> Option<(f64, f64)> {
+    values.fold(None, |acc, v| match acc {
+        None => Some((v, v)),
+        Some((low, high)) => Some((v.min(low), v.max(high))),
+    })
+}
+
+/// Get the origin and the width of the bins covering the range of the values.
+///
+/// If `binwidth` is not provided, the range of the values is divided into `bins` bins.
+fn bin_layout(range: Option<(f64, f64)>, bins: usize, binwidth: Option<f64>) -> (f64, f64) {
+    let (low, high) = range.unwrap_or((0.0, 0.0));
+
+    let width = binwidth.unwrap_or((high - low) / bins.max(1) as f64);
+    if width > 0.0 {
+        (low, width)
+    } else {
+        (low - 0.5, 1.0)
+    }
+}
+
+/// The layout of rectangular bins
+struct RectBins {
+    /// The origin and the width of the bins along x
+    x: (f64, f64),
+
+    /// The origin and the height of the bins along y
+    y: (f64, f64),
+
+    /// The index of the last bins, which also hold the maximum values
+    last: Option<i64>,
+}
+
+impl RectBins {
+    fn new(
+        ranges: Option<((f64, f64), (f64, f64))>,
+        bins: usize,
+        binwidth: Option<(f64, f64)>,
+    ) -> Self {
+        RectBins {
+            x: bin_layout(ranges.map(|r| r.0), bins, binwidth.map(|b| b.0)),
+            y: bin_layout(ranges.map(|r| r.1), bins, binwidth.map(|b| b.1)),
+            // Without a given binwidth, the maximum values belong to the last bins
+            last: binwidth.is_none().then_some(bins.max(1) as i64 - 1),
+        }
+    }
+
+    /// Get the index of the bin of a value along an axis.
+    fn index(&self, v: f64, (origin, width): (f64, f64)) -> i64 {
+        let i = ((v - origin) / width).floor() as i64;
+        self.last.map_or(i, |last| i.min(last))
+    }
+
+    /// Get the lower left and upper right corners of the bin `(i, j)`.
+    fn rect(&self, (i, j): (i64, i64)) -> [(f64, f64); 2] {
+        let ((x0, w), (y0, h)) = (self.x, self.y);
+        let (x, y) = (x0 + i as f64 * w, y0 + j as f64 * h);
+
+        [(x, y), (x + w, y + h)]
+    }
+
+    /// Get the expression of the index of the bins of the values along an axis.
+    #[cfg(feature = "polars")]
+    fn index_expr(&self, v: Expr, (origin, width): (f64, f64)) -> Expr {
+        let i = ((v - lit(origin)) / lit(width))
+            .floor()
+            .cast(DataType::Int64);
+
+        match self.last {
+            Some(last) => when(i.clone().gt(lit(last))).then(lit(last)).otherwise(i),
+            None => i,
+        }
+    }
+}
+
+/// Count the points in rectangular bins.
+///
+/// Returns the lower left and upper right corners of the non-empty bins, with their counts.
+pub(crate) fn bin_2d(
+    points: &[(f64, f64)],
+    bins: usize,
+    binwidth: Option<(f64, f64)>,
+) -> Vec<([(f64, f64); 2], f64)> {
+    let layout = RectBins::new(ranges(points), bins, binwidth);
+
+    let mut counts = BTreeMap::new();
+    for &(x, y) in points {
+        let index = (layout.index(x, layout.x), layout.index(y, layout.y));
+        *counts.entry(index).or_insert(0.0) += 1.0;
+    }
+
+    counts
+        .into_iter()
+        .map(|(index, count)| (layout.rect(index), count))
+        .collect()
+}
+
+/// Count the points of a lazy frame in rectangular bins, in the query of the lazy frame.
+///
+/// The positions are the expressions `x` and `y`. The bins are the same as [`bin_2d`].
+#[cfg(feature = "polars")]
+pub(crate) fn bin_2d_lazy(
+    data: &LazyFrame,
+    (x, y): (Expr, Expr),
+    bins: usize,
+    binwidth: Option<(f64, f64)>,
+) -> Vec<([(f64, f64); 2], f64)> {
+    let points = lazy_points(data, x, y);
+    let layout = RectBins::new(lazy_ranges(&points), bins, binwidth);
+
+    let index = [
+        layout.index_expr(col("x"), layout.x),
+        layout.index_expr(col("y"), layout.y),
+    ];
+
+    lazy_counts(points, index)
+        .into_iter()
+        .map(|(index, count)| (layout.rect(index), count))
+        .collect()
+}
+
+/// The hexagonal bins of points
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct HexBins {
+    /// The distance between the centers of two neighbouring hexagons in a row
+    pub width: f64,
+
+    /// The distance between two rows of hexagons
+    pub height: f64,
+
+    /// The centers of the non-empty hexagons, with their counts
+    pub counts: Vec<((f64, f64), f64)>,
+}
+
+impl HexBins {
+    /// Get the vertices of the hexagon centered at `(x, y)`.
+    pub fn hexagon(&self, (x, y): (f64, f64)) -> Vec<(f64, f64)> {
+        (0..6)
+            .map(|k| {
+                let angle = (30.0 + 60.0 * k as f64).to_radians();
+                (
+                    x + self.width * angle.cos() / 3f64.sqrt(),
+                    y + self.height * angle.sin() * 2.0 / 3.0,
+                )
+            })
+            .collect()
+    }
+}
+
+/// The layout of hexagonal bins
+///
+/// The hexagons are pointy-topped, and every other row is shifted by half a hexagon.
+struct HexLayout {
+    /// The origin and the width of the hexagons along x
+    x: (f64, f64),
+
+    /// The origin and the distance between the rows along y
+    y: (f64, f64),
+}
+
+impl HexLayout {
+    /// The ratio of the row spacing to the width of regular hexagons
+    const RATIO: f64 = 0.866_025_403_784_438_6;
+
+    fn new(
+        ranges: Option<((f64, f64), (f64, f64))>,
+        bins: usize,
+        binwidth: Option<(f64, f64)>,
+    ) -> Self {
+        HexLayout {
+            x: bin_layout(ranges.map(|r| r.0), bins, binwidth.map(|b| b.0)),
+            y: bin_layout(ranges.map(|r| r.1), bins, binwidth.map(|b| b.1)),
+        }
+    }
+
+    /// Get the index `(i, j)` of the hexagon whose center is the nearest to the point.
+    fn index(&self, x: f64, y: f64) -> (i64, i64) {
+        let (u, v) = ((x - self.x.0) / self.x.1, (y - self.y.0) / self.y.1);
+
+        // The nearest center is in one of the two rows around the point
+        let row = v.floor() as i64;
+        [row, row + 1]
+            .into_iter()
+            .map(|j| {
+                let offset = if j.rem_euclid(2) == 1 { 0.5 } else { 0.0 };
+                let i = (u - offset).round() as i64;
+                let d = (u - i as f64 - offset).powi(2) + ((v - j as f64) * Self::RATIO).powi(2);
+                ((i, j), d)
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(index, _)| index)
+            .unwrap()
+    }
+
+    /// Get the center of the hexagon `(i, j)`.
+    fn center(&self, (i, j): (i64, i64)) -> (f64, f64) {
+        let offset = if j.rem_euclid(2) == 1 { 0.5 } else { 0.0 };
+
+        (
+            self.x.0 + (i as f64 + offset) * self.x.1,
+            self.y.0 + j as f64 * self.y.1,
+        )
+    }
+
+    /// Get the expressions of the index of the nearest hexagons of the points.
+    #[cfg(feature = "polars")]
+    fn index_exprs(&self, x: Expr, y: Expr) -> [Expr; 2] {
+        let u = (x - lit(self.x.0)) / lit(self.x.1);
+        let v = (y - lit(self.y.0)) / lit(self.y.1);
+
+        // The index of the nearest center in the row j, and its squared distance
+        let nearest = |j: Expr| {
+            let offset = (j.clone() - (j.clone() / lit(2.0)).floor() * lit(2.0)) * lit(0.5);
+            let i = (u.clone() - offset.clone()).round(0);
+            let du = u.clone() - i.clone() - offset;
+            let dv = (v.clone() - j) * lit(Self::RATIO);
+
+            (i, du.clone() * du + dv.clone() * dv)
+        };
+
+        let row = v.clone().floor();
+        let (i0, d0) = nearest(row.clone());
+        let (i1, d1) = nearest(row.clone() + lit(1.0));
+        let next = d1.lt(d0);
+
+        [
+            when(next.clone()).then(i1).otherwise(i0),
+            when(next).then(row.clone() + lit(1.0)).otherwise(row),
+        ]
+        .map(|index| index.cast(DataType::Int64))
+    }
+
+    fn bins(&self, counts: impl IntoIterator<Item = ((i64, i64), f64)>) -> HexBins {
+        HexBins {
+            width: self.x.1,
+            height: self.y.1,
+            counts: counts
+                .into_iter()
+                .map(|(index, count)| (self.center(index), count))
+                .collect(),
+        }
+    }
+}
+
+/// Count the points in hexagonal bins.
+///
+/// The hexagons are pointy-topped, and every other row is shifted by half a hexagon.
+pub(crate) fn hex_bin(points: &[(f64, f64)], bins: usize, binwidth: Option<(f64, f64)>) -> HexBins {
+    let layout = HexLayout::new(ranges(points), bins, binwidth);
+
+    let mut counts = BTreeMap::new();
+    for &(x, y) in points {
+        *counts.entry(layout.index(x, y)).or_insert(0.0) += 1.0;
+    }
+
+    layout.bins(counts)
+}
+
+/// Count the points of a lazy frame in hexagonal bins, in the query of the lazy frame.
+///
+/// The positions are the expressions `x` and `y`. The bins are the same as [`hex_bin`].
+#[cfg(feature = "polars")]
+pub(crate) fn hex_bin_lazy(
+    data: &LazyFrame,
+    (x, y): (Expr, Expr),
+    bins: usize,
+    binwidth: Option<(f64, f64)>,
+) -> HexBins {
+    let points = lazy_points(data, x, y);
+    let layout = HexLayout::new(lazy_ranges(&points), bins, binwidth);
+    let index = layout.index_exprs(col("x"), col("y"));
+
+    layout.bins(lazy_counts(points, index))
+}
+
+/// Get the ranges of the x and y values of the points, or `None` if there are no points.
+fn ranges(points: &[(f64, f64)]) -> Option<((f64, f64), (f64, f64))> {
+    range(points.iter().map(|p| p.0)).zip(range(points.iter().map(|p| p.1)))
+}
+
+/// Get the query of the pairs of finite positions, as the columns `x` and `y`.
+#[cfg(feature = "polars")]
+fn lazy_points(data: &LazyFrame, x: Expr, y: Expr) -> LazyFrame {
+    data.clone()
+        .select([
+            x.cast(DataType::Float64).alias("x"),
+            y.cast(DataType::Float64).alias("y"),
+        ])
+        .filter(col("x").is_finite().and(col("y").is_finite()))
+}
+
+/// Get the ranges of the x and y values of the query of points, or `None` if there are no points.
+#[cfg(feature = "polars")]
+fn lazy_ranges(points: &LazyFrame) -> Option<((f64, f64), (f64, f64))> {
+    let ranges = collect(points.clone().select([
+        col("x").min().alias("x_min"),
+        col("x").max().alias("x_max"),
+        col("y").min().alias("y_min"),
+        col("y").max().alias("y_max"),
+    ]));
+    let value = |name: &str| ranges.column_f64(name).first().copied();
+
+    match [
+        value("x_min"),
+        value("x_max"),
+        value("y_min"),
+        value("y_max"),
+    ] {
+        [Some(x0), Some(x1), Some(y0), Some(y1)] if x0 <= x1 && y0 <= y1 => {
+            Some(((x0, x1), (y0, y1)))
+        }
+        _ => None,
+    }
+}
+
+/// Count the points of the query by the index of their bins, sorted by the index.
+#[cfg(feature = "polars")]
+fn lazy_counts(points: LazyFrame, [i, j]: [Expr; 2]) -> Vec<((i64, i64), f64)> {
+    let counts = collect(
+        points
+            .select([i.alias("i"), j.alias("j")])
+            .group_by([col("i"), col("j")])
+            .agg([len().alias("count")]),
+    );
+
+    let mut counts: Vec<((i64, i64), f64)> = counts
+        .column_i64("i")
+        .into_iter()
+        .zip(counts.column_i64("j"))
+        .zip(counts.column_f64("count").iter())
+        .filter_map(|((i, j), &count)| Some(((i?, j?), count)))
+        .collect();
+    counts.sort_by_key(|&(index, _)| index);
+
+    counts
+}
+
+/// Get the standard deviation of the gaussian kernel by the rule of thumb
+/// `1.06 * min(sd, IQR / 1.34) * n^(-1/5)`.
+pub(crate) fn bandwidth_nrd(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 1.0;
+    }
+
+    1.06 * spread(values) * (values.len() as f64).powf(-0.2)
+}
+
+/// Get the standard deviation of the kernel by Silverman's rule of thumb
+/// `0.9 * min(sd, IQR / 1.34) * n^(-1/5)`.
+pub(crate) fn bandwidth_nrd0(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 1.0;
+    }
+
+    0.9 * spread(values) * (values.len() as f64).powf(-0.2)
+}
+
+/// Get the robust spread `min(sd, IQR / 1.34)` of at least two values.
+///
+/// Falls back to the standard deviation, then to `1.0`, if the spread is zero.
+fn spread(values: &[f64]) -> f64 {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let sd = (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let iqr = quantile(&sorted, 0.75) - quantile(&sorted, 0.25);
+
+    match sd.min(iqr / 1.34) {
+        s if s > 0.0 => s,
+        _ if sd > 0.0 => sd,
+        _ => 1.0,
+    }
+}
+
+/// Get the quantile of sorted values, with linear interpolation.
+fn quantile(sorted: &[f64], p: f64) -> f64 {
+    let pos = p * (sorted.len() - 1) as f64;
+    let (i, frac) = (pos.floor() as usize, pos.fract());
+
+    match sorted.get(i + 1) {
+        Some(next) => sorted[i] + (next - sorted[i]) * frac,
+        None => sorted[i],
+    }
+}
+
+/// The kernel of a density estimation
+///
+/// The kernels are scaled to have a standard deviation of one bandwidth.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Kernel {
+    /// The gaussian kernel
+    #[default]
+    Gaussian,
+
+    /// The rectangular kernel
+    Rectangular,
+
+    /// The triangular kernel
+    Triangular,
+
+    /// The Epanechnikov kernel
+    Epanechnikov,
+
+    /// The biweight kernel
+    Biweight,
+
+    /// The cosine kernel
+    Cosine,
+
+    /// The optimal cosine kernel
+    Optcosine,
+}
+
+impl std::str::FromStr for Kernel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "gaussian" => Ok(Kernel::Gaussian),
+            "rectangular" => Ok(Kernel::Rectangular),
+            "triangular" => Ok(Kernel::Triangular),
+            "epanechnikov" => Ok(Kernel::Epanechnikov),
+            "biweight" => Ok(Kernel::Biweight),
+            "cosine" => Ok(Kernel::Cosine),
+            "optcosine" => Ok(Kernel::Optcosine),
+            _ => Err(format!("Unsupported kernel: {s}")),
+        }
+    }
+}
+
+impl From<&str> for Kernel {
+    fn from(value: &str) -> Self {
+        value.parse().unwrap()
+    }
+}
+
+impl Kernel {
+    /// Get the density of the kernel with standard deviation `bw` at `u`.
+    pub fn density(&self, u: f64, bw: f64) -> f64 {
+        use std::f64::consts::PI;
+
+        // The half width of the kernels with a bounded support
+        let support = |factor: f64| {
+            let a = bw * factor;
+            (u.abs() < a).then_some(a)
+        };
+
+        match self {
+            Kernel::Gaussian => (-0.5 * (u / bw).powi(2)).exp() / (bw * (2.0 * PI).sqrt()),
+            Kernel::Rectangular => support(3f64.sqrt()).map_or(0.0, |a| 0.5 / a),
+            Kernel::Triangular => support(6f64.sqrt()).map_or(0.0, |a| (1.0 - u.abs() / a) / a),
+            Kernel::Epanechnikov => {
+                support(5f64.sqrt()).map_or(0.0, |a| 0.75 * (1.0 - (u / a).powi(2)) / a)
+            }
+            Kernel::Biweight => support(7f64.sqrt())
+                .map_or(0.0, |a| 15.0 / 16.0 * (1.0 - (u / a).powi(2)).powi(2) / a),
+            Kernel::Cosine => support(1.0 / (1.0 / 3.0 - 2.0 / PI.powi(2)).sqrt())
+                .map_or(0.0, |a| (1.0 + (PI * u / a).cos()) / (2.0 * a)),
+            Kernel::Optcosine => support(1.0 / (1.0 - 8.0 / PI.powi(2)).sqrt())
+                .map_or(0.0, |a| PI / 4.0 * (PI * u / (2.0 * a)).cos() / a),
+        }
+    }
+}
+
+/// Estimate the density of the values with a kernel of bandwidth `bw` at `n`
+/// evenly spaced points over the range of the values.
+///
+/// Returns the points and the density at them.
+pub(crate) fn kde(values: &[f64], n: usize, kernel: Kernel, bw: f64) -> Vec<(f64, f64)> {
+    let Some((low, high)) = range(values.iter().copied()) else {
+        return vec![];
+    };
+
+    let n = n.max(2);
+    let step = (high - low) / (n - 1) as f64;
+    let len = values.len() as f64;
+
+    (0..n)
+        .map(|i| {
+            let x = low + i as f64 * step;
+            let density = values
+                .iter()
+                .map(|v| kernel.density(x - v, bw))
+                .sum::<f64>()
+                / len;
+
+            (x, density)
+        })
+        .collect()
+}
+
+/// Estimate the 2D density of the points with a gaussian kernel on a `n` x `n` grid.
+///
+/// `sd` is the standard deviation of the kernel in x and y. The points are
+/// linearly binned on the grid before the kernel is applied, which keeps the
+/// estimation fast for large datasets.
+pub(crate) fn kde_2d(points: &[(f64, f64)], n: usize, sd: (f64, f64)) -> Grid {
+    let n = n.max(2);
+    let axis = |values: Vec<f64>| {
+        let (low, high) = range(values.into_iter()).unwrap_or((0.0, 1.0));
+        let (low, high) = if high > low {
+            (low, high)
+        } else {
+            (low - 0.5, high + 0.5)
+        };
+        let step = (high - low) / (n - 1) as f64;
+
+        (0..n).map(|i| low + i as f64 * step).collect::<Vec<_>>()
+    };
+
+    let x = axis(points.iter().map(|p| p.0).collect());
+    let y = axis(points.iter().map(|p| p.1).collect());
+    let (dx, dy) = (x[1] - x[0], y[1] - y[0]);
+
+    // Linear binning of the points on the grid nodes
+    let mut weights = vec![0.0; n * n];
+    for &(px, py) in points {
+        let (u, v) = ((px - x[0]) / dx, (py - y[0]) / dy);
+        let (i, j) = (
+            (u.floor() as usize).min(n - 2),
+            (v.floor() as usize).min(n - 2),
+        );
+        let (fu, fv) = (u - i as f64, v - j as f64);
+
+        weights[j * n + i] += (1.0 - fu) * (1.0 - fv);
+        weights[j * n + i + 1] += fu * (1.0 - fv);
+        weights[(j + 1) * n + i] += (1.0 - fu) * fv;
+        weights[(j + 1) * n + i + 1] += fu * fv;
+    }
+
+    // The kernel is separable, so it is applied along x and then along y
+    let kernel = |sd: f64, step: f64| {
+        let reach = ((4.0 * sd / step).ceil() as usize).min(n - 1);
+        (0..=reach)
+            .map(|d| (-0.5 * (d as f64 * step / sd).powi(2)).exp())
+            .collect::<Vec<_>>()
+    };
+    let convolve = |values: &[f64], kernel: &[f64], at: &dyn Fn(usize, usize) -> usize| {
+        let mut out = vec![0.0; n * n];
+        for line in 0..n {
+            for k in 0..n {
+                let lo = k.saturating_sub(kernel.len() - 1);
+                let hi = (k + kernel.len()).min(n);
+                out[at(line, k)] = (lo..hi)
+                    .map(|m| values[at(line, m)] * kernel[k.abs_diff(m)])
+                    .sum();
+            }
+        }
+        out
+    };
+
+    let z = convolve(&weights, &kernel(sd.0, dx), &|j, i| j * n + i);
+    let z = convolve(&z, &kernel(sd.1, dy), &|i, j| j * n + i);
+
+    let norm = points.len().max(1) as f64 * 2.0 * std::f64::consts::PI * sd.0 * sd.1;
+    let z = z.into_iter().map(|z| z / norm).collect();
+
+    Grid { x, y, z }
+}
+
+/// Get the contour levels of the values.
+///
+/// The given `breaks` are used if provided, otherwise about `bins` evenly
+/// spaced levels covering the range of the values.
+pub(crate) fn levels(range: (f64, f64), bins: usize, breaks: Option<&[f64]>) -> Vec<f64> {
+    match breaks {
+        Some(breaks) => breaks.to_vec(),
+        None => {
+            let (mut levels, step) = crate::guide::breaks(range.0, range.1, bins);
+
+            // Cover the whole range
+            if levels.first().is_some_and(|&first| first > range.0) {
+                levels.insert(0, levels[0] - step);
+            }
+            if levels.last().is_some_and(|&last| last < range.1) {
+                levels.push(levels[levels.len() - 1] + step);
+            }
+
+            levels
+        }
+    }
+}
+
+/// An edge of the grid, identified by its lower left node and its direction
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Edge {
+    /// The edge from `(i, j)` to `(i + 1, j)`
+    Horizontal(usize, usize),
+
+    /// The edge from `(i, j)` to `(i, j + 1)`
+    Vertical(usize, usize),
+}
+
+/// Get the iso-lines of the grid at a level with the marching squares algorithm.
+///
+/// Returns the paths of the iso-lines. Closed iso-lines end at their first point.
+/// The cells with missing values are skipped.
+pub(crate) fn contour_lines(grid: &Grid, level: f64) -> Vec<Vec<(f64, f64)>> {
+    let (nx, ny) = (grid.x.len(), grid.y.len());
+    let above = |i: usize, j: usize| grid.at(i, j) >= level;
+
+    let crossing = |edge: Edge| {
+        let ((i0, j0), (i1, j1)) = match edge {
+            Edge::Horizontal(i, j) => ((i, j), (i + 1, j)),
+            Edge::Vertical(i, j) => ((i, j), (i, j + 1)),
+        };
+        let (z0, z1) = (grid.at(i0, j0), grid.at(i1, j1));
+        let t = if z1 != z0 {
+            (level - z0) / (z1 - z0)
+        } else {
+            0.5
+        };
+
+        (
+            grid.x[i0] + (grid.x[i1] - grid.x[i0]) * t,
+            grid.y[j0] + (grid.y[j1] - grid.y[j0]) * t,
+        )
+    };
+
+    let mut segments = vec![];
+    for j in 0..ny.saturating_sub(1) {
+        for i in 0..nx.saturating_sub(1) {
+            if !grid.is_complete(i, j) {
+                continue;
+            }
+
+            let corners = [
+                above(i, j),
+                above(i + 1, j),
+                above(i + 1, j + 1),
+                above(i, j + 1),
+            ];
+            let sides = [
+                Edge::Horizontal(i, j),
+                Edge::Vertical(i + 1, j),
+                Edge::Horizontal(i, j + 1),
+                Edge::Vertical(i, j),
+            ];
+
+            // The side k is between the corners k and k + 1
+            let crossed: Vec<Edge> = (0..4)
+                .filter(|&k| corners[k] != corners[(k + 1) % 4])
+                .map(|k| sides[k])
+                .collect();
+
+            match crossed[..] {
+                [a, b] => segments.push((a, b)),
+                [bottom, right, top, left] => {
+                    // Saddle: the average value decides which corners are connected
+                    let center = (grid.at(i, j)
+                        + grid.at(i + 1, j)
+                        + grid.at(i + 1, j + 1)
+                        + grid.at(i, j + 1))
+                        / 4.0;
+
+                    if (center >= level) == corners[0] {
+                        segments.extend([(bottom, right), (top, left)]);
+                    } else {
+                        segments.extend([(left, bottom), (right, top)]);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Chain the segments sharing an edge into paths
+    let mut by_edge: HashMap<Edge, Vec<usize>> = HashMap::new();
+    for (k, (a, b)) in segments.iter().enumerate() {
+        by_edge.entry(*a).or_default().push(k);
+        by_edge.entry(*b).or_default().push(k);
+    }
+
+    let mut used = vec![false; segments.len()];
+    let follow = |start: Edge, used: &mut Vec<bool>| {
+        let mut path = vec![crossing(start)];
+        let mut edge = start;
+
+        while let Some(&k) = by_edge[&edge].iter().find(|&&k| !used[k]) {
+            used[k] = true;
+            let (a, b) = segments[k];
+            edge = if a == edge { b } else { a };
+            path.push(crossing(edge));
+        }
+
+        path
+    };
+
+    // Open paths start at the boundary of the grid, the others are closed
+    let mut starts: Vec<Edge> = by_edge
+        .iter()
+        .filter(|(_, ks)| ks.len() == 1)
+        .map(|(edge, _)| *edge)
+        .collect();
+    starts.sort();
+    starts.extend(segments.iter().map(|(a, _)| *a));
+
+    let mut paths = vec![];
+    for start in starts {
+        if by_edge[&start].iter().any(|&k| !used[k]) {
+            paths.push(follow(start, &mut used));
+        }
+    }
+
+    paths
+}
+
+/// Clip a polygon whose vertices carry values to the part where `keep(value) >= 0`.
+///
+/// The values are linearly interpolated along the sides of the polygon.
+fn clip(polygon: &[((f64, f64), f64)], keep: impl Fn(f64) -> f64) -> Vec<((f64, f64), f64)> {
+    let mut out = vec![];
+
+    for (k, &(p, z)) in polygon.iter().enumerate() {
+        let (q, w) = polygon[(k + 1) % polygon.len()];
+        let (fp, fq) = (keep(z), keep(w));
+
+        if fp >= 0.0 {
+            out.push((p, z));
+        }
+        if (fp >= 0.0) != (fq >= 0.0) {
+            let t = fp / (fp - fq);
+            out.push((
+                (p.0 + (q.0 - p.0) * t, p.1 + (q.1 - p.1) * t),
+                z + (w - z) * t,
+            ));
+        }
+    }
+
+    out
+}
+
+/// Get the filled band of the grid between two levels, `low <= z < high`.
+///
+/// Returns the polygons covering the band. The cells fully inside the band are
+/// merged by rows, and the other cells are split into two triangles clipped
+/// to the band. The cells with missing values are skipped.
+pub(crate) fn contour_band(grid: &Grid, low: f64, high: f64) -> Vec<Vec<(f64, f64)>> {
+    let (nx, ny) = (grid.x.len(), grid.y.len());
+    let inside = |z: f64| low <= z && z < high;
+
+    let mut polygons = vec![];
+    for j in 0..ny.saturating_sub(1) {
+        let mut run: Option<usize> = None;
+
+        for i in 0..nx {
+            let full = i + 1 < nx
+                && [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)]
+                    .iter()
+                    .all(|&(i, j)| inside(grid.at(i, j)));
+
+            if full {
+                run.get_or_insert(i);
+                continue;
+            }
+
+            if let Some(start) = run.take() {
+                let (x0, x1, y0, y1) = (grid.x[start], grid.x[i], grid.y[j], grid.y[j + 1]);
+                polygons.push(vec![(x0, y0), (x1, y0), (x1, y1), (x0, y1)]);
+            }
+
+            if i + 1 == nx || !grid.is_complete(i, j) {
+                continue;
+            }
+
+            let node = |i: usize, j: usize| ((grid.x[i], grid.y[j]), grid.at(i, j));
+            let triangles = [
+                [node(i, j), node(i + 1, j), node(i + 1, j + 1)],
+                [node(i, j), node(i + 1, j + 1), node(i, j + 1)],
+            ];
+
+            for triangle in triangles {
+                let clipped = clip(&triangle, |z| z - low);
+                let clipped = clip(&clipped, |z| high - z);
+
+                if clipped.len() >= 3 {
+                    polygons.push(clipped.into_iter().map(|(p, _)| p).collect());
+                }
+            }
+        }
+    }
+
+    polygons
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_computes_once() {
+        let mut cache = Cache::default();
+
+        assert_eq!(cache.get_or_init(|| 1), &1);
+        assert_eq!(cache.get_or_init(|| 2), &1);
+
+        cache.reset();
+        assert_eq!(cache.get_or_init(|| 3), &3);
+    }
+
+    #[test]
+    fn bin_2d_counts() {
+        let points = [(0.0, 0.0), (0.4, 0.2), (1.0, 1.0), (0.9, 0.1)];
+        let bins = bin_2d(&points, 2, None);
+
+        assert_eq!(
+            bins,
+            vec![
+                ([(0.0, 0.0), (0.5, 0.5)], 2.0),
+                ([(0.5, 0.0), (1.0, 0.5)], 1.0),
+                ([(0.5, 0.5), (1.0, 1.0)], 1.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn hex_bin_nearest_center() {
+        // The point (0.5, 0.9) is closer to the center (0.5, 1.0) of the shifted row
+        let points = [(0.0, 0.0), (0.1, 0.1), (0.5, 0.9), (2.0, 2.0)];
+        let bins = hex_bin(&points, 2, None);
+
+        assert_eq!(
+            bins.counts,
+            vec![((0.0, 0.0), 2.0), ((0.5, 1.0), 1.0), ((2.0, 2.0), 1.0)]
+        );
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn lazy_bins_match_bins() {
+        use polars::prelude::*;
+
+        // The points with a missing x position are left out
+        let x: Vec<f64> = (0..200)
+            .map(|i| (i as f64 * 0.37).sin() * 3.0)
+            .map(|x| if x > 2.5 { f64::NAN } else { x })
+            .collect();
+        let y: Vec<f64> = (0..200)
+            .map(|i| (i as f64 * 0.73).cos() * 2.0 - 1.0)
+            .collect();
+        let data = df!("x" => &x, "y" => &y).unwrap().lazy();
+        let points = finite_pairs(&x, &y);
+        let positions = (col("x"), col("y"));
+
+        for binwidth in [None, Some((0.5, 0.3))] {
+            assert_eq!(
+                bin_2d_lazy(&data, positions.clone(), 7, binwidth),
+                bin_2d(&points, 7, binwidth)
+            );
+            assert_eq!(
+                hex_bin_lazy(&data, positions.clone(), 7, binwidth),
+                hex_bin(&points, 7, binwidth)
+            );
+        }
+
+        let empty = data.filter(col("x").gt(lit(10.0)));
+        assert_eq!(bin_2d_lazy(&empty, positions.clone(), 7, None), vec![]);
+        assert_eq!(
+            hex_bin_lazy(&empty, positions, 7, None),
+            hex_bin(&[], 7, None)
+        );
+    }
+
+    #[test]
+    fn kernels_integrate_to_one() {
+        let kernels = [
+            Kernel::Gaussian,
+            Kernel::Rectangular,
+            Kernel::Triangular,
+            Kernel::Epanechnikov,
+            Kernel::Biweight,
+            Kernel::Cosine,
+            Kernel::Optcosine,
+        ];
+
+        for kernel in kernels {
+            let step = 0.001;
+            let total: f64 = (-5000..=5000)
+                .map(|i| kernel.density(i as f64 * step, 0.5) * step)
+                .sum();
+
+            assert!((total - 1.0).abs() < 1e-3, "{kernel:?}: {total}");
+        }
+    }
+
+    #[test]
+    fn bandwidth_silverman() {
+        // sd = 1.58, IQR / 1.34 = 1.49
+        let values = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let expected = 0.9 * (2.0 / 1.34) * 5f64.powf(-0.2);
+
+        assert!((bandwidth_nrd0(&values) - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn kde_2d_peak() {
+        // The grid nodes are 0.1 apart, and (0, 0) is a node
+        let points = [(-5.0, -5.0), (0.0, 0.0), (5.0, 5.0)];
+        let grid = kde_2d(&points, 101, (0.5, 0.5));
+
+        let peak = grid.at(50, 50);
+        let expected = 1.0 / 3.0 / (2.0 * std::f64::consts::PI * 0.25);
+        assert!((peak - expected).abs() < 1e-9, "{peak} != {expected}");
+    }
+
+    #[test]
+    fn grid_from_points() {
+        let grid = Grid::from_points(
+            &[2.0, 1.0, 2.0, 1.0, 3.0],
+            &[0.0, 0.0, 1.0, 1.0, 0.0],
+            &[2.0, 1.0, 4.0, 3.0, 5.0],
+        );
+
+        assert_eq!(grid.x, [1.0, 2.0, 3.0]);
+        assert_eq!(grid.y, [0.0, 1.0]);
+        assert_eq!(grid.z[..5], [1.0, 2.0, 5.0, 3.0, 4.0]);
+        assert!(grid.z[5].is_nan());
+
+        // Only the first cell is complete
+        assert_eq!(contour_lines(&grid, 2.5).len(), 1);
+    }
+
+    #[test]
+    fn contour_of_cone() {
+        let axis: Vec<f64> = (0..11).map(|i| i as f64 - 5.0).collect();
+        let z = axis
+            .iter()
+            .flat_map(|y| axis.iter().map(move |x| 10.0 - (x * x + y * y).sqrt()))
+            .collect();
+        let grid = Grid {
+            x: axis.clone(),
+            y: axis,
+            z,
+        };
+
+        let paths = contour_lines(&grid, 7.0);
+        assert_eq!(paths.len(), 1);
+
+        let path = &paths[0];
+        assert_eq!(path.first(), path.last());
+        for (x, y) in path {
+            assert!(((x * x + y * y).sqrt() - 3.0).abs() < 0.2);
+        }
+
+        let area: f64 = contour_band(&grid, 7.0, f64::INFINITY)
+            .iter()
+            .map(|polygon| {
+                (0..polygon.len())
+                    .map(|k| {
+                        let (p, q) = (polygon[k], polygon[(k + 1) % polygon.len()]);
+                        p.0 * q.1 - q.0 * p.1
+                    })
+                    .sum::<f64>()
+                    / 2.0
+            })
+            .sum();
+        assert!((area - std::f64::consts::PI * 9.0).abs() < 1.0, "{area}");
+    }
+}
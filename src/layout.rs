@@ -0,0 +1,733 @@
+//! # Layout module
+//!
+//! Layouts combine several plots into one figure, like `patchwork` in R:
+//! `p1 | p2` puts two plots side by side, `p1 / p2` puts `p1` above `p2`, and
+//! [`wrap_plots!`](crate::wrap_plots!) arranges plots in a grid. The options of a
+//! layout, e.g. the relative sizes, the shared guides and the tags of the
+//! plots, are added with [`plot_layout!`](crate::plot_layout!).
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use gongbi::*;
+//! # fn main() -> anyhow::Result<()> {
+//! # let mpg = gongbi::data::polars::read_csv("examples/mpg.csv")?;
+//! let p1 = plot!(mpg.clone(), aes!(displ, hwy)) + geom_point!();
+//! let p2 = plot!(mpg.clone(), aes!(hwy)) + geom_density!();
+//! let p3 = plot!(mpg, aes!(cyl, fill = drv)) + geom_bar!();
+//!
+//! let figure = (p1 | p2) / p3 + plot_layout!(heights = [2.0, 1.0], tag_levels = "A");
+//!
+//! figure.to_svg("figure.svg", (1024, 1024))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! As `+` binds tighter than `|`, a layout made with `|` must be in parentheses
+//! before adding [`plot_layout!`](crate::plot_layout!).
+
+use std::{
+    ops::{Add, BitOr, Div},
+    path::PathBuf,
+};
+
+use derive_builder::Builder;
+use plotters::{
+    coord::Shift,
+    prelude::{DrawingArea, DrawingBackend},
+};
+
+use crate::{guide, layer::Layer, Plot};
+
+/// How the guides of the plots are drawn in a layout
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Guides {
+    /// Each plot draws its own guides
+    #[default]
+    Keep,
+
+    /// The guides of all plots are drawn once on the right of the layout
+    Collect,
+}
+
+impl std::str::FromStr for Guides {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keep" => Ok(Guides::Keep),
+            "collect" => Ok(Guides::Collect),
+            _ => Err(format!("Unsupported guides: {s}")),
+        }
+    }
+}
+
+impl From<&str> for Guides {
+    fn from(value: &str) -> Self {
+        value.parse().unwrap()
+    }
+}
+
+/// The sequence of the tags of the plots in a layout
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TagLevels {
+    /// Uppercase letters: A, B, C, ...
+    Upper,
+
+    /// Lowercase letters: a, b, c, ...
+    Lower,
+
+    /// Numbers: 1, 2, 3, ...
+    Numeric,
+
+    /// Uppercase roman numerals: I, II, III, ...
+    UpperRoman,
+
+    /// Lowercase roman numerals: i, ii, iii, ...
+    LowerRoman,
+}
+
+impl TagLevels {
+    /// Get the tag of the `i`-th plot, starting from 0
+    pub fn tag(&self, i: usize) -> String {
+        match self {
+            TagLevels::Upper => letters(i),
+            TagLevels::Lower => letters(i).to_lowercase(),
+            TagLevels::Numeric => (i + 1).to_string(),
+            TagLevels::UpperRoman => roman(i + 1),
+            TagLevels::LowerRoman => roman(i + 1).to_lowercase(),
+        }
+    }
+}
+
+impl std::str::FromStr for TagLevels {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "A" => Ok(TagLevels::Upper),
+            "a" => Ok(TagLevels::Lower),
+            "1" => Ok(TagLevels::Numeric),
+            "I" => Ok(TagLevels::UpperRoman),
+            "i" => Ok(TagLevels::LowerRoman),
+            _ => Err(format!("Unsupported tag levels: {s}")),
+        }
+    }
+}
+
+impl From<&str> for TagLevels {
+    fn from(value: &str) -> Self {
+        value.parse().unwrap()
+    }
+}
+
+/// Get the `i`-th sequence of uppercase letters: A, ..., Z, AA, AB, ...
+fn letters(i: usize) -> String {
+    let mut i = i + 1;
+    let mut letters = vec![];
+    while i > 0 {
+        i -= 1;
+        letters.push((b'A' + (i % 26) as u8) as char);
+        i /= 26;
+    }
+
+    letters.into_iter().rev().collect()
+}
+
+/// Get the uppercase roman numeral of a positive number
+fn roman(n: usize) -> String {
+    const NUMERALS: [(usize, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+
+    let mut n = n;
+    let mut roman = String::new();
+    for (value, numeral) in NUMERALS {
+        while n >= value {
+            roman.push_str(numeral);
+            n -= value;
+        }
+    }
+
+    roman
+}
+
+/// # Plot layout
+///
+/// The options of a [`Layout`], added to it with the `+` operator.
+///
+/// To create the options, [`plot_layout!`](crate::plot_layout!) and [`PlotLayout::builder`] can be used.
+#[derive(Clone, Debug, Default, PartialEq, Builder)]
+#[builder(default, setter(into, strip_option))]
+pub struct PlotLayout {
+    /// The number of columns of the grid.
+    #[builder(setter(into = false))]
+    pub ncol: Option<usize>,
+
+    /// The number of rows of the grid.
+    #[builder(setter(into = false))]
+    pub nrow: Option<usize>,
+
+    /// The relative widths of the columns, repeated if there are more columns.
+    pub widths: Option<Vec<f64>>,
+
+    /// The relative heights of the rows, repeated if there are more rows.
+    pub heights: Option<Vec<f64>>,
+
+    /// Whether the guides of the plots are collected on the right of the layout.
+    pub guides: Option<Guides>,
+
+    /// The tags of the plots, in the order they are added to the layout.
+    ///
+    /// The tag set by [`labs!`](crate::labs!) on a plot is kept.
+    pub tag_levels: Option<TagLevels>,
+}
+
+impl PlotLayout {
+    /// Create a [`PlotLayout`] via the builder pattern.
+    pub fn builder() -> PlotLayoutBuilder {
+        PlotLayoutBuilder::default()
+    }
+}
+
+impl Add for PlotLayout {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            ncol: self.ncol.or(rhs.ncol),
+            nrow: self.nrow.or(rhs.nrow),
+            widths: self.widths.or(rhs.widths),
+            heights: self.heights.or(rhs.heights),
+            guides: self.guides.or(rhs.guides),
+            tag_levels: self.tag_levels.or(rhs.tag_levels),
+        }
+    }
+}
+
+/// # plot_layout!: Construct the options of a [`Layout`]
+///
+/// This macro is used to set the options of a [`Layout`] in a more concise way like `patchwork`.
+/// It is a wrapper around [`PlotLayout::builder`].
+///
+/// ## Usage
+///
+/// ```ignore
+/// layout + plot_layout!(
+///     ncol = <usize>,
+///     nrow = <usize>,
+///     widths = [<f64>, ...],
+///     heights = [<f64>, ...],
+///     guides = <"keep" | "collect">,
+///     tag_levels = <"A" | "a" | "1" | "I" | "i">,
+/// )
+/// ```
+///
+/// ### Arguments
+///
+/// - `ncol`, `nrow`: The dimensions of the grid. By default, `|` lays out a row, `/` a column,
+///   and [`wrap_plots!`](crate::wrap_plots!) a grid as square as possible. Rows are added if
+///   the grid is too small for all plots.
+/// - `widths`, `heights`: The relative sizes of the columns and the rows.
+/// - `guides`: `"collect"` draws the guides of all plots once on the right of the layout,
+///   without duplicates.
+/// - `tag_levels`: The sequence of the tags of the plots, e.g. `"A"` for A, B, C, ...
+#[macro_export]
+macro_rules! plot_layout {
+    ($($arg: ident = $val: expr),* $(,)?) => {
+        $crate::layout::PlotLayout::builder()
+            $(.$arg($val))*
+            .build()
+            .unwrap()
+    }
+}
+
+/// How the items of a layout are arranged without `ncol` or `nrow`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Direction {
+    /// A single row, from `|`
+    Row,
+
+    /// A single column, from `/`
+    Column,
+
+    /// A grid as square as possible, from [`wrap_plots!`](crate::wrap_plots!)
+    Wrap,
+}
+
+/// An item of a layout
+#[derive(Clone, Debug)]
+enum Item {
+    Plot(Box<Plot>),
+    Layout(Layout),
+}
+
+/// # Layout: Several plots in one figure
+///
+/// The plots and the nested layouts are arranged in a grid, filled row by row.
+///
+/// A layout is created by combining plots with `|` and `/`, or with [`wrap_plots!`](crate::wrap_plots!).
+/// Finally, call [`Layout::to_svg`] or [`Layout::to_png`] to save the figure to a file.
+#[derive(Clone, Debug)]
+pub struct Layout {
+    /// The plots and the nested layouts
+    items: Vec<Item>,
+
+    /// The default arrangement of the items
+    direction: Direction,
+
+    /// The options of the layout
+    options: PlotLayout,
+}
+
+impl From<Plot> for Layout {
+    fn from(plot: Plot) -> Self {
+        Layout {
+            items: vec![Item::Plot(Box::new(plot))],
+            direction: Direction::Row,
+            options: PlotLayout::default(),
+        }
+    }
+}
+
+impl Layout {
+    /// Arrange plots or layouts in a grid
+    ///
+    /// See also [`wrap_plots!`](crate::wrap_plots!).
+    pub fn wrap<I, L>(items: I) -> Self
+    where
+        I: IntoIterator<Item = L>,
+        L: Into<Layout>,
+    {
+        Layout {
+            items: items
+                .into_iter()
+                .map(|item| item.into().into_item())
+                .collect(),
+            direction: Direction::Wrap,
+            options: PlotLayout::default(),
+        }
+    }
+
+    /// Get the layout as an item of another layout, a single plot being unwrapped
+    fn into_item(mut self) -> Item {
+        if self.items.len() == 1 && self.options == PlotLayout::default() {
+            return self.items.pop().unwrap();
+        }
+
+        Item::Layout(self)
+    }
+
+    /// Append a layout in the direction, extending this layout if it is already arranged in the direction
+    fn stack(self, rhs: Layout, direction: Direction) -> Self {
+        let mut items = if self.direction == direction && self.options == PlotLayout::default() {
+            self.items
+        } else {
+            vec![self.into_item()]
+        };
+        items.push(rhs.into_item());
+
+        Layout {
+            items,
+            direction,
+            options: PlotLayout::default(),
+        }
+    }
+
+    /// Get the number of rows and columns of the grid
+    ///
+    /// Rows are added if the grid given by `nrow` and `ncol` is too small for all items.
+    fn dims(&self) -> (usize, usize) {
+        let n = self.items.len().max(1);
+
+        match (self.options.nrow, self.options.ncol) {
+            (Some(nrow), Some(ncol)) => (nrow.max(n.div_ceil(ncol.max(1))), ncol.max(1)),
+            (Some(nrow), None) => (nrow.max(1), n.div_ceil(nrow.max(1))),
+            (None, Some(ncol)) => (n.div_ceil(ncol.max(1)), ncol.max(1)),
+            (None, None) => match self.direction {
+                Direction::Row => (1, n),
+                Direction::Column => (n, 1),
+                Direction::Wrap => {
+                    let ncol = (n as f64).sqrt().ceil() as usize;
+                    (n.div_ceil(ncol), ncol)
+                }
+            },
+        }
+    }
+
+    /// Get the plots of the layout, with the plots of the nested layouts, in order
+    fn plots(&self) -> Vec<&Plot> {
+        self.items
+            .iter()
+            .flat_map(|item| match item {
+                Item::Plot(plot) => vec![plot.as_ref()],
+                Item::Layout(layout) => layout.plots(),
+            })
+            .collect()
+    }
+
+    /// Get the layout with the tags of the plots set from the tag levels
+    ///
+    /// The plots of the nested layouts continue the sequence of the tags.
+    fn tagged(&self) -> Self {
+        let mut layout = self.clone();
+        layout.set_tags(None, &mut 0);
+
+        layout
+    }
+
+    fn set_tags(&mut self, levels: Option<TagLevels>, next: &mut usize) {
+        let levels = levels.or(self.options.tag_levels);
+
+        for item in &mut self.items {
+            match item {
+                Item::Plot(plot) => {
+                    let Some(levels) = levels else {
+                        continue;
+                    };
+
+                    if plot.label.tag.is_none() {
+                        plot.label.tag = Some(levels.tag(*next));
+                    }
+                    *next += 1;
+                }
+                Item::Layout(layout) => layout.set_tags(levels, next),
+            }
+        }
+    }
+
+    /// Compute the statistics of the layers of all plots in parallel, ahead of drawing
+    fn compute(&self) {
+        let layers: Vec<&dyn Layer> = self
+            .plots()
+            .into_iter()
+            .flat_map(|plot| &plot.layers)
+            .map(AsRef::as_ref)
+            .collect();
+
+        crate::par_map(&layers, |layer| layer.compute());
+    }
+
+    /// Get the guides of all plots, without duplicates
+    fn guides(&self) -> Vec<guide::Guide> {
+        let mut guides = vec![];
+
+        for plot in self.plots() {
+            for guide in plot.guides(&plot.get_trained_scales()) {
+                if !guides.contains(&guide) {
+                    guides.push(guide);
+                }
+            }
+        }
+
+        guides
+    }
+
+    /// Draw the layout on the area, each plot being drawn on its cell by `draw_plot`
+    ///
+    /// If `guides` is not set, the guides are collected by a parent layout.
+    fn draw<'a, DB, F>(
+        &'a self,
+        area: &DrawingArea<DB, Shift>,
+        guides: bool,
+        draw_plot: &F,
+    ) -> anyhow::Result<()>
+    where
+        DB: DrawingBackend,
+        <DB as plotters::prelude::DrawingBackend>::ErrorType: 'static,
+        F: Fn(&'a Plot, &DrawingArea<DB, Shift>, bool) -> anyhow::Result<()>,
+    {
+        let mut area = area.clone();
+        let collect = guides && self.options.guides == Some(Guides::Collect);
+
+        if collect {
+            let collected = self.guides();
+            if !collected.is_empty() {
+                let (w, _) = area.dim_in_pixel();
+                let (items_area, guide_area) =
+                    area.split_horizontally(w.saturating_sub(guide::GUIDE_WIDTH));
+
+                guide::draw_guides(&guide_area, &collected)?;
+                area = items_area;
+            }
+        }
+
+        let (nrow, ncol) = self.dims();
+        let (w, h) = area.dim_in_pixel();
+        let cells = area.split_by_breakpoints(
+            breakpoints(w, self.options.widths.as_deref(), ncol),
+            breakpoints(h, self.options.heights.as_deref(), nrow),
+        );
+
+        for (item, cell) in self.items.iter().zip(cells) {
+            match item {
+                Item::Plot(plot) => draw_plot(plot, &cell, guides && !collect)?,
+                Item::Layout(layout) => layout.draw(&cell, guides && !collect, draw_plot)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Save the layout to an SVG file
+    #[cfg(feature = "svg")]
+    pub fn to_svg<P>(&self, file_path: P, size: (u32, u32)) -> anyhow::Result<()>
+    where
+        P: Into<PathBuf>,
+    {
+        use plotters::prelude::*;
+
+        let file_path = file_path.into();
+        let layout = self.tagged();
+        layout.compute();
+
+        let root = SVGBackend::new(&file_path, size).into_drawing_area();
+
+        root.fill(&WHITE)?;
+        layout.draw(&root, true, &|plot, area, guides| {
+            plot.draw_svg(area, guides)
+        })?;
+        root.present()?;
+
+        Ok(())
+    }
+
+    /// Save the layout to a PNG file
+    #[cfg(feature = "png")]
+    pub fn to_png<P>(&self, file_path: P, size: (u32, u32)) -> anyhow::Result<()>
+    where
+        P: Into<PathBuf>,
+    {
+        use plotters::prelude::*;
+
+        let file_path = file_path.into();
+        let layout = self.tagged();
+        layout.compute();
+
+        let root = BitMapBackend::new(&file_path, size).into_drawing_area();
+
+        root.fill(&WHITE)?;
+        layout.draw(&root, true, &|plot, area, guides| {
+            plot.draw_png(area, guides)
+        })?;
+        root.present()?;
+
+        Ok(())
+    }
+
+    /// Save the layout to a PDF file
+    ///
+    /// The size of the page is physical, e.g. `PageSize::A4.landscape()`, see [`pdf::PageSize`](crate::pdf::PageSize).
+    #[cfg(feature = "pdf")]
+    pub fn to_pdf<P, S>(&self, file_path: P, size: S) -> anyhow::Result<()>
+    where
+        P: Into<PathBuf>,
+        S: Into<crate::pdf::PageSize>,
+    {
+        use plotters::prelude::*;
+
+        let layout = self.tagged();
+        layout.compute();
+
+        let mut svg = String::new();
+        {
+            let root = SVGBackend::with_string(&mut svg, size.into().pixels()).into_drawing_area();
+
+            root.fill(&WHITE)?;
+            layout.draw(&root, true, &|plot, area, guides| {
+                plot.draw_svg(area, guides)
+            })?;
+            root.present()?;
+        }
+
+        std::fs::write(file_path.into(), crate::pdf::svg_to_pdf(&svg)?)?;
+
+        Ok(())
+    }
+
+    /// Save the layout
+    ///
+    /// The file format is determined by the file extension:
+    /// - `.svg`: SVG file, with the `svg` feature
+    /// - `.png`: PNG file, with the `png` feature
+    /// - `.pdf`: PDF file, with the `pdf` feature, whose page is the size in pixels at 96 pixels per inch
+    /// - Otherwise, an error is returned
+    pub fn save<P>(&self, file_path: P, size: (u32, u32)) -> anyhow::Result<()>
+    where
+        P: Into<PathBuf>,
+    {
+        let file_path = file_path.into();
+        match file_path.extension().and_then(|ext| ext.to_str()) {
+            #[cfg(feature = "svg")]
+            Some("svg") => self.to_svg(file_path, size),
+            #[cfg(feature = "png")]
+            Some("png") => self.to_png(file_path, size),
+            #[cfg(feature = "pdf")]
+            Some("pdf") => self.to_pdf(file_path, size),
+            _ => Err(anyhow::anyhow!("Unsupported file format")),
+        }
+    }
+}
+
+/// Split a size into `n` parts by the relative sizes, repeated if there are less than `n`
+///
+/// Returns the positions between the parts.
+fn breakpoints(size: u32, weights: Option<&[f64]>, n: usize) -> Vec<u32> {
+    let weights: Vec<f64> = match weights {
+        Some(weights) if !weights.is_empty() => weights.iter().cycle().take(n).copied().collect(),
+        _ => vec![1.0; n],
+    };
+    let total: f64 = weights.iter().sum();
+
+    weights
+        .iter()
+        .take(n.saturating_sub(1))
+        .scan(0.0, |acc, weight| {
+            *acc += weight;
+            Some((*acc / total * size as f64).round() as u32)
+        })
+        .collect()
+}
+
+/// # wrap_plots!: Arrange plots in a grid
+///
+/// This macro is used to create a [`Layout`] from plots or layouts like `patchwork`.
+/// It is a wrapper around [`Layout::wrap`].
+///
+/// The grid is as square as possible, unless `ncol` or `nrow` is set with [`plot_layout!`](crate::plot_layout!).
+///
+/// ## Usage
+///
+/// ```ignore
+/// wrap_plots!(p1, p2, p3) + plot_layout!(ncol = 3)
+/// ```
+#[macro_export]
+macro_rules! wrap_plots {
+    ($($item: expr),+ $(,)?) => {
+        $crate::layout::Layout::wrap([$($crate::layout::Layout::from($item)),+])
+    };
+}
+
+impl<L> BitOr<L> for Plot
+where
+    L: Into<Layout>,
+{
+    type Output = Layout;
+
+    fn bitor(self, rhs: L) -> Self::Output {
+        Layout::from(self).stack(rhs.into(), Direction::Row)
+    }
+}
+
+impl<L> BitOr<L> for Layout
+where
+    L: Into<Layout>,
+{
+    type Output = Layout;
+
+    fn bitor(self, rhs: L) -> Self::Output {
+        self.stack(rhs.into(), Direction::Row)
+    }
+}
+
+impl<L> Div<L> for Plot
+where
+    L: Into<Layout>,
+{
+    type Output = Layout;
+
+    fn div(self, rhs: L) -> Self::Output {
+        Layout::from(self).stack(rhs.into(), Direction::Column)
+    }
+}
+
+impl<L> Div<L> for Layout
+where
+    L: Into<Layout>,
+{
+    type Output = Layout;
+
+    fn div(self, rhs: L) -> Self::Output {
+        self.stack(rhs.into(), Direction::Column)
+    }
+}
+
+impl Add<PlotLayout> for Layout {
+    type Output = Self;
+
+    fn add(self, rhs: PlotLayout) -> Self::Output {
+        // The options added last take precedence
+        let options = rhs + self.options;
+
+        Layout { options, ..self }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plot() -> Plot {
+        Plot::builder().build().unwrap()
+    }
+
+    #[test]
+    fn operators_nest_layouts() {
+        let layout = plot() | plot() | plot();
+        assert_eq!(layout.items.len(), 3);
+        assert_eq!(layout.dims(), (1, 3));
+
+        let layout = (plot() | plot()) / plot();
+        assert_eq!(layout.dims(), (2, 1));
+        assert!(matches!(&layout.items[0], Item::Layout(row) if row.dims() == (1, 2)));
+        assert!(matches!(layout.items[1], Item::Plot(_)));
+
+        let layout = wrap_plots!(plot(), plot(), plot(), plot(), plot());
+        assert_eq!(layout.dims(), (2, 3));
+
+        let layout = layout + plot_layout!(ncol = 1);
+        assert_eq!(layout.dims(), (5, 1));
+
+        let layout = wrap_plots!(plot(), plot(), plot()) + plot_layout!(ncol = 1, nrow = 1);
+        assert_eq!(layout.dims(), (3, 1));
+
+        let layout = wrap_plots!(plot(), plot(), plot()) + plot_layout!(ncol = 2, nrow = 3);
+        assert_eq!(layout.dims(), (3, 2));
+    }
+
+    #[test]
+    fn tags_follow_plots() {
+        let layout =
+            (plot() | (plot() + crate::labs!(tag = "X"))) / plot() + plot_layout!(tag_levels = "a");
+        let tags: Vec<_> = layout
+            .tagged()
+            .plots()
+            .iter()
+            .map(|plot| plot.label.tag.clone().unwrap())
+            .collect();
+
+        assert_eq!(tags, ["a", "X", "c"]);
+        assert_eq!(TagLevels::Upper.tag(27), "AB");
+        assert_eq!(TagLevels::LowerRoman.tag(13), "xiv");
+    }
+
+    #[test]
+    fn breakpoints_of_sizes() {
+        assert_eq!(breakpoints(300, None, 3), [100, 200]);
+        assert_eq!(breakpoints(300, Some(&[2.0, 1.0]), 2), [200]);
+        assert_eq!(breakpoints(400, Some(&[1.0, 3.0]), 3), [80, 320]);
+        assert!(breakpoints(100, None, 1).is_empty());
+    }
+}
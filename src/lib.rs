@@ -11,59 +11,120 @@
 //! Like `ggplot2`, you can start with [`plot!`], supply a data source and aesthetics ([`aes!`]), and add layers ([`geom_point!`] or [`geom_line!`]) and labels ([`labs!`]).
 //!
 //! ```no_run
-//! # use std::path::PathBuf;
 //! # use gongbi::*;
-//! # use polars::prelude::*;
 //! # fn main() -> anyhow::Result<()> {
-//! let mpg = CsvReadOptions::default()
-//!     .with_has_header(true)
-//!     .try_into_reader_with_file_path(Some("examples/mpg.csv".into()))?
-//!     .finish()?;
-//!
-//! let plot = plot!(mpg, aes!("displ", "hwy"))
+//! let plot = plot!(csv = "examples/mpg.csv", mapping = aes!("displ", "hwy"))
 //!     + geom_point!()
-//!     + labs!(caption = "Demo of geom_point");
+//!     + labs!(title = "Demo of geom_point");
 //!
 //! plot.to_svg("gongbi.svg", (1024, 768))?;
 //! # Ok(())
 //! # }
 //! ```
 //!
+//! The data source can be any [`data::Data`], e.g. a `polars` `DataFrame`, a
+//! `HashMap` of columns, or a `Vec` of structs with `#[derive(Data)]`.
+//!
+//! ## Threads
+//!
+//! Plots and layouts are `Send` and `Sync`, as the data sources and the layers are.
+//! A plot can be built once, shared in an `Arc`, and rendered from several
+//! threads, e.g. for the requests of a web server:
+//!
+//! ```no_run
+//! # use gongbi::*;
+//! # use std::{sync::Arc, thread};
+//! # fn main() -> anyhow::Result<()> {
+//! let plot = Arc::new(plot!(csv = "examples/mpg.csv", mapping = aes!(displ, hwy)) + geom_point!());
+//!
+//! let handles: Vec<_> = (0..4)
+//!     .map(|i| {
+//!         let plot = Arc::clone(&plot);
+//!         thread::spawn(move || plot.to_svg(format!("plot_{i}.svg"), (800, 600)))
+//!     })
+//!     .collect();
+//!
+//! for handle in handles {
+//!     handle.join().unwrap()?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ## Features
+//!
+//! - `polars` (default): [`data::Data`] for `polars` `DataFrame` and `LazyFrame`, and `polars` expressions in [`aes!`].
+//! - `csv` (default): read the data from CSV files, e.g. `plot!(csv = "mpg.csv")` or [`Plot::from_csv`].
+//! - `parquet`: read the data from Parquet files, e.g. `Plot::from_parquet`.
+//! - `arrow`: [`data::Data`] for Arrow `RecordBatch`.
+//! - `derive` (default): `#[derive(Data)]` to plot vectors and slices of structs.
+//! - `svg` (default): [`Plot::to_svg`], saving plots as SVG files.
+//! - `png` (default): [`Plot::to_png`], saving plots as PNG files.
+//! - `pdf`: `Plot::to_pdf`, saving plots as PDF files of a physical size, with the text in embedded fonts.
+//! - `parallel` (default): compute the statistics and ranges of the layers in parallel with `rayon`.
+//! - `raster` (default): [`geom_raster!`], embedded in SVG files as images.
+//! - `ttf` (default): measure text with the system fonts. Without it, the size
+//!   of text is estimated roughly, e.g. in SVG files for WASM or embedded builds.
+//!
+//! At least one of `svg` and `png` is required. For a slim build, disable the
+//! default features and pick the needed ones:
+//!
+//! ```toml
+//! gongbi = { version = "0.1", default-features = false, features = ["derive", "svg"] }
+//! ```
+//!
 
 #![warn(missing_docs)]
 
+#[cfg(not(any(feature = "svg", feature = "png")))]
+compile_error!("At least one of the `svg` and `png` features is required to draw plots");
+
 use std::{
     ops::{Add, Range},
     path::PathBuf,
-    rc::Rc,
+    sync::Arc,
 };
 
 use derive_builder::Builder;
 use plotters::{
     chart::ChartContext,
-    coord::types::RangedCoordf64,
-    prelude::{Cartesian2d, DrawingBackend},
+    coord::{types::RangedCoordf64, Shift},
+    prelude::{Cartesian2d, DrawingArea, DrawingBackend},
 };
 
 pub mod aes;
+pub mod coord;
 pub mod data;
+mod guide;
 pub mod label;
 pub mod layer;
+pub mod layout;
+#[cfg(feature = "pdf")]
+pub mod pdf;
+pub mod scale;
+
+// Let `#[derive(Data)]` refer to `::gongbi` inside this crate as well
+extern crate self as gongbi;
+
+#[cfg(feature = "derive")]
+pub use gongbi_derive::Data;
 
 /// # Plot: The main object to create a plot
 ///
 /// To create a plot, two steps are required:
 /// 1. Create a `Plot` object via [`plot!`] or [`Plot::builder`]
-/// 2. Add layers (e.g. [`geom_point!`], [`geom_line!`]) and labels (e.g. [`labs!`]) to the plot
+/// 2. Add layers (e.g. [`geom_point!`], [`geom_line!`]), scales (e.g. [`scale_fill_gradient!`] or [`scale_y_continuous!`]), coordinates (e.g. [`coord_polar!`] or [`coord_flip!`]) and labels (e.g. [`labs!`]) to the plot
 ///
 /// Finally, call [`Plot::to_svg`] or [`Plot::to_png`] to save the plot to a file
+///
+/// Several plots can be combined into one figure with `|` and `/`, see [`layout::Layout`].
 #[derive(Clone, Debug, Builder)]
 pub struct Plot {
     /// The data source for the plot
     ///
     /// If not specified, the data source must be supplied in each layer
     #[builder(default, setter(custom))]
-    data: Option<Rc<dyn data::Data>>,
+    data: Option<Arc<dyn data::Data>>,
 
     /// The default aesthetics mapping for the plot
     ///
@@ -86,6 +147,22 @@ pub struct Plot {
     /// This is used to add titles, captions, and other labels to the plot
     #[builder(default, setter(skip))]
     label: label::Label,
+
+    /// The scales of the plot
+    ///
+    /// This is used to control how the data values are mapped to the aesthetics, e.g. the colors of `fill`.
+    ///
+    /// Usually, scales are added using the `+` operator.
+    #[builder(default, setter(skip))]
+    scales: scale::Scales,
+
+    /// The coordinate system of the plot
+    ///
+    /// This is used to map the positions of the data to the chart, e.g. polar coordinates for pie charts.
+    ///
+    /// Usually, the coordinate system is added using the `+` operator.
+    #[builder(default, setter(skip))]
+    coord: coord::Coord,
 }
 
 impl PlotBuilder {
@@ -94,9 +171,45 @@ impl PlotBuilder {
     where
         D: data::Data + 'static,
     {
-        self.data = Some(Some(Rc::new(data)));
+        self.data = Some(Some(Arc::new(data)));
         self
     }
+
+    /// Read the data source for the plot from a CSV file with a header
+    ///
+    /// # Panics
+    ///
+    /// Panics if the file cannot be read, see [`Plot::from_csv`] to handle the error.
+    #[cfg(feature = "csv")]
+    pub fn csv<P>(&mut self, file_path: P) -> &mut Self
+    where
+        P: Into<PathBuf>,
+    {
+        let file_path = file_path.into();
+        let data = data::polars::read_csv(&file_path)
+            .unwrap_or_else(|err| panic!("Failed to read {}: {err}", file_path.display()));
+
+        self.data(data)
+    }
+
+    /// Read the data source for the plot from a Parquet file
+    ///
+    /// Only the columns used by the layers are read, when the plot is drawn.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the file cannot be read, see [`Plot::from_parquet`] to handle the error.
+    #[cfg(feature = "parquet")]
+    pub fn parquet<P>(&mut self, file_path: P) -> &mut Self
+    where
+        P: Into<PathBuf>,
+    {
+        let file_path = file_path.into();
+        let data = data::polars::scan_parquet(&file_path)
+            .unwrap_or_else(|err| panic!("Failed to read {}: {err}", file_path.display()));
+
+        self.data(data)
+    }
 }
 
 impl Plot {
@@ -105,12 +218,79 @@ impl Plot {
         PlotBuilder::default()
     }
 
-    fn get_context_range_2d(&self) -> (Range<f64>, Range<f64>) {
-        // Get the range of the x and y axis
-        let range = self
-            .layers
-            .iter()
-            .map(|layer| layer.range_2d())
+    /// Create a new [`Plot`] object with the data of a CSV file with a header
+    ///
+    /// The mappings are supplied by the layers, e.g. `Plot::from_csv("mpg.csv")? + geom_point!(aes!(displ, hwy))`.
+    #[cfg(feature = "csv")]
+    pub fn from_csv<P>(file_path: P) -> anyhow::Result<Self>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let data = data::polars::read_csv(file_path)?;
+
+        Ok(Plot::builder().data(data).build()?)
+    }
+
+    /// Create a new [`Plot`] object with the data of a Parquet file
+    ///
+    /// Only the columns used by the layers are read, when the plot is drawn.
+    #[cfg(feature = "parquet")]
+    pub fn from_parquet<P>(file_path: P) -> anyhow::Result<Self>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let data = data::polars::scan_parquet(file_path)?;
+
+        Ok(Plot::builder().data(data).build()?)
+    }
+
+    /// Compute the statistics of all layers in parallel, ahead of training the scales and drawing
+    ///
+    /// The layers keep their statistics, so only the draw calls run one after another.
+    pub(crate) fn compute(&self) {
+        par_map(&self.layers, |layer| layer.compute());
+    }
+
+    /// Get the panel showing the range of all layers in the coordinate system
+    ///
+    /// In Cartesian coordinates, the axes are expanded by 2.5% on both sides.
+    /// In polar coordinates, the angle leaves a step between the last and the first positions.
+    fn get_panel(&self) -> coord::Panel {
+        let coord::Coord::Polar(polar) = &self.coord else {
+            let (x_range, y_range) = self.get_context_range_2d();
+
+            return coord::Panel::new(self.coord.clone(), x_range, y_range);
+        };
+
+        let range = self.get_range_2d();
+        let (x_step, y_step) = par_map(&self.layers, |layer| layer.resolution_2d())
+            .into_iter()
+            .flatten()
+            .reduce(|acc, cur| (acc.0.min(cur.0), acc.1.min(cur.1)))
+            .unwrap_or((0.0, 0.0));
+
+        let (x_range, y_range) = match polar.theta() {
+            coord::Theta::X => (
+                (range.0 - x_step / 2.0)..(range.1 + x_step / 2.0),
+                range.2..range.3,
+            ),
+            coord::Theta::Y => (
+                range.0..range.1,
+                (range.2 - y_step / 2.0)..(range.3 + y_step / 2.0),
+            ),
+        };
+
+        coord::Panel::new(self.coord.clone(), x_range, y_range)
+    }
+
+    /// Get the range of the x and y positions of all layers
+    ///
+    /// The ranges are merged per axis. An axis not affected by any layer is centered on zero,
+    /// and a range of a single value is widened by 0.5 on both sides, like in ggplot2.
+    fn get_range_2d(&self) -> (f64, f64, f64, f64) {
+        let (x_min, x_max, y_min, y_max) = par_map(&self.layers, |layer| layer.range_2d())
+            .into_iter()
+            .flatten()
             .reduce(|acc, cur| {
                 (
                     acc.0.min(cur.0),
@@ -119,7 +299,22 @@ impl Plot {
                     acc.3.max(cur.3),
                 )
             })
-            .expect("No layers");
+            .expect("No layers affecting the range of the axes");
+
+        let widen = |min: f64, max: f64| match (min.is_finite() && max.is_finite(), min < max) {
+            (true, true) => (min, max),
+            (true, false) => (min - 0.5, max + 0.5),
+            (false, _) => (-0.5, 0.5),
+        };
+        let (x_min, x_max) = widen(x_min, x_max);
+        let (y_min, y_max) = widen(y_min, y_max);
+
+        (x_min, x_max, y_min, y_max)
+    }
+
+    fn get_context_range_2d(&self) -> (Range<f64>, Range<f64>) {
+        // Get the range of the x and y axis
+        let range = self.get_range_2d();
 
         let x_range_len = range.1 - range.0;
         let y_range_len = range.3 - range.2;
@@ -130,9 +325,282 @@ impl Plot {
         (x_range, y_range)
     }
 
+    /// Get the scales trained on the values of all layers
+    ///
+    /// The limits of a scale are set to the range of the mapped values if not provided.
+    fn get_trained_scales(&self) -> scale::Scales {
+        let mut scales = self.scales.clone();
+
+        let fill_range = par_map(&self.layers, |layer| layer.fill_range())
+            .into_iter()
+            .flatten()
+            .reduce(|acc, cur| (acc.0.min(cur.0), acc.1.max(cur.1)));
+        if let Some(fill_range) = fill_range {
+            scales.fill.train(fill_range);
+        }
+
+        let color_levels = par_map(&self.layers, |layer| layer.color_levels());
+        for (_, levels) in color_levels.into_iter().flatten() {
+            scales.color.train(levels);
+        }
+
+        scales
+    }
+
+    /// Split the area of the guides from the root area
+    ///
+    /// Returns the area of the chart, and the area of the guides if there are any guides.
+    fn split_guide_area<DB>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        guides: &[guide::Guide],
+    ) -> (DrawingArea<DB, Shift>, Option<DrawingArea<DB, Shift>>)
+    where
+        DB: DrawingBackend,
+    {
+        if guides.is_empty() {
+            return (root.clone(), None);
+        }
+
+        let (w, _) = root.dim_in_pixel();
+        let (chart_area, guide_area) =
+            root.split_horizontally(w.saturating_sub(guide::GUIDE_WIDTH));
+
+        (chart_area, Some(guide_area))
+    }
+
+    /// Get the legend of the color scale and the colorbar of the fill scale, if any layer maps `color` or `fill`
+    ///
+    /// If both are present, the legend is above the colorbar.
+    pub(crate) fn guides(&self, scales: &scale::Scales) -> Vec<guide::Guide> {
+        let has_fill = self.layers.iter().any(|layer| layer.fill_range().is_some());
+
+        // Without a colorbar, the legend may show the levels of fill, e.g. of geom_bar
+        let color_label = match has_fill {
+            true => self.label.color.as_deref(),
+            false => self.label.color.as_deref().or(self.label.fill.as_deref()),
+        };
+        let color_title = self
+            .layers
+            .iter()
+            .find_map(|layer| layer.color_levels())
+            .map(|(column, _)| color_label.unwrap_or(column));
+
+        let fill_title = self.label.fill.as_deref().or_else(|| {
+            self.layers
+                .iter()
+                .find_map(|layer| layer.fill_title())
+                .or(match &self.mapping.fill {
+                    Some(aes::color::ColorMapping::Column(column)) => Some(column.as_str()),
+                    _ => None,
+                })
+        });
+
+        let mut guides = vec![];
+        if let Some(color_title) = color_title {
+            guides.push(guide::Guide::Legend(
+                scales.color.clone(),
+                Some(color_title.to_string()),
+            ));
+        }
+        if has_fill {
+            guides.push(guide::Guide::Colorbar(
+                scales.fill.clone(),
+                fill_title.map(str::to_string),
+            ));
+        }
+
+        guides
+    }
+
+    /// Get the area of the chart, i.e. the panel with its axes
+    ///
+    /// In polar coordinates, the area is the largest square in the area, so that the circles stay round.
+    /// With a fixed aspect ratio, the area is shrunk so that the panel keeps the ratio.
+    fn chart_area<DB>(
+        &self,
+        area: &DrawingArea<DB, Shift>,
+        panel: &coord::Panel,
+    ) -> anyhow::Result<DrawingArea<DB, Shift>>
+    where
+        DB: DrawingBackend,
+        <DB as plotters::prelude::DrawingBackend>::ErrorType: 'static,
+    {
+        if panel.is_linear() {
+            let Some(aspect_ratio) = panel.aspect_ratio() else {
+                return Ok(area.clone());
+            };
+
+            // The size taken by the margins from Plot::build_chart
+            let (w, h) = area.dim_in_pixel();
+            let (margin_w, margin_h) = (10.0, 10.0);
+
+            // The secondary axis adds a label area on the right, or on the top if flipped
+            let (labels_w, labels_h) = match self.scales.y.sec_axis() {
+                Some(_) if panel.is_flipped() => (1.0, 2.0),
+                Some(_) => (2.0, 1.0),
+                None => (1.0, 1.0),
+            };
+
+            // The label areas depend on the size of the area, so the size is refined a few times
+            let (mut chart_w, mut chart_h) = (w as f64, h as f64);
+            for _ in 0..4 {
+                let label_size = (0.1 * chart_w.min(chart_h)).round();
+                let panel_w = w as f64 - margin_w - labels_w * label_size;
+                let panel_h = h as f64 - margin_h - labels_h * label_size;
+
+                (chart_w, chart_h) = if panel_h > aspect_ratio * panel_w {
+                    (
+                        w as f64,
+                        aspect_ratio * panel_w + margin_h + labels_h * label_size,
+                    )
+                } else {
+                    (
+                        panel_h / aspect_ratio + margin_w + labels_w * label_size,
+                        h as f64,
+                    )
+                };
+            }
+
+            let (chart_w, chart_h) = (
+                (chart_w.round() as u32).clamp(1, w),
+                (chart_h.round() as u32).clamp(1, h),
+            );
+
+            return Ok(area
+                .clone()
+                .shrink(((w - chart_w) / 2, (h - chart_h) / 2), (chart_w, chart_h)));
+        }
+
+        let area = area.margin(5, 5, 5, 5);
+        let (w, h) = area.dim_in_pixel();
+        let side = w.min(h);
+
+        Ok(area.shrink(((w - side) / 2, (h - side) / 2), (side, side)))
+    }
+
+    /// Draw the tag, the title and the subtitle above the plot, and the caption below it
+    ///
+    /// Like `ggplot2`, the title and the subtitle are aligned to the left, after the tag if any,
+    /// and the caption is aligned to the right. Returns the area left for the chart and the guides.
+    fn draw_titles<DB>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+    ) -> anyhow::Result<DrawingArea<DB, Shift>>
+    where
+        DB: DrawingBackend,
+        <DB as plotters::prelude::DrawingBackend>::ErrorType: 'static,
+    {
+        use plotters::{
+            prelude::*,
+            style::text_anchor::{HPos, Pos, VPos},
+        };
+
+        const PADDING: u32 = 10;
+
+        let label = &self.label;
+        let (w, h) = root.dim_in_pixel();
+
+        let (mut left, mut bottom) = (PADDING, 0);
+        if let Some(tag) = &label.tag {
+            let style = TextStyle::from(("sans-serif", 32, FontStyle::Bold).into_font());
+            let (tag_w, tag_h) = root.estimate_text_size(tag, &style)?;
+
+            root.draw_text(tag, &style, (PADDING as i32, PADDING as i32))?;
+            left += tag_w + PADDING;
+            bottom = PADDING + tag_h;
+        }
+
+        let mut top = PADDING;
+        let titles = [
+            (
+                &label.title,
+                TextStyle::from(("sans-serif", 32).into_font()),
+            ),
+            (
+                &label.subtitle,
+                TextStyle::from(("sans-serif", 24).into_font()),
+            ),
+        ];
+        for (text, style) in titles {
+            let Some(text) = text else {
+                continue;
+            };
+            let (_, text_h) = root.estimate_text_size(text, &style)?;
+
+            root.draw_text(text, &style, (left as i32, top as i32))?;
+            top += text_h + PADDING / 2;
+            bottom = bottom.max(top);
+        }
+
+        let mut area = match bottom {
+            0 => root.clone(),
+            _ => root.split_vertically(bottom + PADDING).1,
+        };
+
+        if let Some(caption) = &label.caption {
+            let style = TextStyle::from(("sans-serif", 16).into_font())
+                .pos(Pos::new(HPos::Right, VPos::Bottom));
+            let (_, caption_h) = root.estimate_text_size(caption, &style)?;
+
+            root.draw_text(
+                caption,
+                &style,
+                ((w - PADDING) as i32, (h - PADDING) as i32),
+            )?;
+
+            let (_, area_h) = area.dim_in_pixel();
+            area = area
+                .split_vertically(area_h.saturating_sub(caption_h + 2 * PADDING))
+                .0;
+        }
+
+        Ok(area)
+    }
+
+    /// Build the chart on the area from [`Plot::chart_area`]
+    fn build_chart<'a, DB>(
+        &self,
+        area: &DrawingArea<DB, Shift>,
+        panel: &coord::Panel,
+    ) -> anyhow::Result<ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>>
+    where
+        DB: DrawingBackend,
+        <DB as plotters::prelude::DrawingBackend>::ErrorType: 'static,
+    {
+        use plotters::prelude::*;
+
+        let mut chart = ChartBuilder::on(area);
+
+        if panel.is_linear() {
+            chart
+                .margin(5)
+                .x_label_area_size(10.percent())
+                .y_label_area_size(10.percent());
+
+            if self.scales.y.sec_axis().is_some() {
+                if panel.is_flipped() {
+                    chart.top_x_label_area_size(10.percent());
+                } else {
+                    chart.right_y_label_area_size(10.percent());
+                }
+            }
+        }
+
+        let (x_range, y_range) = panel.chart_range();
+
+        Ok(chart.build_cartesian_2d(x_range, y_range)?)
+    }
+
+    /// Draw the grid and the labels of the axes
+    ///
+    /// In Cartesian coordinates, the mesh is drawn by a chart over the same panel as the chart from
+    /// [`Plot::build_chart`], whose axes follow the breaks and the labels of the position scales.
     fn draw_mesh<DB>(
         &self,
         chart: &mut ChartContext<'_, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+        area: &DrawingArea<DB, Shift>,
+        panel: &coord::Panel,
     ) -> anyhow::Result<()>
     where
         DB: DrawingBackend,
@@ -140,25 +608,60 @@ impl Plot {
     {
         use plotters::prelude::*;
 
-        let mut mesh = chart.configure_mesh();
+        if !panel.is_linear() {
+            return panel.draw_polar_grid(chart);
+        }
+
+        // The secondary axis is drawn with the chart, so only its space is left
+        let (w, h) = area.dim_in_pixel();
+        let label_area_size = (0.1 * w.min(h) as f64).round() as u32;
+        let (mut margin_top, mut margin_right) = (5, 5);
+        if self.scales.y.sec_axis().is_some() {
+            if panel.is_flipped() {
+                margin_top += label_area_size;
+            } else {
+                margin_right += label_area_size;
+            }
+        }
+
+        let (x_scale, y_scale) = match panel.is_flipped() {
+            true => (&self.scales.y, &self.scales.x),
+            false => (&self.scales.x, &self.scales.y),
+        };
+        let (x_range, y_range) = panel.chart_range();
+
+        let mut mesh_chart = ChartBuilder::on(area)
+            .margin(5)
+            .margin_top(margin_top)
+            .margin_right(margin_right)
+            .x_label_area_size(label_area_size)
+            .y_label_area_size(label_area_size)
+            .build_cartesian_2d(
+                guide::AxisCoord::new(x_scale, x_range),
+                guide::AxisCoord::new(y_scale, y_range),
+            )?;
+
+        let mut mesh = mesh_chart.configure_mesh();
         mesh.axis_desc_style(("sans-serif", 24).into_font())
             .x_label_style(("sans-serif", 16).into_font())
             .y_label_style(("sans-serif", 16).into_font());
 
-        if let Some(x_label) = &self.label.x {
-            mesh.x_desc(x_label);
-        } else if let Some(x_label) = self.mapping.x {
-            mesh.x_desc(x_label);
-        } else {
-            mesh.x_desc("x");
-        }
+        let x_label = match (&self.label.x, &self.mapping.x) {
+            (Some(x_label), _) => x_label.clone(),
+            (None, Some(x_label)) => x_label.to_string(),
+            (None, None) => "x".to_string(),
+        };
+        let y_label = match (&self.label.y, &self.mapping.y) {
+            (Some(y_label), _) => y_label.clone(),
+            (None, Some(y_label)) => y_label.to_string(),
+            (None, None) => "y".to_string(),
+        };
 
-        if let Some(y_label) = &self.label.y {
-            mesh.y_desc(y_label);
-        } else if let Some(y_label) = self.mapping.y {
-            mesh.y_desc(y_label);
+        // The labels follow the positions to the other axes
+        if panel.is_flipped() {
+            mesh.x_desc(&y_label).y_desc(&x_label);
         } else {
-            mesh.y_desc("y");
+            mesh.x_desc(&x_label).y_desc(&y_label);
         }
 
         mesh.draw()?;
@@ -166,7 +669,59 @@ impl Plot {
         Ok(())
     }
 
+    /// Draw the secondary axis of the y scale if any, then the rest of the chart with `draw`
+    ///
+    /// The secondary axis is on the right of the panel, or on the top if flipped. It takes its
+    /// label area from the chart, so that the axes of the mesh are not drawn there.
+    /// The secondary axis is not drawn in polar coordinates.
+    fn draw_with_secondary_axis<'a, DB, F>(
+        &self,
+        chart: ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+        panel: &coord::Panel,
+        draw: F,
+    ) -> anyhow::Result<()>
+    where
+        DB: DrawingBackend,
+        <DB as plotters::prelude::DrawingBackend>::ErrorType: 'static,
+        F: FnOnce(
+            &mut ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+        ) -> anyhow::Result<()>,
+    {
+        use plotters::prelude::*;
+
+        let mut chart = chart;
+        let Some(sec_axis) = self.scales.y.sec_axis().filter(|_| panel.is_linear()) else {
+            return draw(&mut chart);
+        };
+
+        let coord = guide::SecondaryCoord::new(sec_axis, panel.y_range());
+        let name = sec_axis.name().unwrap_or_default();
+
+        if panel.is_flipped() {
+            let mut chart = chart.set_secondary_coord(coord, panel.x_range());
+            chart
+                .configure_secondary_axes()
+                .axis_desc_style(("sans-serif", 24).into_font())
+                .label_style(("sans-serif", 16).into_font())
+                .x_desc(name)
+                .draw()?;
+
+            draw(&mut chart)
+        } else {
+            let mut chart = chart.set_secondary_coord(panel.x_range(), coord);
+            chart
+                .configure_secondary_axes()
+                .axis_desc_style(("sans-serif", 24).into_font())
+                .label_style(("sans-serif", 16).into_font())
+                .y_desc(name)
+                .draw()?;
+
+            draw(&mut chart)
+        }
+    }
+
     /// Save the plot to an SVG file
+    #[cfg(feature = "svg")]
     pub fn to_svg<P>(&self, file_path: P, size: (u32, u32)) -> anyhow::Result<()>
     where
         P: Into<PathBuf>,
@@ -178,26 +733,47 @@ impl Plot {
         let root = SVGBackend::new(&file_path, size).into_drawing_area();
 
         root.fill(&WHITE)?;
+        self.draw_svg(&root, true)?;
+        root.present()?;
 
-        let mut chart = ChartBuilder::on(&root);
+        Ok(())
+    }
 
-        chart
-            .margin(5)
-            .x_label_area_size(10.percent())
-            .y_label_area_size(10.percent());
+    /// Draw the plot on an area of an SVG file
+    ///
+    /// If `guides` is not set, the guides are left to the caller, e.g. collected by a [`layout::Layout`].
+    #[cfg(feature = "svg")]
+    pub(crate) fn draw_svg<'a>(
+        &'a self,
+        root: &DrawingArea<plotters::prelude::SVGBackend<'a>, Shift>,
+        guides: bool,
+    ) -> anyhow::Result<()> {
+        self.compute();
 
-        if let Some(caption) = &self.label.caption {
-            chart.caption(caption, ("sans-serif", 32).into_font());
-        }
+        let scales = self.get_trained_scales();
+        let panel = self.get_panel();
+        let guides = match guides {
+            true => self.guides(&scales),
+            false => vec![],
+        };
+        let plot_area = self.draw_titles(root)?;
+        let (chart_area, guide_area) = self.split_guide_area(&plot_area, &guides);
+        let chart_area = self.chart_area(&chart_area, &panel)?;
 
-        let (x_range, y_range) = self.get_context_range_2d();
+        let chart = self.build_chart(&chart_area, &panel)?;
 
-        let mut chart = chart.build_cartesian_2d(x_range, y_range)?;
+        self.draw_with_secondary_axis(chart, &panel, |chart| {
+            self.draw_mesh(chart, &chart_area, &panel)?;
 
-        self.draw_mesh(&mut chart)?;
+            for layer in &self.layers {
+                layer.draw_svg_2d(chart, &panel, &scales)?;
+            }
 
-        for layer in &self.layers {
-            layer.draw_svg_2d(&mut chart)?;
+            Ok(())
+        })?;
+
+        if let Some(guide_area) = guide_area {
+            guide::draw_guides(&guide_area, &guides)?;
         }
 
         // chart
@@ -206,12 +782,11 @@ impl Plot {
         //     .label_font(("sans-serif", 16).into_font())
         //     .draw()?;
 
-        root.present()?;
-
         Ok(())
     }
 
-    /// Save the plot to a PNG file
+    /// Save the plot to an PNG file
+    #[cfg(feature = "png")]
     pub fn to_png<P>(&self, file_path: P, size: (u32, u32)) -> anyhow::Result<()>
     where
         P: Into<PathBuf>,
@@ -223,26 +798,47 @@ impl Plot {
         let root = BitMapBackend::new(&file_path, size).into_drawing_area();
 
         root.fill(&WHITE)?;
+        self.draw_png(&root, true)?;
+        root.present()?;
 
-        let mut chart = ChartBuilder::on(&root);
+        Ok(())
+    }
 
-        chart
-            .margin(5)
-            .x_label_area_size(10.percent())
-            .y_label_area_size(10.percent());
+    /// Draw the plot on an area of an PNG file
+    ///
+    /// If `guides` is not set, the guides are left to the caller, e.g. collected by a [`layout::Layout`].
+    #[cfg(feature = "png")]
+    pub(crate) fn draw_png<'a>(
+        &'a self,
+        root: &DrawingArea<plotters::prelude::BitMapBackend<'a>, Shift>,
+        guides: bool,
+    ) -> anyhow::Result<()> {
+        self.compute();
 
-        if let Some(caption) = &self.label.caption {
-            chart.caption(caption, ("sans-serif", 32).into_font());
-        }
+        let scales = self.get_trained_scales();
+        let panel = self.get_panel();
+        let guides = match guides {
+            true => self.guides(&scales),
+            false => vec![],
+        };
+        let plot_area = self.draw_titles(root)?;
+        let (chart_area, guide_area) = self.split_guide_area(&plot_area, &guides);
+        let chart_area = self.chart_area(&chart_area, &panel)?;
+
+        let chart = self.build_chart(&chart_area, &panel)?;
 
-        let (x_range, y_range) = self.get_context_range_2d();
+        self.draw_with_secondary_axis(chart, &panel, |chart| {
+            self.draw_mesh(chart, &chart_area, &panel)?;
 
-        let mut chart = chart.build_cartesian_2d(x_range, y_range)?;
+            for layer in &self.layers {
+                layer.draw_png_2d(chart, &panel, &scales)?;
+            }
 
-        self.draw_mesh(&mut chart)?;
+            Ok(())
+        })?;
 
-        for layer in &self.layers {
-            layer.draw_png_2d(&mut chart)?;
+        if let Some(guide_area) = guide_area {
+            guide::draw_guides(&guide_area, &guides)?;
         }
 
         // chart
@@ -251,7 +847,40 @@ impl Plot {
         //     .label_font(("sans-serif", 16).into_font())
         //     .draw()?;
 
-        root.present()?;
+        Ok(())
+    }
+
+    /// Save the plot to a PDF file
+    ///
+    /// The size of the page is physical, e.g. `PageSize::mm(180.0, 120.0)`, see [`pdf::PageSize`].
+    ///
+    /// ```no_run
+    /// # use gongbi::{*, pdf::PageSize};
+    /// # fn main() -> anyhow::Result<()> {
+    /// let p = plot!(csv = "examples/mpg.csv", mapping = aes!(displ, hwy)) + geom_point!();
+    ///
+    /// p.to_pdf("mpg.pdf", PageSize::mm(180.0, 120.0))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "pdf")]
+    pub fn to_pdf<P, S>(&self, file_path: P, size: S) -> anyhow::Result<()>
+    where
+        P: Into<PathBuf>,
+        S: Into<pdf::PageSize>,
+    {
+        use plotters::prelude::*;
+
+        let mut svg = String::new();
+        {
+            let root = SVGBackend::with_string(&mut svg, size.into().pixels()).into_drawing_area();
+
+            root.fill(&WHITE)?;
+            self.draw_svg(&root, true)?;
+            root.present()?;
+        }
+
+        std::fs::write(file_path.into(), pdf::svg_to_pdf(&svg)?)?;
 
         Ok(())
     }
@@ -259,8 +888,9 @@ impl Plot {
     /// Save the plot
     ///
     /// The file format is determined by the file extension:
-    /// - `.svg`: SVG file
-    /// - `.png`: PNG file
+    /// - `.svg`: SVG file, with the `svg` feature
+    /// - `.png`: PNG file, with the `png` feature
+    /// - `.pdf`: PDF file, with the `pdf` feature, whose page is the size in pixels at 96 pixels per inch
     /// - Otherwise, an error is returned
     pub fn save<P>(&self, file_path: P, size: (u32, u32)) -> anyhow::Result<()>
     where
@@ -268,8 +898,12 @@ impl Plot {
     {
         let file_path = file_path.into();
         match file_path.extension().and_then(|ext| ext.to_str()) {
+            #[cfg(feature = "svg")]
             Some("svg") => self.to_svg(file_path, size),
+            #[cfg(feature = "png")]
             Some("png") => self.to_png(file_path, size),
+            #[cfg(feature = "pdf")]
+            Some("pdf") => self.to_pdf(file_path, size),
             _ => Err(anyhow::anyhow!("Unsupported file format")),
         }
     }
@@ -301,6 +935,11 @@ macro_rules! __plot {
         ///
         /// The `data =` part can be omitted if the data source is the first argument.
         ///
+        /// ### csv, parquet
+        ///
+        /// Read the default data source from a file instead, e.g. `plot!(csv = "examples/mpg.csv")`.
+        /// They require the `csv` and `parquet` features.
+        ///
         /// ### mapping
         ///
         /// The default aesthetics mapping for the plot. If not specified, the aesthetics mapping must be supplied in each layer.
@@ -368,8 +1007,26 @@ where
         *rhs.mapping_mut() += self.mapping.clone();
 
         // If the layer does not have data, use the plot's data
-        rhs.data_mut()
-            .get_or_insert(self.data.as_ref().expect("Plot does not have data").clone());
+        if rhs.data_mut().is_none() {
+            match &self.data {
+                Some(data) => *rhs.data_mut() = Some(data.clone()),
+                None if rhs.requires_data() => panic!("Plot does not have data"),
+                None => {}
+            }
+        }
+
+        // Collect the columns read by the layer from a lazy frame in one query
+        #[cfg(feature = "polars")]
+        {
+            let columns = [rhs.mapping_mut().columns(), rhs.columns()].concat();
+            let projection = rhs
+                .data_mut()
+                .as_deref()
+                .and_then(|data| data::polars::project(data, columns));
+            if projection.is_some() {
+                *rhs.data_mut() = projection;
+            }
+        }
 
         let mut layers = self.layers;
         layers.push(Box::new(rhs));
@@ -387,3 +1044,196 @@ impl Add<label::Label> for Plot {
         Plot { label, ..self }
     }
 }
+
+impl Add<coord::Coord> for Plot {
+    type Output = Self;
+
+    fn add(self, rhs: coord::Coord) -> Self::Output {
+        Plot { coord: rhs, ..self }
+    }
+}
+
+impl Add<scale::Scale> for Plot {
+    type Output = Self;
+
+    fn add(self, rhs: scale::Scale) -> Self::Output {
+        let mut scales = self.scales;
+
+        match rhs {
+            scale::Scale::Color(color) => scales.color = color,
+            scale::Scale::Fill(fill) => scales.fill = fill,
+            scale::Scale::X(x) => scales.x = x,
+            scale::Scale::Y(y) => scales.y = y,
+        }
+
+        Plot { scales, ..self }
+    }
+}
+
+/// Map the items, e.g. the layers of a plot, in parallel with the `parallel` feature
+///
+/// The results are in the order of the items.
+pub(crate) fn par_map<'a, I, T, F>(items: &'a [I], f: F) -> Vec<T>
+where
+    I: Sync,
+    T: Send,
+    F: Fn(&'a I) -> T + Send + Sync,
+{
+    #[cfg(feature = "parallel")]
+    use rayon::prelude::*;
+
+    #[cfg(feature = "parallel")]
+    let items = items.par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let items = items.iter();
+
+    items.map(f).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, thread};
+
+    use super::*;
+
+    #[test]
+    fn plots_are_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<Plot>();
+        assert_send_sync::<layout::Layout>();
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn render_from_threads() {
+        let data = HashMap::from([("x", vec![1.0, 2.0, 3.0]), ("y", vec![3.0, 1.0, 2.0])]);
+        let plot = plot!(data, aes!(x, y))
+            + geom_point!()
+            + scale_y_continuous!(labels = |y: f64| format!("{y} m"));
+        let dir = std::env::temp_dir();
+
+        thread::scope(|scope| {
+            for i in 0..4 {
+                let (plot, dir) = (&plot, &dir);
+                scope.spawn(move || {
+                    plot.to_svg(dir.join(format!("gongbi_thread_{i}.svg")), (400, 300))
+                });
+            }
+        });
+
+        for i in 0..4 {
+            let file_path = dir.join(format!("gongbi_thread_{i}.svg"));
+            assert!(std::fs::read_to_string(&file_path).unwrap().contains("3 m"));
+            std::fs::remove_file(file_path).unwrap();
+        }
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn lazy_layers_match_data_frames() {
+        use polars::prelude::*;
+
+        let df = df!(
+            "id" => (0..60).collect::<Vec<i32>>(),
+            "x" => (0..60).map(|i| (i % 7) as f64).collect::<Vec<_>>(),
+            "y" => (0..60).map(|i| (i as f64 * 0.7).sin() * 4.0).collect::<Vec<_>>(),
+            "class" => (0..60).map(|i| ["a", "b", "c"][i % 3]).collect::<Vec<_>>(),
+        )
+        .unwrap();
+        // The rows are in any order without `maintain_order`
+        let lf = df.clone().lazy().group_by([col("id")]).agg([
+            col("x").first(),
+            col("y").first(),
+            col("class").first(),
+        ]);
+
+        let layers = |plot: Plot| {
+            let plot = plot
+                + geom_bar!(aes!(x, fill = class))
+                + geom_bar!(aes!(x, y = col("y") * col("y")))
+                + geom_bin2d!(bins = 5)
+                + geom_hex!(bins = 5)
+                + geom_point!(aes!(x = col("x") + col("y"), y = y, color = class));
+
+            plot.layers
+                .iter()
+                .map(|layer| {
+                    let range = layer.range_2d().unwrap();
+                    let round = |v: f64| (v * 1e9).round();
+                    let range = [range.0, range.1, range.2, range.3].map(round);
+                    let levels = layer.color_levels().map(|(_, levels)| levels);
+
+                    (range, layer.fill_range(), levels)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(layers(plot!(lf, aes!(x, y))), layers(plot!(df, aes!(x, y))));
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn empty_lazy_frames() {
+        use polars::prelude::*;
+
+        use crate::data::Data;
+
+        let df = df!("x" => [1.0, 2.0], "y" => [None::<f64>, None]).unwrap();
+        let empty = df.clone().lazy().filter(col("x").gt(lit(10.0)));
+        let nulls = df.lazy();
+
+        let no_range = (f64::INFINITY, f64::NEG_INFINITY);
+        assert_eq!(empty.column_range_f64("x"), no_range);
+        assert_eq!(empty.column_len("x"), 0);
+        assert_eq!(nulls.column_range_f64("y"), no_range);
+        assert_eq!(nulls.column_len("y"), 2);
+
+        let ranges = [empty, nulls].map(|data| {
+            let plot =
+                plot!(data, aes!(x, y)) + geom_point!() + geom_bar!() + geom_bin2d!() + geom_hex!();
+
+            plot.get_range_2d()
+        });
+        assert_eq!(ranges, [(-0.5, 0.5, -0.5, 0.5), (1.0, 2.0, -0.5, 0.5)]);
+    }
+
+    #[test]
+    fn ranges_are_merged_per_axis() {
+        let plot = plot!() + geom_hline!(yintercept = 30, expand = true);
+        assert_eq!(plot.get_range_2d(), (-0.5, 0.5, 29.5, 30.5));
+
+        let data = HashMap::from([("x", vec![1.0, 2.0]), ("y", vec![3.0, 1.0])]);
+        let plot =
+            plot!(data, aes!(x, y)) + geom_point!() + geom_vline!(xintercept = 5.0, expand = true);
+        assert_eq!(plot.get_range_2d(), (1.0, 5.0, 1.0, 3.0));
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn reference_lines_of_any_shape() {
+        let data = HashMap::from([("x", vec![1.0, 2.0]), ("y", vec![3.0, 1.0])]);
+        let plot = plot!(data, aes!(x, y))
+            + geom_point!()
+            + geom_hline!(aes!(shape = 3), yintercept = 2.0)
+            + geom_abline!(aes!(shape = 3), slope = 1.0, intercept = 0.0);
+        let file_path = std::env::temp_dir().join("gongbi_reference_shape.svg");
+
+        plot.to_svg(&file_path, (400, 300)).unwrap();
+        std::fs::remove_file(file_path).unwrap();
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn empty_annotation_values() {
+        let data = HashMap::from([("x", vec![1.0, 2.0]), ("y", vec![3.0, 1.0])]);
+        let plot = plot!(data, aes!(x, y))
+            + geom_point!()
+            + geom_abline!(slope = &[][..], intercept = 0.0)
+            + annotate!("text", x = &[][..], y = 2.0, label = "empty");
+        let file_path = std::env::temp_dir().join("gongbi_empty_annotation.svg");
+
+        plot.to_svg(&file_path, (400, 300)).unwrap();
+        std::fs::remove_file(file_path).unwrap();
+    }
+}
@@ -0,0 +1,225 @@
+//! # PDF module
+//!
+//! Plots are saved as PDF files through SVG: a plot is drawn as an SVG at the
+//! size of the page, which is converted by `svg2pdf`. The text is embedded with
+//! subsets of the system fonts, so it is selectable and the PDF looks the same
+//! whether the fonts are installed or not, e.g. when it is printed by a journal.
+//!
+//! The size of a page is physical, see [`PageSize`].
+
+use std::sync::{Arc, Mutex, OnceLock};
+
+use svg2pdf::{
+    usvg::{
+        fontdb::{Database, Family, Query},
+        FontResolver, Options, Tree,
+    },
+    ConversionOptions, PageOptions,
+};
+
+/// The number of PostScript points in an inch, the unit of PDF
+const POINTS_PER_INCH: f64 = 72.0;
+
+/// The number of pixels in an inch, the unit of SVG
+const PIXELS_PER_INCH: f64 = 96.0;
+
+/// # Page size
+///
+/// The physical size of a PDF page, e.g. `PageSize::mm(180.0, 120.0)` for a
+/// figure spanning two columns, or `PageSize::A4.landscape()`.
+///
+/// The plot is drawn at the size of the page in pixels of 1/96 inch, like SVG
+/// files, so the fonts of 24 pixels of the titles are 18 points on paper.
+/// A size in pixels, e.g. `(1024, 768)`, is converted the same way.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PageSize {
+    /// The width in points.
+    width: f64,
+
+    /// The height in points.
+    height: f64,
+}
+
+impl PageSize {
+    /// The ISO A4 paper size, 210 x 297 mm.
+    pub const A4: PageSize = PageSize::mm(210.0, 297.0);
+
+    /// The ISO A5 paper size, 148 x 210 mm.
+    pub const A5: PageSize = PageSize::mm(148.0, 210.0);
+
+    /// The US letter paper size, 8.5 x 11 inches.
+    pub const LETTER: PageSize = PageSize::inches(8.5, 11.0);
+
+    /// Create a page size in PostScript points, i.e. 1/72 inch.
+    pub const fn points(width: f64, height: f64) -> Self {
+        PageSize { width, height }
+    }
+
+    /// Create a page size in inches.
+    pub const fn inches(width: f64, height: f64) -> Self {
+        PageSize::points(width * POINTS_PER_INCH, height * POINTS_PER_INCH)
+    }
+
+    /// Create a page size in millimeters.
+    pub const fn mm(width: f64, height: f64) -> Self {
+        PageSize::inches(width / 25.4, height / 25.4)
+    }
+
+    /// Create a page size in centimeters.
+    pub const fn cm(width: f64, height: f64) -> Self {
+        PageSize::mm(width * 10.0, height * 10.0)
+    }
+
+    /// Get the page in landscape orientation, i.e. wider than high.
+    pub fn landscape(self) -> Self {
+        match self.width < self.height {
+            true => PageSize::points(self.height, self.width),
+            false => self,
+        }
+    }
+
+    /// Get the width in points.
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+
+    /// Get the height in points.
+    pub fn height(&self) -> f64 {
+        self.height
+    }
+
+    /// Get the size in pixels to draw the plot at, rounded to whole pixels.
+    pub(crate) fn pixels(&self) -> (u32, u32) {
+        let scale = PIXELS_PER_INCH / POINTS_PER_INCH;
+
+        (
+            (self.width * scale).round() as u32,
+            (self.height * scale).round() as u32,
+        )
+    }
+}
+
+/// A size in pixels, at 96 pixels per inch like SVG files.
+impl From<(u32, u32)> for PageSize {
+    fn from((width, height): (u32, u32)) -> Self {
+        let scale = POINTS_PER_INCH / PIXELS_PER_INCH;
+
+        PageSize::points(width as f64 * scale, height as f64 * scale)
+    }
+}
+
+/// Get the database of the system fonts, loaded once for all PDF files.
+fn fonts() -> Arc<Database> {
+    static FONTS: OnceLock<Arc<Database>> = OnceLock::new();
+
+    FONTS
+        .get_or_init(|| {
+            let mut fonts = Database::new();
+            fonts.load_system_fonts();
+
+            // The generic sans-serif family is Arial by default, use another sans-serif font if missing
+            let query = Query {
+                families: &[Family::SansSerif],
+                ..Query::default()
+            };
+            if fonts.query(&query).is_none() {
+                let family = fonts
+                    .faces()
+                    .flat_map(|face| &face.families)
+                    .map(|(family, _)| family)
+                    .filter(|family| family.contains("Sans") && !family.contains("Mono"))
+                    .min()
+                    .cloned();
+
+                if let Some(family) = family {
+                    fonts.set_sans_serif_family(family);
+                }
+            }
+
+            Arc::new(fonts)
+        })
+        .clone()
+}
+
+/// Convert an SVG drawn at the size of [`PageSize::pixels`] into a PDF.
+pub(crate) fn svg_to_pdf(svg: &str) -> anyhow::Result<Vec<u8>> {
+    convert(svg, fonts())
+}
+
+/// Convert an SVG into a PDF, with its text in the fonts of the database.
+///
+/// Returns an error if no font matches the text, which would be dropped otherwise.
+fn convert(svg: &str, fonts: Arc<Database>) -> anyhow::Result<Vec<u8>> {
+    let missing = Mutex::new(None);
+    let select_font = FontResolver::default_font_selector();
+    let options = Options {
+        fontdb: fonts,
+        font_resolver: FontResolver {
+            select_font: Box::new(|font, fonts| {
+                let id = select_font(font, fonts);
+                if id.is_none() {
+                    let families: Vec<String> =
+                        font.families().iter().map(ToString::to_string).collect();
+                    *missing.lock().unwrap() = Some(families.join(", "));
+                }
+
+                id
+            }),
+            ..FontResolver::default()
+        },
+        ..Options::default()
+    };
+
+    let tree = Tree::from_str(svg, &options)?;
+    if let Some(families) = missing.lock().unwrap().take() {
+        return Err(anyhow::anyhow!("No font found for the text in {families}"));
+    }
+
+    let page = PageOptions {
+        dpi: PIXELS_PER_INCH as f32,
+    };
+
+    svg2pdf::to_pdf(&tree, ConversionOptions::default(), page)
+        .map_err(|err| anyhow::anyhow!("Failed to convert the plot to PDF: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn physical_sizes() {
+        assert_eq!(PageSize::inches(1.0, 2.0), PageSize::points(72.0, 144.0));
+        assert_eq!(PageSize::cm(2.54, 2.54), PageSize::inches(1.0, 1.0));
+        assert_eq!(PageSize::A4.pixels(), (794, 1123));
+        assert_eq!(PageSize::A4.landscape().pixels(), (1123, 794));
+        assert_eq!(PageSize::from((1024, 768)), PageSize::points(768.0, 576.0));
+        assert_eq!(PageSize::from((1024, 768)).pixels(), (1024, 768));
+    }
+
+    #[test]
+    fn pdf_of_svg() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="96" height="48">
+            <rect width="96" height="48" fill="white"/>
+            <text x="10" y="30" font-family="sans-serif" font-size="16">gongbi</text>
+        </svg>"#;
+        let pdf = svg_to_pdf(svg).unwrap();
+
+        assert!(pdf.starts_with(b"%PDF-"));
+        // One inch by half an inch
+        let pdf = String::from_utf8_lossy(&pdf);
+        assert!(pdf.contains("/MediaBox [0 0 72 36]"), "{pdf}");
+        // The text is kept as text in an embedded font
+        assert!(pdf.contains("/FontFile"), "{pdf}");
+    }
+
+    #[test]
+    fn pdf_without_fonts() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="96" height="48">
+            <text x="10" y="30" font-family="sans-serif" font-size="16">gongbi</text>
+        </svg>"#;
+        let err = convert(svg, Arc::new(Database::new())).unwrap_err();
+
+        assert!(err.to_string().contains("sans-serif"), "{err}");
+    }
+}
@@ -0,0 +1,820 @@
+//! Scale module
+//!
+//! Scales control how the data values are mapped to the aesthetics, e.g. how
+//! the values of the `fill` column are mapped to colors, or where the breaks of
+//! the axes of the `x` and `y` positions are.
+//!
+//! Scales are added to a [`Plot`](crate::Plot) with the `+` operator, like
+//! [`scale_fill_gradient!`](crate::scale_fill_gradient!) or [`scale_y_continuous!`](crate::scale_y_continuous!).
+
+use std::{fmt, ops::Range, sync::Arc};
+
+use derive_builder::Builder;
+use derive_more::From;
+use plotters::style::RGBColor;
+
+use crate::{aes::color::Color, guide};
+
+pub mod format;
+
+use format::Format;
+
+/// The default low and high colors of the continuous color scale
+const DEFAULT_GRADIENT: [(u8, u8, u8); 2] = [(0x13, 0x2B, 0x43), (0x56, 0xB1, 0xF7)];
+
+/// The colors of the viridis palette, from low to high
+const VIRIDIS: [(u8, u8, u8); 10] = [
+    (0x44, 0x01, 0x54),
+    (0x48, 0x28, 0x78),
+    (0x3E, 0x4A, 0x89),
+    (0x31, 0x68, 0x8E),
+    (0x26, 0x82, 0x8E),
+    (0x1F, 0x9E, 0x89),
+    (0x35, 0xB7, 0x79),
+    (0x6D, 0xCD, 0x59),
+    (0xB4, 0xDE, 0x2C),
+    (0xFD, 0xE7, 0x25),
+];
+
+/// A scale to add to a plot
+///
+/// Each variant is the scale of an aesthetic.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Scale {
+    /// The scale of the `color` aesthetic
+    Color(DiscreteColor),
+
+    /// The scale of the `fill` aesthetic
+    Fill(ContinuousColor),
+
+    /// The scale of the `x` position
+    X(Continuous),
+
+    /// The scale of the `y` position
+    Y(Continuous),
+}
+
+/// The scales of a plot
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Scales {
+    /// The scale of the `color` aesthetic
+    pub color: DiscreteColor,
+
+    /// The scale of the `fill` aesthetic
+    pub fill: ContinuousColor,
+
+    /// The scale of the `x` position
+    pub x: Continuous,
+
+    /// The scale of the `y` position
+    pub y: Continuous,
+}
+
+/// The breaks of an axis
+#[derive(Clone, Debug, PartialEq, From)]
+pub enum Breaks {
+    /// The approximate number of breaks, at multiples of 1, 2 or 5 times a power of 10
+    Count(usize),
+
+    /// The positions of the breaks
+    Values(Vec<f64>),
+}
+
+impl From<i32> for Breaks {
+    fn from(count: i32) -> Self {
+        Breaks::Count(count.max(0) as usize)
+    }
+}
+
+impl From<&[f64]> for Breaks {
+    fn from(values: &[f64]) -> Self {
+        Breaks::Values(values.to_vec())
+    }
+}
+
+impl<const N: usize> From<[f64; N]> for Breaks {
+    fn from(values: [f64; N]) -> Self {
+        Breaks::Values(values.to_vec())
+    }
+}
+
+impl Breaks {
+    /// Get the breaks in the range, sorted.
+    pub fn resolve(&self, range: Range<f64>) -> Vec<f64> {
+        let (low, high) = (range.start.min(range.end), range.start.max(range.end));
+
+        match self {
+            Breaks::Count(count) => guide::breaks(low, high, *count).0,
+            Breaks::Values(values) => {
+                let mut values: Vec<f64> = values
+                    .iter()
+                    .copied()
+                    .filter(|value| (low..=high).contains(value))
+                    .collect();
+                values.sort_by(f64::total_cmp);
+
+                values
+            }
+        }
+    }
+}
+
+/// # Continuous position scale
+///
+/// The continuous position scale controls the axis of a numeric position:
+/// its breaks, minor breaks, labels and secondary axis.
+///
+/// To create a continuous position scale, use [`scale_x_continuous!`](crate::scale_x_continuous!),
+/// [`scale_y_continuous!`](crate::scale_y_continuous!) or [`Continuous::builder`].
+#[derive(Clone, Debug, Default, PartialEq, Builder)]
+pub struct Continuous {
+    /// The breaks of the axis, with a label and a major grid line.
+    ///
+    /// If not provided, the breaks are chosen from the range.
+    #[builder(default, setter(into, strip_option))]
+    breaks: Option<Breaks>,
+
+    /// The minor breaks of the axis, with a minor grid line.
+    ///
+    /// If not provided, the minor breaks are between the breaks.
+    #[builder(default, setter(into, strip_option))]
+    minor_breaks: Option<Breaks>,
+
+    /// The format of the labels of the breaks, e.g. [`format::percent`].
+    #[builder(default, setter(into, strip_option))]
+    labels: Option<Format>,
+
+    /// The secondary axis, on the opposite side of the panel.
+    #[builder(default, setter(strip_option))]
+    sec_axis: Option<SecAxis>,
+}
+
+impl Continuous {
+    /// Create a new [`Continuous`] via the builder pattern.
+    pub fn builder() -> ContinuousBuilder {
+        ContinuousBuilder::default()
+    }
+
+    /// Get the breaks of the axis.
+    pub fn breaks(&self) -> Option<&Breaks> {
+        self.breaks.as_ref()
+    }
+
+    /// Get the minor breaks of the axis.
+    pub fn minor_breaks(&self) -> Option<&Breaks> {
+        self.minor_breaks.as_ref()
+    }
+
+    /// Get the format of the labels.
+    pub fn labels(&self) -> Option<&Format> {
+        self.labels.as_ref()
+    }
+
+    /// Get the secondary axis.
+    pub fn sec_axis(&self) -> Option<&SecAxis> {
+        self.sec_axis.as_ref()
+    }
+}
+
+/// # Secondary axis
+///
+/// A secondary axis shows the positions of the primary axis through a
+/// transformation, e.g. a quantity in another unit. It has its own breaks and name.
+///
+/// To create a secondary axis, use [`sec_axis!`](crate::sec_axis!) or [`SecAxis::builder`].
+#[derive(Clone, Builder)]
+pub struct SecAxis {
+    /// The transformation from the positions of the primary axis, which must be monotonic.
+    #[builder(setter(custom))]
+    transform: Arc<dyn Fn(f64) -> f64 + Send + Sync>,
+
+    /// The name of the axis.
+    #[builder(default, setter(into, strip_option))]
+    name: Option<String>,
+
+    /// The breaks of the axis, in the transformed values.
+    ///
+    /// If not provided, the breaks are chosen from the transformed range.
+    #[builder(default, setter(into, strip_option))]
+    breaks: Option<Breaks>,
+
+    /// The format of the labels of the breaks.
+    #[builder(default, setter(into, strip_option))]
+    labels: Option<Format>,
+}
+
+impl SecAxisBuilder {
+    /// Set the transformation from the positions of the primary axis, which must be monotonic.
+    pub fn transform<F>(&mut self, transform: F) -> &mut Self
+    where
+        F: Fn(f64) -> f64 + Send + Sync + 'static,
+    {
+        self.transform = Some(Arc::new(transform));
+        self
+    }
+}
+
+impl SecAxis {
+    /// Create a new [`SecAxis`] via the builder pattern.
+    pub fn builder() -> SecAxisBuilder {
+        SecAxisBuilder::default()
+    }
+
+    /// Transform a position of the primary axis.
+    pub fn transform(&self, value: f64) -> f64 {
+        (self.transform)(value)
+    }
+
+    /// Get the name of the axis.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Get the breaks of the axis.
+    pub fn breaks(&self) -> Option<&Breaks> {
+        self.breaks.as_ref()
+    }
+
+    /// Get the format of the labels.
+    pub fn labels(&self) -> Option<&Format> {
+        self.labels.as_ref()
+    }
+}
+
+impl fmt::Debug for SecAxis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecAxis")
+            .field("name", &self.name)
+            .field("breaks", &self.breaks)
+            .field("labels", &self.labels)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for SecAxis {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.transform, &other.transform)
+            && self.name == other.name
+            && self.breaks == other.breaks
+            && self.labels == other.labels
+    }
+}
+
+/// # Continuous color scale
+///
+/// The continuous color scale maps numeric values to a gradient of colors.
+///
+/// To create a continuous color scale, use [`scale_fill_gradient!`](crate::scale_fill_gradient!),
+/// [`scale_fill_gradientn!`](crate::scale_fill_gradientn!),
+/// [`scale_fill_viridis_c!`](crate::scale_fill_viridis_c!) or [`ContinuousColor::builder`].
+#[derive(Clone, Debug, PartialEq, Builder)]
+#[builder(build_fn(validate = "Self::validate"))]
+pub struct ContinuousColor {
+    /// The colors of the gradient, from the lowest to the highest value.
+    ///
+    /// At least one color is required.
+    #[builder(
+        default = "DEFAULT_GRADIENT.iter().map(|&c| c.into()).collect()",
+        setter(custom)
+    )]
+    colors: Vec<Color>,
+
+    /// The range of values mapped to the gradient.
+    ///
+    /// If not provided, the range of the values in all layers is used.
+    #[builder(default, setter(strip_option))]
+    limits: Option<(f64, f64)>,
+
+    /// The color of missing values and values outside the limits.
+    #[builder(default = "Color::RGB(RGBColor(127, 127, 127))", setter(into))]
+    na_value: Color,
+}
+
+impl Default for ContinuousColor {
+    fn default() -> Self {
+        ContinuousColor::builder().build().unwrap()
+    }
+}
+
+impl ContinuousColorBuilder {
+    /// Set the colors of the gradient, from the lowest to the highest value.
+    pub fn colors<I, T>(&mut self, colors: I) -> &mut Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<Color>,
+    {
+        self.colors = Some(colors.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Set the color of the lowest value.
+    pub fn low<T: Into<Color>>(&mut self, low: T) -> &mut Self {
+        let colors = self.colors.get_or_insert_with(default_gradient);
+        match colors.first_mut() {
+            Some(color) => *color = low.into(),
+            None => colors.push(low.into()),
+        }
+        self
+    }
+
+    /// Set the color of the highest value.
+    pub fn high<T: Into<Color>>(&mut self, high: T) -> &mut Self {
+        let colors = self.colors.get_or_insert_with(default_gradient);
+        match colors.last_mut() {
+            Some(color) => *color = high.into(),
+            None => colors.push(high.into()),
+        }
+        self
+    }
+
+    /// Use the viridis palette as the gradient.
+    pub fn viridis(&mut self) -> &mut Self {
+        self.colors(VIRIDIS)
+    }
+
+    /// Check the gradient has at least one color.
+    fn validate(&self) -> Result<(), String> {
+        match self.colors.as_ref().is_some_and(Vec::is_empty) {
+            true => Err("colors of a continuous color scale must not be empty".to_string()),
+            false => Ok(()),
+        }
+    }
+}
+
+fn default_gradient() -> Vec<Color> {
+    DEFAULT_GRADIENT.iter().map(|&c| c.into()).collect()
+}
+
+impl ContinuousColor {
+    /// Create a new [`ContinuousColor`] via the builder pattern.
+    pub fn builder() -> ContinuousColorBuilder {
+        ContinuousColorBuilder::default()
+    }
+
+    /// Get the range of values mapped to the gradient.
+    pub fn limits(&self) -> Option<(f64, f64)> {
+        self.limits
+    }
+
+    /// Set the limits from the range of the data if they are not provided.
+    pub(crate) fn train(&mut self, range: (f64, f64)) {
+        self.limits.get_or_insert(range);
+    }
+
+    /// Map a value to a color.
+    ///
+    /// Missing values and values outside the limits are mapped to `na_value`.
+    pub fn map(&self, value: f64) -> RGBColor {
+        let (low, high) = self.limits.unwrap_or((0.0, 1.0));
+
+        if !value.is_finite() || value < low || value > high {
+            return self.na_value.as_rgb();
+        }
+
+        let t = if high > low {
+            (value - low) / (high - low)
+        } else {
+            0.5
+        };
+
+        self.gradient(t)
+    }
+
+    /// Get the color at the relative position `t` of the gradient, from `0.0` to `1.0`.
+    pub fn gradient(&self, t: f64) -> RGBColor {
+        let n = self.colors.len();
+        if n == 1 {
+            return self.colors[0].as_rgb();
+        }
+
+        let pos = t.clamp(0.0, 1.0) * (n - 1) as f64;
+        let i = (pos.floor() as usize).min(n - 2);
+        let frac = pos - i as f64;
+
+        let (a, b) = (self.colors[i].as_rgb(), self.colors[i + 1].as_rgb());
+        let lerp = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * frac).round() as u8;
+
+        RGBColor(lerp(a.0, b.0), lerp(a.1, b.1), lerp(a.2, b.2))
+    }
+}
+
+/// # Discrete color scale
+///
+/// The discrete color scale maps the distinct values of a column, called levels,
+/// to colors. By default, the colors are evenly spaced hues like `ggplot2`.
+///
+/// To create a discrete color scale, use [`scale_color_manual!`](crate::scale_color_manual!)
+/// or [`DiscreteColor::builder`].
+#[derive(Clone, Debug, Default, PartialEq, Builder)]
+pub struct DiscreteColor {
+    /// The colors of the levels, in the order of the levels.
+    ///
+    /// If not provided, evenly spaced hues are used.
+    #[builder(default, setter(custom))]
+    values: Option<Vec<Color>>,
+
+    /// The levels of the scale, sorted.
+    ///
+    /// The levels are set from the values of all layers when the plot is drawn.
+    #[builder(setter(skip))]
+    levels: Vec<String>,
+}
+
+impl DiscreteColorBuilder {
+    /// Set the colors of the levels, in the order of the levels.
+    pub fn values<I, T>(&mut self, values: I) -> &mut Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<Color>,
+    {
+        self.values = Some(Some(values.into_iter().map(Into::into).collect()));
+        self
+    }
+}
+
+impl DiscreteColor {
+    /// Create a new [`DiscreteColor`] via the builder pattern.
+    pub fn builder() -> DiscreteColorBuilder {
+        DiscreteColorBuilder::default()
+    }
+
+    /// Get the levels of the scale.
+    pub fn levels(&self) -> &[String] {
+        &self.levels
+    }
+
+    /// Add the levels of a layer to the scale, keeping the levels sorted and distinct.
+    pub(crate) fn train<I: IntoIterator<Item = String>>(&mut self, levels: I) {
+        self.levels.extend(levels);
+        self.levels.sort();
+        self.levels.dedup();
+    }
+
+    /// Map a level to a color.
+    ///
+    /// Unknown levels are mapped to grey.
+    pub fn map(&self, level: &str) -> RGBColor {
+        let Ok(index) = self.levels.binary_search_by(|l| l.as_str().cmp(level)) else {
+            return RGBColor(127, 127, 127);
+        };
+
+        match &self.values {
+            Some(values) if !values.is_empty() => values[index % values.len()].as_rgb(),
+            _ => hue(index, self.levels.len()),
+        }
+    }
+}
+
+/// Get the color of the `i`-th of `n` evenly spaced hues, with the chroma and
+/// luminance of `ggplot2`.
+fn hue(i: usize, n: usize) -> RGBColor {
+    let h = 15.0 + 360.0 * i as f64 / n.max(1) as f64;
+
+    hcl(h, 100.0, 65.0)
+}
+
+/// Convert a color from the polar CIE-LUV space to sRGB.
+fn hcl(h: f64, c: f64, l: f64) -> RGBColor {
+    // The D65 white point
+    let (xn, yn, zn) = (95.047, 100.0, 108.883);
+    let un = 4.0 * xn / (xn + 15.0 * yn + 3.0 * zn);
+    let vn = 9.0 * yn / (xn + 15.0 * yn + 3.0 * zn);
+
+    let (u, v) = (c * h.to_radians().cos(), c * h.to_radians().sin());
+
+    let y = if l > 8.0 {
+        yn * ((l + 16.0) / 116.0).powi(3)
+    } else {
+        yn * l / 903.3
+    };
+    let (u, v) = (u / (13.0 * l) + un, v / (13.0 * l) + vn);
+    let x = y * 9.0 * u / (4.0 * v);
+    let z = y * (12.0 - 3.0 * u - 20.0 * v) / (4.0 * v);
+
+    let (x, y, z) = (x / 100.0, y / 100.0, z / 100.0);
+    let gamma = |c: f64| {
+        let c = if c <= 0.0031308 {
+            12.92 * c
+        } else {
+            1.055 * c.powf(1.0 / 2.4) - 0.055
+        };
+        (c.clamp(0.0, 1.0) * 255.0).round() as u8
+    };
+
+    RGBColor(
+        gamma(3.240479 * x - 1.537150 * y - 0.498535 * z),
+        gamma(-0.969256 * x + 1.875992 * y + 0.041556 * z),
+        gamma(0.055648 * x - 0.204043 * y + 1.057311 * z),
+    )
+}
+
+/// # scale_color_manual!: Construct a discrete color scale with given colors
+///
+/// This macro is used to create a [`DiscreteColor`] scale of the `color` aesthetic.
+/// It is a wrapper around [`DiscreteColor::builder`].
+///
+/// ## Usage
+///
+/// ```ignore
+/// scale_color_manual!(
+///     values = [<Color>, ...],
+/// )
+/// ```
+///
+/// ## Example
+///
+/// ```
+/// # use gongbi::*;
+/// let scale = scale_color_manual!(values = ["red", "blue"]);
+/// ```
+#[macro_export]
+macro_rules! scale_color_manual {
+    ($($param: ident = $value: expr),* $(,)?) => {
+        $crate::scale::Scale::Color(
+            $crate::scale::DiscreteColor::builder()
+                $(.$param($value))*
+                .build()
+                .unwrap()
+        )
+    };
+}
+
+/// # scale_fill_gradient!: Construct a two-color gradient fill scale
+///
+/// This macro is used to create a [`ContinuousColor`] scale of the `fill` aesthetic.
+/// It is a wrapper around [`ContinuousColor::builder`].
+///
+/// ## Usage
+///
+/// ```ignore
+/// scale_fill_gradient!(
+///     low = <Color>,
+///     high = <Color>,
+///     limits = (<f64>, <f64>),
+///     na_value = <Color>,
+/// )
+/// ```
+///
+/// ## Example
+///
+/// ```
+/// # use gongbi::*;
+/// let scale = scale_fill_gradient!(low = "white", high = "red", limits = (0.0, 1.0));
+/// ```
+#[macro_export]
+macro_rules! scale_fill_gradient {
+    ($($param: ident = $value: expr),* $(,)?) => {
+        $crate::scale::Scale::Fill(
+            $crate::scale::ContinuousColor::builder()
+                $(.$param($value))*
+                .build()
+                .unwrap()
+        )
+    };
+}
+
+/// # scale_fill_gradientn!: Construct a n-color gradient fill scale
+///
+/// This macro is used to create a [`ContinuousColor`] scale of the `fill` aesthetic
+/// with a gradient of multiple colors.
+///
+/// ## Usage
+///
+/// ```ignore
+/// scale_fill_gradientn!(
+///     colors = [<Color>, ...],
+///     limits = (<f64>, <f64>),
+///     na_value = <Color>,
+/// )
+/// ```
+///
+/// ## Example
+///
+/// ```
+/// # use gongbi::*;
+/// let scale = scale_fill_gradientn!(colors = ["blue", "white", "red"]);
+/// ```
+#[macro_export]
+macro_rules! scale_fill_gradientn {
+    ($($param: ident = $value: expr),* $(,)?) => {
+        $crate::scale_fill_gradient!($($param = $value),*)
+    };
+}
+
+/// # scale_fill_viridis_c!: Construct a viridis fill scale
+///
+/// This macro is used to create a [`ContinuousColor`] scale of the `fill` aesthetic
+/// with the colorblind-friendly viridis palette.
+///
+/// ## Usage
+///
+/// ```ignore
+/// scale_fill_viridis_c!(
+///     limits = (<f64>, <f64>),
+///     na_value = <Color>,
+/// )
+/// ```
+#[macro_export]
+macro_rules! scale_fill_viridis_c {
+    ($($param: ident = $value: expr),* $(,)?) => {
+        $crate::scale::Scale::Fill(
+            $crate::scale::ContinuousColor::builder()
+                .viridis()
+                $(.$param($value))*
+                .build()
+                .unwrap()
+        )
+    };
+}
+
+/// # scale_x_continuous!: Construct a continuous scale of the x position
+///
+/// This macro is used to create a [`Continuous`] scale of the `x` position.
+/// It is a wrapper around [`Continuous::builder`].
+///
+/// ## Usage
+///
+/// ```ignore
+/// scale_x_continuous!(
+///     breaks = <usize | Vec<f64>>,
+///     minor_breaks = <usize | Vec<f64>>,
+///     labels = <Format | Fn(f64) -> String>,
+/// )
+/// ```
+///
+/// ### Arguments
+///
+/// #### breaks, minor_breaks
+///
+/// The approximate number of breaks, or their positions. The breaks have a
+/// label and a major grid line, and the minor breaks a minor grid line.
+///
+/// #### labels
+///
+/// The format of the labels, from [`format`] or a closure.
+///
+/// ## Example
+///
+/// ```
+/// # use gongbi::*;
+/// use gongbi::scale::format;
+///
+/// let scale = scale_x_continuous!(breaks = vec![0.0, 60.0, 120.0], labels = format::duration());
+/// ```
+#[macro_export]
+macro_rules! scale_x_continuous {
+    ($($param: ident = $value: expr),* $(,)?) => {
+        $crate::scale::Scale::X(
+            $crate::scale::Continuous::builder()
+                $(.$param($value))*
+                .build()
+                .unwrap()
+        )
+    };
+}
+
+/// # scale_y_continuous!: Construct a continuous scale of the y position
+///
+/// This macro is used to create a [`Continuous`] scale of the `y` position.
+/// It is a wrapper around [`Continuous::builder`].
+///
+/// ## Usage
+///
+/// ```ignore
+/// scale_y_continuous!(
+///     breaks = <usize | Vec<f64>>,
+///     minor_breaks = <usize | Vec<f64>>,
+///     labels = <Format | Fn(f64) -> String>,
+///     sec_axis = sec_axis!(...),
+/// )
+/// ```
+///
+/// It accepts the same arguments as [`scale_x_continuous!`](crate::scale_x_continuous!),
+/// and the secondary axis on the right of the panel.
+///
+/// ## Example
+///
+/// ```
+/// # use gongbi::*;
+/// use gongbi::scale::format;
+///
+/// // The memory in MiB, and in percent of a limit of 512 MiB on the right
+/// let scale = scale_y_continuous!(
+///     breaks = 8,
+///     sec_axis = sec_axis!(|mib| mib / 512.0, name = "limit", labels = format::percent())
+/// );
+/// ```
+#[macro_export]
+macro_rules! scale_y_continuous {
+    ($($param: ident = $value: expr),* $(,)?) => {
+        $crate::scale::Scale::Y(
+            $crate::scale::Continuous::builder()
+                $(.$param($value))*
+                .build()
+                .unwrap()
+        )
+    };
+}
+
+/// # sec_axis!: Construct a secondary axis
+///
+/// This macro is used to create a [`SecAxis`] for the `sec_axis` of a position
+/// scale, e.g. [`scale_y_continuous!`](crate::scale_y_continuous!).
+/// It is a wrapper around [`SecAxis::builder`].
+///
+/// ## Usage
+///
+/// ```ignore
+/// sec_axis!(
+///     transform = <Fn(f64) -> f64>,
+///     name = <String>,
+///     breaks = <usize | Vec<f64>>,
+///     labels = <Format | Fn(f64) -> String>,
+/// )
+/// ```
+///
+/// The `transform =` part can be omitted if the transformation is the first argument.
+#[macro_export]
+macro_rules! sec_axis {
+    ($($param: ident = $value: expr),* $(,)?) => {
+        $crate::scale::SecAxis::builder()
+            $(.$param($value))*
+            .build()
+            .unwrap()
+    };
+
+    ($transform: expr $(, $($param: ident = $value: expr),+ $(,)?)?) => {
+        $crate::sec_axis!(transform = $transform $(, $($param = $value),+)?)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn continuous_color_map() {
+        let scale = ContinuousColor::builder()
+            .low("black")
+            .high("white")
+            .limits((0.0, 10.0))
+            .build()
+            .unwrap();
+
+        assert_eq!(scale.map(0.0), RGBColor(0, 0, 0));
+        assert_eq!(scale.map(5.0), RGBColor(128, 128, 128));
+        assert_eq!(scale.map(10.0), RGBColor(255, 255, 255));
+        assert_eq!(scale.map(11.0), RGBColor(127, 127, 127));
+        assert_eq!(scale.map(f64::NAN), RGBColor(127, 127, 127));
+    }
+
+    #[test]
+    fn discrete_color_hue() {
+        let mut scale = DiscreteColor::default();
+        scale.train(["b", "c", "a", "b"].map(String::from));
+
+        assert_eq!(scale.levels(), ["a", "b", "c"]);
+        // The default colors of ggplot2 are #F8766D, #00BA38 and #619CFF
+        assert_eq!(scale.map("a"), RGBColor(0xF8, 0x76, 0x6D));
+        assert_eq!(scale.map("b"), RGBColor(0x00, 0xBA, 0x38));
+        assert_eq!(scale.map("c"), RGBColor(0x61, 0x9C, 0xFF));
+        assert_eq!(scale.map("d"), RGBColor(127, 127, 127));
+    }
+
+    #[test]
+    fn continuous_color_gradientn() {
+        let scale = ContinuousColor::builder()
+            .colors(["red", "lime", "blue"])
+            .build()
+            .unwrap();
+
+        assert_eq!(scale.gradient(0.0), RGBColor(255, 0, 0));
+        assert_eq!(scale.gradient(0.5), RGBColor(0, 255, 0));
+        assert_eq!(scale.gradient(0.75), RGBColor(0, 128, 128));
+        assert_eq!(scale.gradient(1.0), RGBColor(0, 0, 255));
+    }
+
+    #[test]
+    fn continuous_color_without_colors() {
+        let result = ContinuousColor::builder()
+            .colors(Vec::<Color>::new())
+            .build();
+        assert!(result.is_err());
+
+        let scale = ContinuousColor::builder()
+            .colors(Vec::<Color>::new())
+            .low("red")
+            .build()
+            .unwrap();
+        assert_eq!(scale.gradient(0.5), RGBColor(255, 0, 0));
+    }
+
+    #[test]
+    fn breaks_resolve() {
+        assert_eq!(Breaks::from(4).resolve(0.0..1.0), [0.0, 0.5, 1.0]);
+        assert_eq!(
+            Breaks::from(vec![5.0, -1.0, 2.0]).resolve(0.0..10.0),
+            [2.0, 5.0]
+        );
+    }
+}
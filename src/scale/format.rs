@@ -0,0 +1,226 @@
+//! # Format module
+//!
+//! Formats turn the breaks of an axis into labels, e.g. `0.25` into `25%`.
+//! They are set with the `labels` of a position scale like [`scale_y_continuous!`](crate::scale_y_continuous!).
+//!
+//! ## Example
+//!
+//! ```
+//! # use gongbi::*;
+//! use gongbi::scale::format;
+//!
+//! let scale = scale_y_continuous!(labels = format::bytes());
+//! assert_eq!(format::bytes().format(1536.0), "1.5 KiB");
+//!
+//! // A custom format from a closure
+//! let scale = scale_y_continuous!(labels = |ms: f64| format!("{ms} ms"));
+//! ```
+
+use std::{fmt, sync::Arc};
+
+/// # Format
+///
+/// A format turns a value into a label.
+///
+/// Besides the built-in formats of this module, any closure `Fn(f64) -> String + Send + Sync`
+/// can be converted into a format.
+#[derive(Clone)]
+pub struct Format(Arc<dyn Fn(f64) -> String + Send + Sync>);
+
+impl Format {
+    /// Create a new [`Format`] from a closure.
+    pub fn new<F>(format: F) -> Self
+    where
+        F: Fn(f64) -> String + Send + Sync + 'static,
+    {
+        Format(Arc::new(format))
+    }
+
+    /// Format a value into a label.
+    pub fn format(&self, value: f64) -> String {
+        (self.0)(value)
+    }
+}
+
+impl<F> From<F> for Format
+where
+    F: Fn(f64) -> String + Send + Sync + 'static,
+{
+    fn from(format: F) -> Self {
+        Format::new(format)
+    }
+}
+
+impl fmt::Debug for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Format").finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for Format {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Format a number with at most `decimals` decimals, without trailing zeros.
+fn trim(value: f64, decimals: usize) -> String {
+    let label = format!("{value:.decimals$}");
+    let label = match label.contains('.') {
+        true => label.trim_end_matches('0').trim_end_matches('.'),
+        false => &label,
+    };
+
+    match label {
+        "-0" => "0".to_string(),
+        label => label.to_string(),
+    }
+}
+
+/// Format a number with a unit prefix, e.g. `1500` with `1000` and `["", "k"]` into `1.5k`.
+fn prefixed(value: f64, base: f64, prefixes: &[&str], separator: &str) -> String {
+    let mut value = value;
+    let mut prefix = 0;
+    while value.abs() >= base && prefix + 1 < prefixes.len() {
+        value /= base;
+        prefix += 1;
+    }
+
+    format!("{}{separator}{}", trim(value, 2), prefixes[prefix])
+}
+
+/// Format the values as percentages, e.g. `0.25` into `25%`.
+pub fn percent() -> Format {
+    Format::new(|value| format!("{}%", trim(value * 100.0, 2)))
+}
+
+/// Format the values with SI prefixes, e.g. `1500` into `1.5k`, `2e6` into `2M` and `3e9` into `3G`.
+pub fn si() -> Format {
+    Format::new(|value| prefixed(value, 1000.0, &["", "k", "M", "G", "T", "P"], ""))
+}
+
+/// Format the values as numbers of bytes with binary prefixes, e.g. `1536` into `1.5 KiB`.
+pub fn bytes() -> Format {
+    Format::new(|value| {
+        prefixed(
+            value,
+            1024.0,
+            &["B", "KiB", "MiB", "GiB", "TiB", "PiB"],
+            " ",
+        )
+    })
+}
+
+/// Format the values with commas between the thousands, e.g. `1234567` into `1,234,567`.
+pub fn comma() -> Format {
+    Format::new(|value| {
+        let label = trim(value, 2);
+        let (sign, label) = match label.strip_prefix('-') {
+            Some(label) => ("-", label),
+            None => ("", label.as_str()),
+        };
+        let (integer, fraction) = label.split_at(label.find('.').unwrap_or(label.len()));
+
+        let mut grouped = String::new();
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                grouped.push(',');
+            }
+            grouped.push(digit);
+        }
+
+        format!("{sign}{grouped}{fraction}")
+    })
+}
+
+/// Format the values in scientific notation, e.g. `1500` into `1.5e+03`.
+pub fn scientific() -> Format {
+    Format::new(|value| {
+        if value == 0.0 {
+            return "0e+00".to_string();
+        }
+
+        let exponent = value.abs().log10().floor() as i32;
+        let mantissa = trim(value / 10f64.powi(exponent), 2);
+        let sign = if exponent < 0 { '-' } else { '+' };
+
+        format!("{mantissa}e{sign}{:02}", exponent.abs())
+    })
+}
+
+/// Format the values as durations in seconds, e.g. `0.25` into `250ms` and `5400` into `1h 30m`.
+pub fn duration() -> Format {
+    Format::new(|seconds| {
+        let sign = if seconds < 0.0 { "-" } else { "" };
+        let seconds = seconds.abs();
+
+        if seconds == 0.0 {
+            return "0s".to_string();
+        }
+        if seconds < 1.0 {
+            return format!("{sign}{}ms", trim(seconds * 1000.0, 2));
+        }
+        if seconds < 60.0 {
+            return format!("{sign}{}s", trim(seconds, 2));
+        }
+
+        // The two largest units, e.g. days and hours
+        let units = [("d", 86400.0), ("h", 3600.0), ("m", 60.0), ("s", 1.0)];
+        let total = seconds.round();
+        let first = units
+            .iter()
+            .position(|&(_, size)| total >= size)
+            .unwrap_or(units.len() - 1);
+
+        let (unit, size) = units[first];
+        let mut label = format!("{sign}{}{unit}", (total / size).floor());
+        if let Some(&(unit, next)) = units.get(first + 1) {
+            let rest = ((total % size) / next).floor();
+            if rest > 0.0 {
+                label.push_str(&format!(" {rest}{unit}"));
+            }
+        }
+
+        label
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_formats() {
+        assert_eq!(percent().format(0.25), "25%");
+        assert_eq!(percent().format(0.125), "12.5%");
+
+        assert_eq!(si().format(950.0), "950");
+        assert_eq!(si().format(1500.0), "1.5k");
+        assert_eq!(si().format(-2e6), "-2M");
+        assert_eq!(si().format(3e9), "3G");
+
+        assert_eq!(bytes().format(512.0), "512 B");
+        assert_eq!(bytes().format(1536.0), "1.5 KiB");
+        assert_eq!(bytes().format(3.0 * 1024.0 * 1024.0 * 1024.0), "3 GiB");
+
+        assert_eq!(comma().format(1234567.0), "1,234,567");
+        assert_eq!(comma().format(-1234.5), "-1,234.5");
+        assert_eq!(comma().format(999.0), "999");
+
+        assert_eq!(scientific().format(1500.0), "1.5e+03");
+        assert_eq!(scientific().format(0.00025), "2.5e-04");
+
+        assert_eq!(duration().format(0.25), "250ms");
+        assert_eq!(duration().format(42.0), "42s");
+        assert_eq!(duration().format(150.0), "2m 30s");
+        assert_eq!(duration().format(5400.0), "1h 30m");
+        assert_eq!(duration().format(86400.0), "1d");
+    }
+
+    #[test]
+    fn closure_format() {
+        let format: Format = (|ms: f64| format!("{ms} ms")).into();
+
+        assert_eq!(format.format(5.0), "5 ms");
+    }
+}